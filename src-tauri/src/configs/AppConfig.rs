@@ -1,4 +1,4 @@
-use crate::consts::{BASE_DIR, CONFIG_NAME, CUSTOM_BIND_LTX, VERSIONS_DIR};
+use crate::consts::{BASE_DIR, CONFIG_NAME, CUSTOM_BIND_LTX, DEFAULT_DOWNLOAD_WORKERS, MAX_DOWNLOAD_WORKERS, MAX_UNPACK_THREADS, VERSIONS_DIR};
 use crate::handlers::dto::ReleaseManifest;
 use crate::utils::patch_markers::InstalledPatch;
 use crate::logger::LogLevel;
@@ -99,6 +99,83 @@ pub struct FileProgress {
   pub is_unpacked: bool,
   pub size: u64,
   pub total_size: u64,
+  /// Expected SHA-256 from the release manifest, None = not verified.
+  #[serde(default)]
+  pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DownloadJobKind {
+  /// Full release; the files live in `progress_download[version_name]`.
+  Release,
+  Patch { patch_name: String },
+  /// Patches installed one after another, in chain order; the ones already
+  /// installed are skipped when the job is resumed.
+  #[serde(rename = "patch_chain")]
+  PatchChain {
+    patches: Vec<String>,
+  },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadJobState {
+  Queued,
+  Running,
+  Paused,
+  Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadJob {
+  pub id: String,
+  pub version_name: String,
+  pub kind: DownloadJobKind,
+  pub state: DownloadJobState,
+  /// Last error of a failed job.
+  #[serde(default)]
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyScheme {
+  #[default]
+  Http,
+  Https,
+  Socks5,
+}
+
+/// Download speed cap for a time-of-day window (local time, "HH:MM").
+/// `to` before `from` spans midnight; `limit` in bytes per second, 0 = unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BandwidthRule {
+  pub from: String,
+  pub to: String,
+  #[serde(default)]
+  pub limit: u64,
+}
+
+/// Outbound proxy for every HTTP client of the launcher (see `utils::proxy`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProxySettings {
+  #[serde(default)]
+  pub enabled: bool,
+  #[serde(default)]
+  pub scheme: ProxyScheme,
+  #[serde(default)]
+  pub host: String,
+  #[serde(default)]
+  pub port: u16,
+  #[serde(default)]
+  pub username: String,
+  /// Stored encoded like the provider tokens (`encode_token`).
+  #[serde(default)]
+  pub password: String,
+  /// Hosts that bypass the proxy, NO_PROXY syntax: `example.org`, `.lan`, `192.168.0.0/16`.
+  #[serde(default)]
+  pub bypass: Vec<String>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -222,6 +299,9 @@ pub struct AppConfig {
   pub installed_versions: HashMap<String, Version>,
   #[serde(default)]
   pub progress_download: HashMap<String, VersionProgress>,
+  /// Download queue in priority order; finished and cancelled jobs are removed.
+  #[serde(default)]
+  pub download_jobs: Vec<DownloadJob>,
   #[serde(default)]
   pub progress_upload: Option<VersionProgressUpload>,
 
@@ -263,6 +343,36 @@ pub struct AppConfig {
   #[serde(default)]
   pub bg_etag: Option<String>,
 
+  /// Directory (local or SMB/NFS mount) served by the local / LAN provider.
+  /// None = local provider is not registered.
+  #[serde(default)]
+  pub local_provider_path: Option<String>,
+
+  /// Web root of a Gitea/Forgejo mirror, overrides `gitea.host` of the
+  /// distribution profile. None = the profile decides. Applied on start.
+  #[serde(default)]
+  pub gitea_host: Option<String>,
+  /// Base url of a static mirror, overrides `mirror.base_url` of the
+  /// distribution profile. None = the profile decides. Applied on start.
+  #[serde(default)]
+  pub mirror_base_url: Option<String>,
+
+  #[serde(default)]
+  pub proxy: ProxySettings,
+
+  /// Files downloaded in parallel; 0 = `DEFAULT_DOWNLOAD_WORKERS`.
+  #[serde(default)]
+  pub download_workers: u8,
+  /// Total download speed cap in bytes per second, 0 = unlimited.
+  #[serde(default)]
+  pub download_speed_limit: u64,
+  /// Overrides `download_speed_limit` inside the windows; the first matching rule wins.
+  #[serde(default)]
+  pub bandwidth_schedule: Vec<BandwidthRule>,
+  /// Threads used to unpack archives; 0 = one per CPU core.
+  #[serde(default)]
+  pub unpack_threads: u8,
+
   // SKIPED PROPS
   #[serde(skip)]
   pub path: String,
@@ -304,9 +414,18 @@ impl Default for AppConfig {
       apply_key_profile: Some(true),
       versions: vec![],
       progress_download: HashMap::new(),
+      download_jobs: vec![],
       tokens: HashMap::new(),
       user_data_cache: None,
       bg_etag: None,
+      local_provider_path: None,
+      gitea_host: None,
+      mirror_base_url: None,
+      proxy: ProxySettings::default(),
+      download_workers: 0,
+      download_speed_limit: 0,
+      bandwidth_schedule: vec![],
+      unpack_threads: 0,
       progress_upload: None,
       choosed_version_path: None,
     }
@@ -318,6 +437,20 @@ impl AppConfig {
     self.apply_key_profile.unwrap_or(self.selected_profile.is_some())
   }
 
+  pub fn download_workers(&self) -> u8 {
+    match self.download_workers {
+      0 => DEFAULT_DOWNLOAD_WORKERS,
+      n => n.min(MAX_DOWNLOAD_WORKERS),
+    }
+  }
+
+  pub fn unpack_threads(&self) -> usize {
+    match self.unpack_threads {
+      0 => std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+      n => n.min(MAX_UNPACK_THREADS) as usize,
+    }
+  }
+
   /// Загружает конфиг из файла. Если файла нет — создаёт новый с first_run = true.
   pub fn load_or_create(app_handle: &tauri::AppHandle) -> Result<Self> {
     let config_dir = app_handle
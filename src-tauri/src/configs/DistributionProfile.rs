@@ -0,0 +1,205 @@
+use std::{fs, path::PathBuf, sync::OnceLock};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri::path::BaseDirectory;
+
+use crate::configs::AppConfig::AppConfig;
+use crate::consts::{
+  BASE_DIR, GITEA_HOST, GITEA_ORG, GITHUB_API_HOST, GITHUB_HOST, GITHUB_INDEX_RAW_URL, GITHUB_LAUNCHER_REPO_NAME, GITHUB_ORG,
+  GITLAB_API_HOST, GITLAB_INDEX_PROJECT_ID, INDEX_REPO_NAME, MAIN_DEVELOPER_NAME, MIRROR_BASE_URL, REPO_LAUNCGER_ID, REPO_LAUNCGER_ID_2,
+};
+
+/// File name of the distribution profile. Looked up next to the launcher
+/// executable first (shipped by a distributor), then in the config directory.
+pub const PROFILE_NAME: &str = "profile.json";
+
+static PROFILE: OnceLock<DistributionProfile> = OnceLock::new();
+
+/// Everything that ties the launcher to one set of releases: where the
+/// release repos, the launcher repo and the release indexes live, plus
+/// branding. The built-in default is the Global War distribution; a sister
+/// project ships its own `profile.json` (any omitted field keeps the default).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistributionProfile {
+  /// Display name of the distribution (window title, UI branding).
+  pub name: String,
+  /// Repo the writer commits `index.json` to on GitHub/Gitea (GitLab uses `gitlab.index_project_id`).
+  pub index_repo_name: String,
+  pub github: GithubProfile,
+  pub gitlab: GitlabProfile,
+  pub gitea: GiteaProfile,
+  pub mirror: MirrorProfile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GithubProfile {
+  pub web_host: String,
+  pub api_host: String,
+  /// Organization with the release repos.
+  pub org: String,
+  /// Owner of the launcher repo; also the author of the manifest / user issues.
+  pub launcher_owner: String,
+  pub launcher_repo: String,
+  /// Launcher background inside the launcher repo.
+  pub bg_path: String,
+  pub index_raw_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitlabProfile {
+  pub api_host: String,
+  /// Project with the launcher data (bg, manifest and user issues).
+  pub launcher_project_id: u32,
+  /// Project with the launcher releases (self-update).
+  pub launcher_release_project_id: u32,
+  /// Launcher background inside the launcher project.
+  pub bg_path: String,
+  /// Project with `index.json`; 0 = no GitLab index.
+  pub index_project_id: u32,
+  /// Root group with the release groups.
+  pub root_group_id: u32,
+  /// Max size of a release repo in bytes.
+  pub max_repo_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GiteaProfile {
+  /// Web root of the Gitea/Forgejo instance; empty = provider not registered.
+  pub host: String,
+  /// Organization with the release repos and the mirrored launcher repo.
+  pub org: String,
+  pub launcher_repo: String,
+  pub bg_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MirrorProfile {
+  /// Base url of the static mirror; empty = provider not registered.
+  pub base_url: String,
+  /// Extra download hosts for absolute asset urls in the mirror index
+  /// (CDN, object storage), e.g. `cdn.example.org`.
+  pub allowed_hosts: Vec<String>,
+}
+
+impl Default for DistributionProfile {
+  fn default() -> Self {
+    Self {
+      name: "Global War".to_owned(),
+      index_repo_name: INDEX_REPO_NAME.to_owned(),
+      github: GithubProfile::default(),
+      gitlab: GitlabProfile::default(),
+      gitea: GiteaProfile::default(),
+      mirror: MirrorProfile::default(),
+    }
+  }
+}
+
+impl Default for GithubProfile {
+  fn default() -> Self {
+    Self {
+      web_host: GITHUB_HOST.to_owned(),
+      api_host: GITHUB_API_HOST.to_owned(),
+      org: GITHUB_ORG.to_owned(),
+      launcher_owner: MAIN_DEVELOPER_NAME.to_owned(),
+      launcher_repo: GITHUB_LAUNCHER_REPO_NAME.to_owned(),
+      bg_path: "src/static/bg.jpg".to_owned(),
+      index_raw_url: GITHUB_INDEX_RAW_URL.to_owned(),
+    }
+  }
+}
+
+impl Default for GitlabProfile {
+  fn default() -> Self {
+    Self {
+      api_host: GITLAB_API_HOST.to_owned(),
+      launcher_project_id: REPO_LAUNCGER_ID,
+      launcher_release_project_id: REPO_LAUNCGER_ID_2,
+      bg_path: "data/bg/bg.jpg".to_owned(),
+      index_project_id: GITLAB_INDEX_PROJECT_ID,
+      root_group_id: 122475050,
+      max_repo_size: 10200547328,
+    }
+  }
+}
+
+impl Default for GiteaProfile {
+  fn default() -> Self {
+    Self {
+      host: GITEA_HOST.to_owned(),
+      org: GITEA_ORG.to_owned(),
+      launcher_repo: GITHUB_LAUNCHER_REPO_NAME.to_owned(),
+      bg_path: "src/static/bg.jpg".to_owned(),
+    }
+  }
+}
+
+impl Default for MirrorProfile {
+  fn default() -> Self {
+    Self {
+      base_url: MIRROR_BASE_URL.to_owned(),
+      allowed_hosts: vec![],
+    }
+  }
+}
+
+/// Active profile. Falls back to the built-in default when `init` was not
+/// called (or found no profile file).
+pub fn profile() -> &'static DistributionProfile {
+  PROFILE.get_or_init(DistributionProfile::default)
+}
+
+fn candidate_paths(app_handle: &tauri::AppHandle) -> Vec<PathBuf> {
+  let mut paths = vec![];
+
+  if let Some(exe_dir) = std::env::current_exe().ok().and_then(|p| p.parent().map(|d| d.to_path_buf())) {
+    paths.push(exe_dir.join(PROFILE_NAME));
+  }
+  if let Some(config_dir) = app_handle
+    .path()
+    .resolve(BASE_DIR, BaseDirectory::AppConfig)
+    .ok()
+    .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+  {
+    paths.push(config_dir.join(PROFILE_NAME));
+  }
+
+  paths
+}
+
+/// Call once during `tauri_setup`, before the providers are registered.
+/// A broken profile file is an error: silently falling back to the default
+/// would point a rebranded launcher at someone else's releases. Mirror hosts
+/// the player set in the launcher config override the profile ones.
+pub fn init(app_handle: &tauri::AppHandle, config: &AppConfig) -> Result<()> {
+  let mut loaded = DistributionProfile::default();
+
+  if let Some(path) = candidate_paths(app_handle).into_iter().find(|p| p.is_file()) {
+    let content = fs::read_to_string(&path).with_context(|| format!("Cannot read distribution profile {:?}", &path))?;
+    loaded = serde_json::from_str(&content).with_context(|| format!("Invalid distribution profile {:?}", &path))?;
+    log::info!("Distribution profile '{}' loaded from {:?}", &loaded.name, &path);
+  } else {
+    log::info!("No {} found, using the built-in distribution profile", PROFILE_NAME);
+  }
+
+  if let Some(host) = config.gitea_host.as_deref().filter(|h| !h.is_empty()) {
+    log::info!("Gitea host '{}' set in the launcher config", host);
+    loaded.gitea.host = host.to_owned();
+  }
+  if let Some(base_url) = config.mirror_base_url.as_deref().filter(|u| !u.is_empty()) {
+    log::info!("Static mirror '{}' set in the launcher config", base_url);
+    loaded.mirror.base_url = base_url.to_owned();
+  }
+
+  PROFILE
+    .set(loaded)
+    .map_err(|_| anyhow::anyhow!("Distribution profile already initialised"))?;
+
+  Ok(())
+}
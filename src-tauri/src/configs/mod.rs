@@ -1,4 +1,5 @@
 pub mod AppConfig;
+pub mod DistributionProfile;
 pub mod GameConfig;
 
 pub use AppConfig::RunParams;
@@ -8,7 +8,22 @@ pub const GITHUB_LAUNCHER_REPO_NAME: &str = "launcher-gw";
 
 pub const GITLAB_API_HOST: &str = "https://gitlab.com/api/v4";
 
+/// Web root of the self-hosted Gitea/Forgejo mirror (API lives under `/api/v1`).
+/// Empty = set by the distribution profile or `AppConfig.gitea_host`; when
+/// none of them does, the provider is not registered.
+pub const GITEA_HOST: &str = "";
+/// Organization on the Gitea/Forgejo mirror holding the release repos and the launcher repo.
+pub const GITEA_ORG: &str = "Global-War-Releases";
+
+/// Base URL of a plain static HTTP mirror serving `index.json`, `bg.jpg` and
+/// the copied release files. Empty = set by the distribution profile or
+/// `AppConfig.mirror_base_url`; when none of them does, the provider is not registered.
+pub const MIRROR_BASE_URL: &str = "";
+
 pub const MANIFEST_NAME: &str = "manifest.json";
+/// Description of an offline install bundle, at the bundle root.
+pub const BUNDLE_INFO_NAME: &str = "bundle.json";
+pub const BUNDLE_SCHEMA_VERSION: u32 = 1;
 pub const VERSIONS_DIR: &str = "versions";
 
 pub const EXE_WIN_NAME: &str = "Launcher.exe";
@@ -32,8 +47,33 @@ pub const CUSTOM_BIND_LTX: &str = "custom.ltx";
 // Providers ids
 pub const GITLAB_PID: &str = "gitlab";
 pub const GITHUB_PID: &str = "github";
+pub const GITEA_PID: &str = "gitea";
+pub const MIRROR_PID: &str = "mirror";
+pub const LOCAL_PID: &str = "local";
 
-pub const PULL_FILES_SIZE: u8 = 1;
+/// Parallel download workers when the user did not choose (`AppConfig.download_workers` = 0).
+pub const DEFAULT_DOWNLOAD_WORKERS: u8 = 3;
+pub const MAX_DOWNLOAD_WORKERS: u8 = 8;
+/// Upper bound for `AppConfig.unpack_threads`.
+pub const MAX_UNPACK_THREADS: u8 = 64;
+/// Connections per file for segmented downloads (CDNs cap per-connection speed).
+pub const DOWNLOAD_SEGMENTS: usize = 4;
+/// Smaller files are fetched over a single connection.
+pub const SEGMENT_MIN_SIZE: u64 = 64 * 1024 * 1024;
+/// A download stream that delivers no bytes for this long is reconnected from its resume point.
+pub const DOWNLOAD_STALL_TIMEOUT_SECS: u64 = 30;
+/// Consecutive failures on one source before a worker looks for another provider.
+pub const FAILOVER_AFTER_RETRIES: u32 = 3;
+/// Release downloads running at the same time; the rest wait in the queue.
+pub const MAX_ACTIVE_DOWNLOAD_JOBS: usize = 1;
+/// Patch installs running next to the release downloads, so a small patch
+/// does not wait behind a multi-GB release of another version.
+pub const MAX_ACTIVE_PATCH_JOBS: usize = 1;
+/// Patch manifests read at the same time when the patch list comes from the
+/// provider API (no release index): an updates repo may have hundreds of releases.
+pub const PATCH_MANIFEST_FETCH_CONCURRENCY: usize = 4;
+/// Kept free on every volume a download or install writes to.
+pub const DISK_SPACE_RESERVE: u64 = 256 * 1024 * 1024;
 
 // Static release index (player-side, raw CDN — not counted against API rate limit)
 // Per-provider: each provider gets its own index with provider-specific URLs.
@@ -64,15 +104,28 @@ pub const CACHE_TTL_RAW_FILE_SECS: u64 = 600; // 10 min
 /// Launcher background image.
 pub const CACHE_TTL_BACKGROUND_SECS: u64 = 86400; // 24 hours
 
+/// Longest back-off of a background job when the GitHub API quota is
+/// exhausted. A reset further away fails the call with a quota error;
+/// interactive calls never wait.
+pub const GITHUB_RATE_LIMIT_MAX_WAIT_SECS: u64 = 300; // 5 min
+
+/// Interval of the background provider health checks.
+pub const HEALTH_CHECK_INTERVAL_SECS: u64 = 60;
+/// Checks kept per provider for the rolling latency / error statistics.
+pub const HEALTH_WINDOW: usize = 10;
+/// Failed checks in a row before the current provider is switched away from.
+pub const HEALTH_FAILS_BEFORE_SWITCH: u32 = 2;
+/// Good checks in a row before switching back to the provider saved by the user.
+pub const HEALTH_OKS_BEFORE_RESTORE: u32 = 3;
+
 // User data cache TTLs (persisted in config.json, separate from http_cache).
 /// Positive cache hit (user found with flags).
 pub const USER_CACHE_TTL_POSITIVE_SECS: u64 = 86400; // 24 hours
 /// Negative cache hit (no issue found).
 pub const USER_CACHE_TTL_NEGATIVE_SECS: u64 = 21600; // 6 hours
 
-/// Default git branch used when uploading the manifest and creating a tag.
-/// TODO: this is a temporary crutch. The correct fix is to fetch the repo's
-/// default branch from the provider and thread it through `add_file_to_repo` /
-/// `create_tag` / `create_release` (which currently hardcode "master" on the
-/// provider side too — see Github::__create_release `target_commitish`).
+/// Fallback git branch for uploads, tags and index commits when the repo's
+/// default branch cannot be fetched (`ApiProvider::get_default_branch`), and
+/// for empty GitLab projects that have no default branch yet. Raw reads on
+/// GitLab / Gitea use it as is, without a lookup.
 pub const DEFAULT_BRANCH: &str = "master";
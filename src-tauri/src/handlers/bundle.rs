@@ -0,0 +1,79 @@
+use std::{path::Path, sync::Arc};
+
+use tauri::Emitter;
+use tokio::sync::{Mutex, broadcast};
+
+use crate::{
+  configs::AppConfig::AppConfig,
+  handlers::upload_v2::UploadCancelMap,
+  service::{bundle, files::ServiceFiles, main::Service, unpack::ServiceUnpacker},
+  utils::errors::log_full_error,
+};
+
+const EVT_BUNDLE_LOG: &str = "bundle-log";
+
+fn cancel_key(version_name: &str) -> String {
+  format!("bundle-export:{}", version_name)
+}
+
+/// Exports a release and its patch chain for offline installs. `path` is a
+/// directory, or a single container file when it ends with `.zip`.
+#[tauri::command]
+pub async fn export_bundle(
+  app: tauri::AppHandle,
+  cancel_map: tauri::State<'_, UploadCancelMap>,
+  service: tauri::State<'_, Arc<Mutex<Service>>>,
+  service_files: tauri::State<'_, Arc<ServiceFiles>>,
+  versionName: String,
+  path: String,
+) -> Result<(), String> {
+  let key = cancel_key(&versionName);
+  if crate::utils::locks::lock(&cancel_map).contains_key(&key) {
+    return Err("BUNDLE_EXPORT_ALREADY_RUNNING".to_string());
+  }
+  let (cancel_tx, _) = broadcast::channel::<()>(1);
+  crate::utils::locks::lock(&cancel_map).insert(key.clone(), cancel_tx.clone());
+  scopeguard::defer! { crate::utils::locks::lock(&cancel_map).remove(&key); };
+
+  let api_client = {
+    let service_guard = service.lock().await;
+    service_guard.api_client.clone()
+  };
+
+  bundle::export_bundle(&api_client, &service_files, &versionName, Path::new(&path), &cancel_tx, |msg| {
+    let _ = app.emit(EVT_BUNDLE_LOG, msg);
+  })
+  .await
+  .map_err(|e| {
+    log_full_error(&e);
+    e.to_string()
+  })
+}
+
+#[tauri::command]
+pub async fn cancel_export_bundle(cancel_map: tauri::State<'_, UploadCancelMap>, versionName: String) -> Result<(), String> {
+  if let Some(tx) = crate::utils::locks::lock(&cancel_map).get(&cancel_key(&versionName)) {
+    let _ = tx.send(());
+  }
+  Ok(())
+}
+
+/// Installs a bundle under `installRoot` (the default install dir); returns the
+/// name of the installed version.
+#[tauri::command]
+pub async fn import_bundle(
+  app: tauri::AppHandle,
+  app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+  service_unpack: tauri::State<'_, Arc<ServiceUnpacker>>,
+  path: String,
+  installRoot: String,
+) -> Result<String, String> {
+  bundle::import_bundle(&app, &app_config, &service_unpack, Path::new(&path), Path::new(&installRoot), |msg| {
+    let _ = app.emit(EVT_BUNDLE_LOG, msg);
+  })
+  .await
+  .map_err(|e| {
+    log_full_error(&e);
+    e.to_string()
+  })
+}
@@ -1,6 +1,7 @@
 use crate::consts::MANIFEST_NAME;
-use crate::handlers::dto::{CompressProgressPayload, PatchMeta, ReleaseManifest, ReleaseManifestFile};
+use crate::handlers::dto::{CompressProgressPayload, PatchMeta, ReleaseManifest, ReleaseManifestEntry, ReleaseManifestFile};
 use crate::utils::CountingWriter::CountingWriter;
+use crate::utils::hash::{copy_sha256, sha256_file};
 use anyhow::Result;
 use globset::{Glob, GlobSetBuilder};
 use std::fs::{self, File};
@@ -116,6 +117,9 @@ fn pack_split_archives_blocking(
     base_patch: None,
     base_release_tag: None,
     deleted_files: vec![],
+    entries: vec![],
+    deltas: vec![],
+    supersedes: vec![],
   };
 
   // Optional launcher exe (e.g. Stalker-CoC.exe) recorded in the manifest as a
@@ -173,8 +177,13 @@ fn pack_split_archives_blocking(
       let meta = file_path.metadata().map_err(|e| e.to_string())?;
       let size = meta.len();
       compressed_size += size;
+      let sha256 = sha256_file(&file_path).map_err(|e| e.to_string())?;
 
-      manifest.files.push(ReleaseManifestFile { name: archive_name, size });
+      manifest.files.push(ReleaseManifestFile {
+        name: archive_name,
+        size,
+        sha256: Some(sha256),
+      });
 
       part_number += 1;
       let archive_path = out_dir.join(format!("data{}.zip", part_number));
@@ -207,7 +216,14 @@ fn pack_split_archives_blocking(
 
     let f = std::fs::File::open(&full_path).map_err(|e| e.to_string())?;
     let mut reader = std::io::BufReader::new(f);
-    std::io::copy(&mut reader, &mut zip).map_err(|e| e.to_string())?;
+    // Hashed while compressing: verify/repair of installs compare against it.
+    let sha256 = copy_sha256(&mut reader, &mut zip).map_err(|e| e.to_string())?;
+    manifest.entries.push(ReleaseManifestEntry {
+      path: str_file_name,
+      size,
+      sha256,
+      archive: format!("data{}.zip", part_number),
+    });
 
     processed_size += size;
     current_group_size += size;
@@ -220,10 +236,14 @@ fn pack_split_archives_blocking(
   let meta = file_path.metadata().map_err(|e| e.to_string())?;
   let size = meta.len();
   compressed_size += size;
+  // Re-read after `finish()`: the zip writer seeks back to patch headers, so
+  // the bytes cannot be hashed on the fly.
+  let sha256 = sha256_file(&file_path).map_err(|e| e.to_string())?;
 
   manifest.files.push(ReleaseManifestFile {
     name: archive_name,
     size,
+    sha256: Some(sha256),
   });
 
   manifest.compressed_size = compressed_size;
@@ -234,6 +254,8 @@ fn pack_split_archives_blocking(
     manifest.base_patch = pm.base_patch.clone().filter(|s| !s.is_empty());
     manifest.base_release_tag = pm.base_release_tag.clone().filter(|s| !s.is_empty());
     manifest.deleted_files = pm.deleted_files.clone();
+    manifest.deltas = pm.deltas.clone();
+    manifest.supersedes = pm.supersedes.clone();
   }
 
   let manifest_path = Path::new(&targetPath).join(MANIFEST_NAME).to_string_lossy().into_owned();
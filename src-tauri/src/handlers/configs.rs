@@ -3,12 +3,19 @@ use tauri::Manager;
 use tokio::sync::Mutex;
 
 use crate::{
-  configs::{AppConfig::AppConfig, RunParams},
-  consts::MANIFEST_NAME,
+  configs::{
+    AppConfig::{AppConfig, BandwidthRule, ProxySettings},
+    DistributionProfile::{DistributionProfile, profile},
+    RunParams,
+  },
+  consts::{LOCAL_PID, MANIFEST_NAME, MAX_DOWNLOAD_WORKERS, MAX_UNPACK_THREADS},
   handlers,
-  providers::dto::ProviderStatus,
-  service::main::Service,
-  utils::encoding::{decode_token, mask_token},
+  providers::dto::{ProviderHealth, ProviderStatus},
+  service::{files::ServiceFiles, health_monitor::HealthMap, main::Service, unpack::ServiceUnpacker},
+  utils::{
+    encoding::{decode_token, encode_token, mask_token},
+    http_cache, proxy,
+  },
 };
 
 #[tauri::command]
@@ -18,6 +25,7 @@ pub async fn get_config(app: tauri::AppHandle) -> Result<AppConfig, String> {
   // Never send provider tokens to the webview via get_config.
   let mut cfg = config_guard.clone();
   cfg.tokens.clear();
+  cfg.proxy.password.clear();
   Ok(cfg)
 }
 
@@ -124,6 +132,42 @@ pub async fn get_tokens(app: tauri::AppHandle) -> Result<HashMap<String, String>
   Ok(masked_tokens)
 }
 
+/// Parallel download workers (0 = default), the total speed cap in bytes
+/// per second (0 = unlimited) and the time-of-day rules overriding it. The
+/// caps apply to running downloads right away, the worker count from the
+/// next start/resume.
+#[tauri::command]
+pub async fn set_download_settings(
+  app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+  service_files: tauri::State<'_, Arc<ServiceFiles>>,
+  service_unpack: tauri::State<'_, Arc<ServiceUnpacker>>,
+  workers: u8,
+  speedLimit: u64,
+  schedule: Vec<BandwidthRule>,
+  unpackThreads: u8,
+) -> Result<(), String> {
+  if workers > MAX_DOWNLOAD_WORKERS {
+    return Err(format!("At most {} download workers are allowed", MAX_DOWNLOAD_WORKERS));
+  }
+  if unpackThreads > MAX_UNPACK_THREADS {
+    return Err(format!("At most {} unpack threads are allowed", MAX_UNPACK_THREADS));
+  }
+
+  // Validates the rules before anything is saved.
+  service_files.set_bandwidth(speedLimit, &schedule).map_err(|e| e.to_string())?;
+
+  let mut config_guard = app_config.lock().await;
+
+  config_guard.download_workers = workers;
+  config_guard.download_speed_limit = speedLimit;
+  config_guard.bandwidth_schedule = schedule;
+  config_guard.unpack_threads = unpackThreads;
+  config_guard.save().map_err(|e| e.to_string())?;
+  service_unpack.set_threads(config_guard.unpack_threads());
+
+  Ok(())
+}
+
 #[tauri::command]
 pub async fn set_default_install_path(app: tauri::AppHandle, path: String) -> Result<(), String> {
   let state = app.try_state::<Arc<Mutex<AppConfig>>>().ok_or("Config not initialized")?;
@@ -178,12 +222,185 @@ pub async fn set_current_api_provider(
   Ok(())
 }
 
+/// Active distribution profile (branding and release locations) for the UI.
+#[tauri::command]
+pub async fn get_distribution_profile() -> Result<DistributionProfile, String> {
+  Ok(profile().clone())
+}
+
 #[tauri::command]
 pub async fn get_api_providers_stats(service: tauri::State<'_, Arc<Mutex<Service>>>) -> Result<Vec<(&'static str, ProviderStatus)>, String> {
   let stats = {
     let service_guard = service.lock().await;
-    service_guard.stats.clone()
+    // Ping results are a snapshot; the API quota changes with every call, take it live.
+    service_guard
+      .stats
+      .iter()
+      .map(|(id, stat)| {
+        let mut stat = stat.clone();
+        if let Ok(live) = service_guard.api_client.get_status(id) {
+          stat.rate_limit = live.rate_limit;
+        }
+        (*id, stat)
+      })
+      .collect::<Vec<_>>()
+  };
+
+  Ok(stats)
+}
+
+/// Rolling health statistics of the providers from the background monitor.
+#[tauri::command]
+pub async fn get_providers_health(health: tauri::State<'_, HealthMap>) -> Result<Vec<(&'static str, ProviderHealth)>, String> {
+  let health = crate::utils::locks::lock(&health);
+  Ok(health.iter().map(|(id, samples)| (*id, samples.summary())).collect())
+}
+
+/// Sets (or clears with `None`) the directory of the local / LAN provider and
+/// re-registers it right away. Returns the fresh ping status of the provider.
+#[tauri::command]
+pub async fn set_local_provider_path(
+  app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+  service: tauri::State<'_, Arc<Mutex<Service>>>,
+  path: Option<String>,
+) -> Result<Option<ProviderStatus>, String> {
+  let path = path.filter(|p| !p.trim().is_empty());
+  if let Some(ref p) = path
+    && !Path::new(p).is_dir()
+  {
+    return Err(format!("Directory not found: {}", p));
+  }
+
+  {
+    let mut config_guard = app_config.lock().await;
+
+    config_guard.local_provider_path = path.clone();
+    config_guard.save().map_err(|e| e.to_string())?;
+  }
+
+  let (was_current, api_client) = {
+    let mut service_guard = service.lock().await;
+    let was_current = service_guard
+      .api_client
+      .current_provider()
+      .map(|p| p.id() == LOCAL_PID)
+      .unwrap_or(false);
+
+    service_guard.register_local(path.as_deref()).map_err(|e| e.to_string())?;
+    (was_current, service_guard.api_client.clone())
+  };
+
+  // A disconnected share can make the ping hang: not under the Service lock.
+  let status = match api_client.get_provider(LOCAL_PID) {
+    Ok(provider) => Some(provider.ping().await),
+    Err(_) => None,
   };
 
+  let mut service_guard = service.lock().await;
+  service_guard.stats.retain(|(id, _)| *id != LOCAL_PID);
+  if let Some(ref s) = status {
+    service_guard.stats.push((LOCAL_PID, s.clone()));
+  }
+
+  // Re-registering drops the current selection when it was the local provider;
+  // it stays on it only while the new folder answers.
+  if was_current {
+    let next = if status.as_ref().is_some_and(|s| s.available) {
+      Some(LOCAL_PID)
+    } else {
+      service_guard.stats.iter().find(|(_, s)| s.available).map(|(id, _)| *id)
+    };
+    if let Some(id) = next {
+      service_guard.api_client.set_current_provider(id).map_err(|e| e.to_string())?;
+    }
+  }
+
+  Ok(status)
+}
+
+/// Trimmed http(s) url of a mirror, None for an empty value.
+fn mirror_url(value: Option<String>, what: &str) -> Result<Option<String>, String> {
+  let value = value.map(|v| v.trim().trim_end_matches('/').to_owned()).filter(|v| !v.is_empty());
+  if let Some(ref v) = value {
+    let url = url::Url::parse(v).map_err(|e| format!("Invalid {} '{}': {}", what, v, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+      return Err(format!("{} must be an http(s) url: {}", what, v));
+    }
+  }
+  Ok(value)
+}
+
+/// Sets (or clears with `None`) the web root of the Gitea/Forgejo mirror.
+/// The providers and the index reader work off the distribution profile
+/// fixed at start, so the new host is used after a restart.
+#[tauri::command]
+pub async fn set_gitea_host(app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>, host: Option<String>) -> Result<(), String> {
+  let host = mirror_url(host, "Gitea host")?;
+
+  let mut config_guard = app_config.lock().await;
+  config_guard.gitea_host = host;
+  config_guard.save().map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+/// Sets (or clears with `None`) the base url of the static mirror; used
+/// after a restart like `set_gitea_host`.
+#[tauri::command]
+pub async fn set_mirror_base_url(app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>, base_url: Option<String>) -> Result<(), String> {
+  let base_url = mirror_url(base_url, "Mirror url")?;
+
+  let mut config_guard = app_config.lock().await;
+  config_guard.mirror_base_url = base_url;
+  config_guard.save().map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+/// Saves the proxy settings and rebuilds every HTTP client with them.
+/// `password` = None keeps the stored one (the webview never gets it back).
+/// Returns the fresh ping status of the providers through the new route.
+#[tauri::command]
+pub async fn set_proxy_settings(
+  app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+  service: tauri::State<'_, Arc<Mutex<Service>>>,
+  proxy: ProxySettings,
+  password: Option<String>,
+) -> Result<Vec<(&'static str, ProviderStatus)>, String> {
+  let mut settings = proxy;
+  settings.host = settings.host.trim().to_owned();
+  settings.bypass = settings.bypass.iter().map(|h| h.trim().to_owned()).filter(|h| !h.is_empty()).collect();
+
+  {
+    let mut config_guard = app_config.lock().await;
+
+    settings.password = match password {
+      Some(p) => encode_token(&p),
+      None => config_guard.proxy.password.clone(),
+    };
+    // Rejected before anything is saved: a config no client can be built
+    // with would otherwise take the providers down on the next start.
+    proxy::validate(&settings).map_err(|e| format!("{:#}", e))?;
+
+    config_guard.proxy = settings.clone();
+    config_guard.save().map_err(|e| e.to_string())?;
+  }
+
+  proxy::set(&settings).map_err(|e| format!("{:#}", e))?;
+  http_cache::rebuild_shared_client().map_err(|e| format!("{:#}", e))?;
+
+  let api_client = {
+    let service_guard = service.lock().await;
+    service_guard.api_client.rebuild_clients().map_err(|e| e.to_string())?;
+    service_guard.api_client.clone()
+  };
+
+  log::info!("Proxy settings changed (enabled: {}), clients rebuilt", settings.enabled);
+
+  // Pinged on a clone (provider statuses are shared): a dead proxy must not
+  // block every other command until the pings time out.
+  let stats = api_client.ping_all().await;
+  service.lock().await.stats = stats.clone();
+
   Ok(stats)
 }
@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use crate::{configs::AppConfig::DownloadJob, service::download_jobs::DownloadJobs};
+
+#[tauri::command]
+pub async fn get_download_jobs(jobs: tauri::State<'_, Arc<DownloadJobs>>) -> Result<Vec<DownloadJob>, String> {
+  Ok(jobs.list())
+}
+
+#[tauri::command]
+pub async fn pause_download_job(jobs: tauri::State<'_, Arc<DownloadJobs>>, jobId: String) -> Result<(), String> {
+  jobs.pause(&jobId).await
+}
+
+#[tauri::command]
+pub async fn resume_download_job(jobs: tauri::State<'_, Arc<DownloadJobs>>, jobId: String) -> Result<(), String> {
+  jobs.resume(&jobId).await
+}
+
+/// Drops the job from the queue; downloaded files and progress stay on disk.
+#[tauri::command]
+pub async fn cancel_download_job(jobs: tauri::State<'_, Arc<DownloadJobs>>, jobId: String) -> Result<(), String> {
+  jobs.cancel(&jobId).await
+}
+
+/// Moves a job to `position` in the queue (0 = next to run).
+#[tauri::command]
+pub async fn move_download_job(jobs: tauri::State<'_, Arc<DownloadJobs>>, jobId: String, position: usize) -> Result<(), String> {
+  jobs.reorder(&jobId, position).await
+}
@@ -50,6 +50,10 @@ pub struct ReleaseManifestFile {
   pub name: String,
   #[serde(default)]
   pub size: u64,
+  /// Hex SHA-256 of the archive. None for manifests packed before hashes were
+  /// recorded (and for `manifest.json` itself): such files are not verified.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sha256: Option<String>,
 }
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ReleaseManifest {
@@ -77,6 +81,46 @@ pub struct ReleaseManifest {
   /// Files to delete when applying the patch, relative to the game root.
   #[serde(default)]
   pub deleted_files: Vec<String>,
+  /// Every packed game file; empty for manifests packed before the list was
+  /// recorded (such installs cannot be verified).
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub entries: Vec<ReleaseManifestEntry>,
+  /// Modified files a patch ships as binary deltas.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub deltas: Vec<PatchDelta>,
+  /// Cumulative patch: the patch tags it replaces, in chain order. It applies
+  /// on top of the full release and leaves the install at the last of them.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub supersedes: Vec<String>,
+}
+
+/// A modified file shipped as a binary delta (see `utils::delta`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchDelta {
+  /// Relative to the game root, `/`-separated.
+  pub path: String,
+  /// Size and hex SHA-256 of the file the delta was made against.
+  pub base_size: u64,
+  pub base_sha256: String,
+  /// Size and hex SHA-256 of the patched file.
+  pub size: u64,
+  pub sha256: String,
+  /// `fullN.zip` holding the whole patched file, downloaded only when the
+  /// local file differs from the base. Filled in when the patch is packed.
+  #[serde(default)]
+  pub archive: String,
+}
+
+/// A game file inside the release archives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseManifestEntry {
+  /// Relative to the game root, `/`-separated.
+  pub path: String,
+  pub size: u64,
+  /// Hex SHA-256 of the file content.
+  pub sha256: String,
+  /// `dataN.zip` holding the file.
+  pub archive: String,
 }
 
 /// Patch metadata passed to the packer when building a patch upload.
@@ -88,6 +132,10 @@ pub struct PatchMeta {
   pub base_release_tag: Option<String>,
   #[serde(default)]
   pub deleted_files: Vec<String>,
+  #[serde(default)]
+  pub deltas: Vec<PatchDelta>,
+  #[serde(default)]
+  pub supersedes: Vec<String>,
 }
 
 #[derive(Clone, Serialize)]
@@ -115,3 +163,20 @@ pub struct UnzipTask {
   pub archive_path: PathBuf,
   pub destination_path: PathBuf,
 }
+
+/// Runtime switch of the current provider by the health monitor (`provider-switched` event).
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderSwitch {
+  pub from: Option<String>,
+  pub to: String,
+  /// "unavailable" (current provider went down) or "restored" (back to the saved one).
+  pub reason: String,
+}
+
+/// Provider currently serving a file of a download (`download-file-source` event).
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadFileSource {
+  pub version_name: String,
+  pub file_name: String,
+  pub provider_id: String,
+}
@@ -1,8 +1,10 @@
 pub mod register;
 
+pub mod bundle;
 pub mod compress;
 pub mod configs;
 pub mod continue_download_version;
+pub mod download_jobs;
 pub mod dto;
 pub mod logger;
 pub mod patch_install;
@@ -17,4 +19,5 @@ pub mod system;
 pub mod updater;
 pub mod upload_v2;
 pub mod user_ltx;
+pub mod verify;
 pub mod window;
@@ -1,24 +1,34 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, Mutex as StdMutex};
 
 use anyhow::{Context, Result, bail};
-use futures_util::StreamExt;
+use futures_util::{StreamExt, TryStreamExt, stream};
 use serde::Serialize;
 use tauri::Emitter;
 use tokio::sync::{Mutex, broadcast};
 
-use crate::configs::AppConfig::AppConfig;
-use crate::utils::patch_markers::{InstalledPatch, read_installed_patches, write_patch_marker};
-use crate::consts::MANIFEST_NAME;
+use crate::configs::AppConfig::{AppConfig, DownloadJob, DownloadJobKind, DownloadJobState};
+use crate::utils::patch_markers::{InstalledPatch, read_installed_patches, remove_patch_marker, write_patch_marker};
+use crate::consts::{MANIFEST_NAME, PATCH_MANIFEST_FETCH_CONCURRENCY};
 use crate::handlers::dto::ReleaseManifest;
-use crate::handlers::upload_v2::UploadCancelMap;
 use crate::providers::ApiClient::ApiClient::ApiClient;
 use crate::providers::ApiProvider::ApiProvider;
-use crate::providers::dto::Project;
-use crate::service::files::{DownloadOutcome, ServiceFiles};
+use crate::providers::dto::{Project, RepoReleaseAsset, RepoReleaseInfo};
+use crate::service::disk_space::{patch_chain_plan, patch_plan};
+use crate::service::download_jobs::{DownloadContext, DownloadJobs};
+use crate::service::download_pipeline::emit_file_source;
+use crate::service::failover::{DownloadSource, next_source};
+use crate::service::files::DownloadOutcome;
+use crate::service::index::IndexPatch;
 use crate::service::main::Service;
+use crate::service::part_file;
+use crate::service::patch_backup;
+use crate::service::patch_delta;
+use crate::service::retry::RetryCounter;
 use crate::service::unpack::ServiceUnpacker;
+use crate::utils::delta::DELTA_DIR;
 use crate::utils::errors::log_full_error;
 
 /// Patch-related events emitted to the frontend.
@@ -26,12 +36,18 @@ const EVT_PATCHES_AVAILABLE: &str = "patches-available";
 const EVT_INSTALL_PROGRESS: &str = "patch-install-progress";
 const EVT_INSTALL_LOG: &str = "patch-install-log";
 
+/// `supersedes` of already read manifests, by manifest URL. A published patch
+/// never changes its manifest, so each one is downloaded once per session.
+static SUPERSEDES_CACHE: LazyLock<StdMutex<HashMap<String, Vec<String>>>> = LazyLock::new(|| StdMutex::new(HashMap::new()));
+
 #[derive(Debug, Clone, Serialize)]
 pub struct PatchInfo {
   pub name: String,
   pub notes: Option<String>,
   pub size: Option<u64>,
   pub is_next: bool,
+  /// Cumulative patch: the patch tags it replaces.
+  pub supersedes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -98,12 +114,12 @@ pub fn project_id_for(api_client: &ApiClient, project: &Project) -> Result<Strin
 }
 
 // ---------------------------------------------------------------------------
-// Lightweight auto-check: count available patches without downloading manifests.
+// Lightweight auto-check: count the patches still to install.
 // Used at startup for the update badge.
 // ---------------------------------------------------------------------------
 
-/// Checks how many patches are available for a version (lightweight: no manifest
-/// downloads). Returns `Some(count)` or `None` on error.
+/// Checks how many patches are missing for a version (lightweight: the index, or
+/// cached manifests). Returns `Some(count)` or `None` on error.
 pub(crate) async fn check_patches_available(
   api_client: &ApiClient,
   app_config: &Arc<Mutex<AppConfig>>,
@@ -117,18 +133,17 @@ pub(crate) async fn check_patches_available(
       .find(|v| v.name == version_name)
       .map(|v| v.installed_path.clone())
   };
-  let installed_count = match installed_path {
-    Some(ref p) => read_installed_patches(Path::new(p)).len(),
-    None => 0,
+  let installed = match installed_path {
+    Some(ref p) => read_installed_patches(Path::new(p)),
+    None => Vec::new(),
   };
-
   // Try static index first (0 API calls).
-  let provider_id = api_client.current_provider().ok()?.id();
-  if let Ok(index) = crate::service::index::load_index(provider_id).await {
+  if let Ok(index) = api_client.current_provider().ok()?.release_index().await {
     if let Some(entry) = index.releases.iter().find(|r| r.path == version_name) {
       let total = entry.patches.len();
-      log::info!("Auto-check '{}': {} patches from index ({} installed)", version_name, total, installed_count);
-      return Some(total.saturating_sub(installed_count));
+      log::info!("Auto-check '{}': {} patches from index ({} installed)", version_name, total, installed.len());
+      let mut patches = index_patch_infos(&entry.patches);
+      return Some(plan_missing(&mut patches, &installed).len());
     }
   }
 
@@ -167,11 +182,12 @@ pub(crate) async fn check_patches_available(
     }
   };
 
-  let total = releases.len();
-  if total > installed_count {
-    Some(total - installed_count)
-  } else {
-    Some(0)
+  match api_patch_infos(api_client, releases).await {
+    Ok(mut patches) => Some(plan_missing(&mut patches, &installed).len()),
+    Err(e) => {
+      log::warn!("Auto-check: cannot read the patches of '{}': {}", version_name, e);
+      None
+    }
   }
 }
 
@@ -217,10 +233,10 @@ pub(crate) async fn get_version_patches_impl(
     let svc = service.lock().await;
     svc.api_client.clone()
   };
-  let provider_id = api_client.current_provider()?.id();
+  let api = api_client.current_provider()?;
 
   // Find the installed version and read patch markers from disk.
-  let installed_set: HashSet<String> = {
+  let installed: Vec<InstalledPatch> = {
     let cfg = app_config.lock().await;
     let v = cfg
       .installed_versions
@@ -228,34 +244,14 @@ pub(crate) async fn get_version_patches_impl(
       .find(|v| v.name == version_name)
       .ok_or_else(|| anyhow::anyhow!("Version '{}' not found in installed_versions", version_name))?;
     read_installed_patches(Path::new(&v.installed_path))
-      .into_iter()
-      .map(|p| p.name)
-      .collect()
   };
 
   // Try the static release index first (0 API calls).
-  if let Ok(index) = crate::service::index::load_index(provider_id).await {
+  if let Ok(index) = api.release_index().await {
     if let Some(entry) = index.releases.iter().find(|r| r.path == version_name) {
       log::info!("get_version_patches '{}': loaded {} patches from index", version_name, entry.patches.len());
-      let mut found_next = false;
-      let mut patches: Vec<PatchInfo> = Vec::new();
-
-      for patch in &entry.patches {
-        let is_installed = installed_set.contains(&patch.tag);
-        let is_next = !is_installed && !found_next;
-        if is_next {
-          found_next = true;
-        }
-        let size: u64 = patch.assets.iter().filter(|a| a.name != MANIFEST_NAME).map(|a| a.size).sum();
-        patches.push(PatchInfo {
-          name: patch.tag.clone(),
-          notes: patch.notes.clone(),
-          size: if size > 0 { Some(size) } else { None },
-          is_next,
-        });
-      }
-
-      let missing: Vec<String> = patches.iter().filter(|p| !installed_set.contains(&p.name)).map(|p| p.name.clone()).collect();
+      let mut patches = index_patch_infos(&entry.patches);
+      let missing = plan_missing(&mut patches, &installed);
       return Ok(PatchCheckResult { patches, missing });
     }
   }
@@ -273,56 +269,118 @@ pub(crate) async fn get_version_patches_impl(
     log::info!("  release: tag='{}' name='{}' assets={}", rel.tag_name, rel.name, rel.assets.len());
   }
 
-  if releases.is_empty() {
-    return Ok(PatchCheckResult {
-      patches: vec![],
-      missing: vec![],
-    });
-  }
+  let mut patches = api_patch_infos(&api_client, releases).await?;
+  let missing = plan_missing(&mut patches, &installed);
+
+  Ok(PatchCheckResult { patches, missing })
+}
 
-  let mut sorted_releases = releases;
-  sorted_releases.sort_by(|a, b| {
+/// Patch list of the updates repo releases, oldest first. Only the manifest
+/// tells a cumulative patch apart, so the manifests are read (a few at a time,
+/// cached); a manifest that cannot be read fails the whole list instead of
+/// passing a cumulative patch off as a regular one.
+async fn api_patch_infos(api_client: &ApiClient, mut releases: Vec<RepoReleaseInfo>) -> Result<Vec<PatchInfo>> {
+  releases.sort_by(|a, b| {
     let ta = a.created_at.as_deref().unwrap_or("");
     let tb = b.created_at.as_deref().unwrap_or("");
     ta.cmp(tb)
   });
 
-  let mut found_next = false;
-  let mut patches: Vec<PatchInfo> = Vec::new();
+  let supersedes: Vec<Vec<String>> = stream::iter(releases.iter().map(|release| release_supersedes(api_client, release)))
+    .buffered(PATCH_MANIFEST_FETCH_CONCURRENCY)
+    .try_collect()
+    .await?;
 
-  for release in &sorted_releases {
-    let name = release.tag_name.clone();
-    let is_installed = installed_set.contains(&name);
-    let is_next = !is_installed && !found_next;
-    if is_next {
-      found_next = true;
-    }
+  Ok(
+    releases
+      .into_iter()
+      .zip(supersedes)
+      .map(|(release, supersedes)| {
+        let total: u64 = release
+          .assets
+          .iter()
+          .filter(|a| a.name != MANIFEST_NAME)
+          .filter_map(|a| a.size)
+          .sum();
+        PatchInfo {
+          name: release.tag_name,
+          notes: release.body,
+          size: if total > 0 { Some(total) } else { None },
+          is_next: false,
+          supersedes,
+        }
+      })
+      .collect(),
+  )
+}
 
-    let size: Option<u64> = {
-      let total: u64 = release
-        .assets
-        .iter()
-        .filter(|a| a.name != MANIFEST_NAME)
-        .filter_map(|a| a.size)
-        .sum();
-      if total > 0 { Some(total) } else { None }
-    };
+/// `supersedes` from the manifest of `release` (empty without a manifest).
+async fn release_supersedes(api_client: &ApiClient, release: &RepoReleaseInfo) -> Result<Vec<String>> {
+  let Some(asset) = release.assets.iter().find(|a| a.name == MANIFEST_NAME) else {
+    return Ok(vec![]);
+  };
+  if let Some(cached) = crate::utils::locks::lock(&SUPERSEDES_CACHE).get(&asset.download_link) {
+    return Ok(cached.clone());
+  }
+
+  let manifest = download_manifest(api_client, &asset.download_link)
+    .await
+    .with_context(|| format!("cannot read the manifest of '{}'", &release.tag_name))?;
+  crate::utils::locks::lock(&SUPERSEDES_CACHE).insert(asset.download_link.clone(), manifest.supersedes.clone());
+  Ok(manifest.supersedes)
+}
+
+fn index_patch_infos(patches: &[IndexPatch]) -> Vec<PatchInfo> {
+  patches
+    .iter()
+    .map(|patch| {
+      let size: u64 = patch.assets.iter().filter(|a| a.name != MANIFEST_NAME).map(|a| a.size).sum();
+      PatchInfo {
+        name: patch.tag.clone(),
+        notes: patch.notes.clone(),
+        size: if size > 0 { Some(size) } else { None },
+        is_next: false,
+        supersedes: patch.supersedes.clone(),
+      }
+    })
+    .collect()
+}
 
-    patches.push(PatchInfo {
-      name,
-      notes: release.body.clone(),
-      size,
-      is_next,
-    });
+/// Patches still to install, in install order, and marks the first one as
+/// next. A player without patches jumps to the newest cumulative patch (the
+/// one replacing the longest chain) and then takes only the patches after it;
+/// everyone else follows the regular chain, so cumulative patches are skipped.
+fn plan_missing(patches: &mut [PatchInfo], installed: &[InstalledPatch]) -> Vec<String> {
+  let mut done: HashSet<&str> = HashSet::new();
+  for p in installed {
+    done.insert(p.name.as_str());
+    done.extend(p.supersedes.iter().map(String::as_str));
   }
 
-  let missing: Vec<String> = patches.iter().filter(|p| !installed_set.contains(&p.name)).map(|p| p.name.clone()).collect();
+  let jump = match installed.is_empty() {
+    true => patches.iter().filter(|p| !p.supersedes.is_empty()).max_by_key(|p| p.supersedes.len()),
+    false => None,
+  };
+  let mut missing: Vec<String> = Vec::new();
+  if let Some(jump) = jump {
+    missing.push(jump.name.clone());
+    done.extend(jump.supersedes.iter().map(String::as_str));
+  }
+  missing.extend(
+    patches
+      .iter()
+      .filter(|p| p.supersedes.is_empty() && !done.contains(p.name.as_str()))
+      .map(|p| p.name.clone()),
+  );
 
-  Ok(PatchCheckResult { patches, missing })
+  for p in patches.iter_mut() {
+    p.is_next = missing.first() == Some(&p.name);
+  }
+  missing
 }
 
 /// Downloads and parses a manifest.json from a release asset URL.
-async fn download_manifest(api_client: &ApiClient, url: &str) -> Result<ReleaseManifest> {
+pub(crate) async fn download_manifest(api_client: &ApiClient, url: &str) -> Result<ReleaseManifest> {
   let api = api_client.current_provider()?;
   let (stream, _stream_start) = api.get_blob_by_url_stream(url, &None).await?;
   let bytes = stream
@@ -338,222 +396,739 @@ async fn download_manifest(api_client: &ApiClient, url: &str) -> Result<ReleaseM
 }
 
 // ---------------------------------------------------------------------------
-// start_install_patch
+// start_install_patch / start_install_patch_chain
 // ---------------------------------------------------------------------------
 
+/// True while a patch install or chain of the version is queued or running.
+fn patch_install_busy(jobs: &DownloadJobs, version_name: &str) -> bool {
+  jobs.list().iter().any(|j| {
+    j.version_name == version_name
+      && matches!(j.kind, DownloadJobKind::Patch { .. } | DownloadJobKind::PatchChain { .. })
+      && matches!(j.state, DownloadJobState::Queued | DownloadJobState::Running)
+  })
+}
+
+/// Queues the install as a download job and waits for it.
+#[tauri::command]
+pub async fn start_install_patch(jobs: tauri::State<'_, Arc<DownloadJobs>>, versionName: String, patchName: String) -> Result<(), String> {
+  // Guard: only one install per version at a time.
+  if patch_install_busy(&jobs, &versionName) {
+    return Err("PATCH_INSTALL_ALREADY_RUNNING".to_string());
+  }
+
+  jobs.run(DownloadJob::patch(&versionName, &patchName)).await
+}
+
+/// Queues every missing patch of the version as one chain job and waits for it.
 #[tauri::command]
-pub async fn start_install_patch(
+pub async fn start_install_patch_chain(
   app: tauri::AppHandle,
-  cancel_map: tauri::State<'_, UploadCancelMap>,
   service: tauri::State<'_, Arc<Mutex<Service>>>,
   app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>,
-  service_files: tauri::State<'_, Arc<ServiceFiles>>,
-  service_unpack: tauri::State<'_, Arc<ServiceUnpacker>>,
+  jobs: tauri::State<'_, Arc<DownloadJobs>>,
   versionName: String,
-  patchName: String,
 ) -> Result<(), String> {
-  // Guard: only one install per version at a time.
-  let cancel_key = format!("patch-install:{}", &versionName);
-  if crate::utils::locks::lock(&cancel_map).contains_key(&cancel_key) {
+  if patch_install_busy(&jobs, &versionName) {
     return Err("PATCH_INSTALL_ALREADY_RUNNING".to_string());
   }
-  let (cancel_tx, _) = broadcast::channel::<()>(1);
-  crate::utils::locks::lock(&cancel_map).insert(cancel_key.clone(), cancel_tx.clone());
-  scopeguard::defer! { crate::utils::locks::lock(&cancel_map).remove(&cancel_key); };
-
-  let result = start_install_patch_inner(
-    &app,
-    &cancel_tx,
-    &service,
-    &app_config,
-    &service_files,
-    &service_unpack,
-    &versionName,
-    &patchName,
-  )
-  .await;
 
-  if let Err(ref e) = result {
-    log_full_error(e);
+  // `missing` keeps the chain order of the updates repo.
+  let check = get_version_patches_impl(&app, &service, &app_config, &versionName).await.map_err(|e| {
+    log_full_error(&e);
+    e.to_string()
+  })?;
+  if check.missing.is_empty() {
+    return Ok(());
   }
 
-  result.map_err(|e| e.to_string())
+  jobs.run(DownloadJob::patch_chain(&versionName, &check.missing)).await
 }
 
-async fn start_install_patch_inner(
-  app: &tauri::AppHandle,
-  cancel_tx: &broadcast::Sender<()>,
-  service: &tauri::State<'_, Arc<Mutex<Service>>>,
-  app_config: &tauri::State<'_, Arc<Mutex<AppConfig>>>,
-  service_files: &tauri::State<'_, Arc<ServiceFiles>>,
-  service_unpack: &tauri::State<'_, Arc<ServiceUnpacker>>,
-  version_name: &str,
-  patch_name: &str,
-) -> Result<()> {
-  let api_client = {
-    let svc = service.lock().await;
-    svc.api_client.clone()
-  };
-  let api = api_client.current_provider()?;
-  let provider_id = api.id().to_string();
+/// A patch release of the updates repo with its parsed manifest.
+struct PatchRelease {
+  release: RepoReleaseInfo,
+  manifest: ReleaseManifest,
+  /// full*.zip archives to download: some delta bases differ locally.
+  full_archives: Vec<String>,
+}
 
-  // Find version and read installed patches from marker files.
-  let installed_path = {
-    let cfg = app_config.lock().await;
-    cfg
-      .installed_versions
-      .values()
-      .find(|v| v.name == version_name)
-      .ok_or_else(|| anyhow::anyhow!("Version '{}' not found", version_name))?
-      .installed_path
-      .clone()
-  };
-  let last_installed_patch = read_installed_patches(Path::new(&installed_path))
-    .last()
-    .map(|p| p.name.clone());
+impl PatchRelease {
+  /// The data*.zip archives (no manifest.json) plus the needed full*.zip ones.
+  fn archive_assets(&self) -> Vec<&RepoReleaseAsset> {
+    self
+      .release
+      .assets
+      .iter()
+      .filter(|a| a.name != MANIFEST_NAME && (a.name.starts_with("data") || self.full_archives.contains(&a.name)))
+      .collect()
+  }
 
-  // Resolve updates repo and find the target release.
-  let updates_project = resolve_updates_project(&api_client, version_name).await?;
-  let pid = project_id_for(&api_client, &updates_project)?;
+  /// See `InstalledPatch::chain_tip`.
+  fn chain_tip(&self) -> &str {
+    self.manifest.supersedes.last().unwrap_or(&self.release.tag_name)
+  }
 
-  let releases = api.get_repo_releases(&pid).await?;
+  fn archives_size(&self) -> u64 {
+    self.archive_assets().iter().filter_map(|a| a.size).sum()
+  }
+
+  /// Game files the patch writes, so a later patch of the same run finds them
+  /// only after this one is applied.
+  fn shipped_paths(&self) -> impl Iterator<Item = &String> {
+    let entries = self.manifest.entries.iter().map(|e| &e.path);
+    entries.chain(self.manifest.deltas.iter().map(|d| &d.path))
+  }
+
+  /// Picks the full*.zip archives for the deltas whose base differs from the
+  /// local file (`shipped` files are left to the earlier patch of the run).
+  async fn select_full_archives(&mut self, installed_path: &Path, shipped: &HashSet<String>) -> Result<()> {
+    if self.manifest.deltas.is_empty() {
+      return Ok(());
+    }
+    let (path, deltas, shipped) = (installed_path.to_path_buf(), self.manifest.deltas.clone(), shipped.clone());
+    let stale: Vec<String> = tokio::task::spawn_blocking(move || {
+      let mut names: Vec<String> = patch_delta::stale(&path, &deltas, &shipped)
+        .into_iter()
+        .map(|d| d.archive.clone())
+        .collect();
+      names.sort();
+      names.dedup();
+      names
+    })
+    .await?;
+
+    let published = |name: &String| !name.is_empty() && self.release.assets.iter().any(|a| &a.name == name);
+    if let Some(missing) = stale.iter().find(|name| !published(name)) {
+      bail!(
+        "Patch '{}' needs the full copy of files that differ locally, but the archive '{}' is not published",
+        &self.release.tag_name,
+        missing
+      );
+    }
+    if !stale.is_empty() {
+      log::info!("Patch '{}': delta bases differ locally, downloading {:?}", &self.release.tag_name, &stale);
+    }
+    self.full_archives = stale;
+    Ok(())
+  }
+
+  fn marker(&self, provider_id: &str) -> InstalledPatch {
+    InstalledPatch {
+      name: self.release.tag_name.clone(),
+      provider_id: provider_id.to_string(),
+      installed_at: Some(chrono::Local::now().to_rfc3339()),
+      notes: self.release.body.clone(),
+      backup: None,
+      supersedes: self.manifest.supersedes.clone(),
+    }
+  }
+}
+
+/// Resolves patch releases of a version: from the static release index first
+/// (its manifest and asset URLs need no API calls), from the updates repo API
+/// only for patches the index lacks. The repo is listed at most once.
+struct PatchSource<'a> {
+  api_client: &'a ApiClient,
+  version_name: &'a str,
+  index: Vec<IndexPatch>,
+  releases: Option<Vec<RepoReleaseInfo>>,
+}
+
+impl<'a> PatchSource<'a> {
+  async fn load(api_client: &'a ApiClient, version_name: &'a str) -> PatchSource<'a> {
+    let index = match api_client.current_provider() {
+      Ok(api) => match api.release_index().await {
+        Ok(index) => index
+          .releases
+          .into_iter()
+          .find(|r| r.name == version_name || r.path == version_name)
+          .map(|r| r.patches)
+          .unwrap_or_default(),
+        Err(e) => {
+          log::info!("Patch install '{}': release index unavailable, using the API: {}", version_name, e);
+          vec![]
+        }
+      },
+      Err(_) => vec![],
+    };
+
+    PatchSource {
+      api_client,
+      version_name,
+      index,
+      releases: None,
+    }
+  }
+
+  async fn fetch(&mut self, patch_name: &str) -> Result<PatchRelease> {
+    if let Some(patch) = self.index.iter().find(|p| p.tag == patch_name) {
+      match self.fetch_indexed(patch).await {
+        Ok(release) => return Ok(release),
+        // A stale index (e.g. a re-uploaded patch) must not block the install.
+        Err(e) => log::warn!("Patch '{}' from the release index failed, using the API: {}", patch_name, e),
+      }
+    }
+
+    if self.releases.is_none() {
+      let updates_project = resolve_updates_project(self.api_client, self.version_name).await?;
+      let pid = project_id_for(self.api_client, &updates_project)?;
+      self.releases = Some(self.api_client.current_provider()?.get_repo_releases(&pid).await?);
+    }
+    fetch_patch_release(self.api_client, self.releases.as_deref().unwrap_or_default(), patch_name).await
+  }
+
+  async fn fetch_indexed(&self, patch: &IndexPatch) -> Result<PatchRelease> {
+    let manifest_url = patch.manifest.as_deref().ok_or_else(|| anyhow::anyhow!("no manifest URL in the index"))?;
+    let manifest = download_manifest(self.api_client, manifest_url).await?;
+    let release = RepoReleaseInfo {
+      tag_name: patch.tag.clone(),
+      name: patch.tag.clone(),
+      body: patch.notes.clone(),
+      created_at: None,
+      assets: patch
+        .assets
+        .iter()
+        .map(|a| RepoReleaseAsset {
+          name: a.name.clone(),
+          size: Some(a.size).filter(|s| *s > 0),
+          download_link: a.url.clone(),
+        })
+        .collect(),
+    };
+    log::info!("Patch '{}' resolved from the release index", &patch.tag);
+
+    Ok(PatchRelease {
+      release,
+      manifest,
+      full_archives: vec![],
+    })
+  }
+}
+
+/// Finds `patch_name` among the updates repo releases and downloads its manifest.
+async fn fetch_patch_release(api_client: &ApiClient, releases: &[RepoReleaseInfo], patch_name: &str) -> Result<PatchRelease> {
   let release = releases
     .iter()
     .find(|r| r.tag_name == patch_name)
     .ok_or_else(|| anyhow::anyhow!("Patch '{}' not found in updates repo", patch_name))?
     .clone();
 
-  // Download and validate manifest.
   let manifest_asset = release
     .assets
     .iter()
     .find(|a| a.name == MANIFEST_NAME)
-    .ok_or_else(|| anyhow::anyhow!("Patch '{}' has no manifest.json", patch_name))?
-    .clone();
+    .ok_or_else(|| anyhow::anyhow!("Patch '{}' has no manifest.json", patch_name))?;
+  let manifest = download_manifest(api_client, &manifest_asset.download_link).await?;
 
-  let manifest = download_manifest(&api_client, &manifest_asset.download_link).await?;
+  Ok(PatchRelease {
+    release,
+    manifest,
+    full_archives: vec![],
+  })
+}
 
-  // Validate chain: base_patch must match the last installed patch.
-  let expected_base = manifest.base_patch.as_deref().unwrap_or("");
-  let actual_base = last_installed_patch.as_deref().unwrap_or("");
+/// Checks that `patch` continues the chain after `last_installed`.
+fn check_patch_base(patch: &PatchRelease, last_installed: Option<&str>) -> Result<()> {
+  let expected_base = patch.manifest.base_patch.as_deref().unwrap_or("");
+  let actual_base = last_installed.unwrap_or("");
   if expected_base != actual_base {
     bail!(
       "Patch chain mismatch: patch '{}' expects base '{}', but last installed is '{}'",
-      patch_name,
+      &patch.release.tag_name,
       expected_base,
       actual_base
     );
   }
+  Ok(())
+}
 
-  install_log(app, format!("Installing patch '{}' for version '{}' ...", patch_name, version_name));
+/// Bytes downloaded out of every archive of the install (one patch or a
+/// chain); drives the first half of the progress bar.
+struct DownloadTally {
+  done: u64,
+  total: u64,
+}
 
-  // Prepare download directory.
-  let patches_dir = Path::new(&installed_path).join(".patches").join(patch_name);
-  std::fs::create_dir_all(&patches_dir).context("create patch download dir")?;
+/// Downloads `assets` of a patch into `dir`. An archive left complete by
+/// an earlier attempt is kept when it still matches its hash.
+#[allow(clippy::too_many_arguments)]
+async fn download_patch_archives(
+  ctx: &DownloadContext,
+  api_client: &ApiClient,
+  cancel_tx: &broadcast::Sender<()>,
+  version_name: &str,
+  patch: &PatchRelease,
+  assets: &[&RepoReleaseAsset],
+  dir: &Path,
+  tally: &mut DownloadTally,
+) -> Result<Vec<PathBuf>> {
+  let app = &ctx.app;
+  let api = api_client.current_provider()?;
+  std::fs::create_dir_all(dir).context("create patch download dir")?;
+
+  let mut archives = Vec::with_capacity(assets.len());
+  for (i, asset) in assets.iter().enumerate() {
+    let file_path = dir.join(&asset.name);
+    let expected = patch
+      .manifest
+      .files
+      .iter()
+      .find(|f| f.name == asset.name)
+      .and_then(|f| f.sha256.as_deref());
 
-  let version_name_owned = version_name.to_string();
+    // For GitLab: fetch size via HEAD if not available.
+    let actual_size = match asset.size {
+      Some(size) if size > 0 => size,
+      _ => api.get_file_content_size(&asset.download_link).await.unwrap_or(0),
+    };
 
-  // Download data*.zip assets (skip manifest.json).
-  let data_assets: Vec<_> = release
-    .assets
-    .iter()
-    .filter(|a| a.name != MANIFEST_NAME && a.name.starts_with("data"))
-    .collect();
+    let complete = std::fs::metadata(&file_path).is_ok_and(|m| actual_size > 0 && m.len() == actual_size);
+    if complete && ctx.files.verify_download(&file_path, expected).await.is_ok() {
+      install_log(app, format!("{} already downloaded", &asset.name));
+    } else {
+      install_log(app, format!("Downloading {} ({}/{})", &asset.name, i + 1, assets.len()));
+
+      // Same retry policy as release files: resume from the .part, back off per error
+      // class, and hand the archive over to the next provider that has it.
+      let part_path = format!("{}.part", file_path.to_string_lossy());
+      let mut source = DownloadSource::current(&asset.download_link);
+      let mut tried = vec![api.id().to_owned()];
+      let mut retries = RetryCounter::default();
+      let mut stop_rx = cancel_tx.subscribe();
+      loop {
+        let seek = part_file::downloaded_bytes(&part_path);
+        let res = ctx
+          .files
+          .download_blob_to_file(
+            api_client,
+            source.provider_id.as_deref(),
+            version_name,
+            &source.url,
+            &actual_size,
+            &file_path,
+            &seek,
+            cancel_tx.subscribe(),
+          )
+          .await;
+        // Removes a corrupted archive, so the next attempt downloads it from zero.
+        let res = match res {
+          Ok(DownloadOutcome::Completed) => ctx.files.verify_download(&file_path, expected).await.map(|_| DownloadOutcome::Completed),
+          other => other,
+        };
+
+        match res {
+          Ok(DownloadOutcome::Completed) => break,
+          Ok(DownloadOutcome::Interrupted) => {
+            install_log(app, "Download interrupted.".to_string());
+            return Err(anyhow::anyhow!("USER_CANCELLED"));
+          }
+          Err(e) => {
+            let failure = retries.fail(&e);
+            if failure.should_fail_over() {
+              if let Some(next) = next_source(api_client, version_name, &asset.name, actual_size, &mut tried).await {
+                let provider = next.provider_name(api_client);
+                install_log(app, format!("Download of {} failed: {}. Switching to '{}'", &asset.name, e, provider));
+                source = next;
+                retries.reset();
+                emit_file_source(app, api_client, version_name, &asset.name, &source);
+                continue;
+              }
+            }
+            let Some(delay) = failure.delay else {
+              return Err(e.context(format!("{} failed after {} attempts", &asset.name, failure.total)));
+            };
+            install_log(
+              app,
+              format!("Download of {} failed: {}. Retrying in {:.1}s", &asset.name, e, delay.as_secs_f64()),
+            );
+            // A pause or shutdown must not wait out a long backoff.
+            tokio::select! {
+              _ = tokio::time::sleep(delay) => {}
+              _ = stop_rx.recv() => {
+                install_log(app, "Download interrupted.".to_string());
+                return Err(anyhow::anyhow!("USER_CANCELLED"));
+              }
+            }
+          }
+        }
+      }
+    }
+
+    tally.done += actual_size;
+    let _ = app.emit(
+      EVT_INSTALL_PROGRESS,
+      PatchInstallProgress {
+        stage: "download".to_string(),
+        version: version_name.to_string(),
+        file: asset.name.clone(),
+        file_progress: 100.0,
+        total_progress: if tally.total > 0 {
+          (tally.done as f64 / tally.total as f64).min(1.0) * 50.0
+        } else {
+          50.0
+        },
+      },
+    );
+    archives.push(file_path);
+  }
+
+  Ok(archives)
+}
 
-  let grand_total: u64 = data_assets.iter().filter_map(|a| a.size).sum();
-  let mut downloaded_total: u64 = 0;
+/// Where `apply_patch` downloads the `fullN.zip` archives it finds it needs
+/// only while applying the deltas.
+pub(crate) struct FullArchiveSource<'a> {
+  ctx: &'a DownloadContext,
+  api_client: &'a ApiClient,
+  cancel_tx: &'a broadcast::Sender<()>,
+  version_name: &'a str,
+  patch: &'a PatchRelease,
+  dir: &'a Path,
+}
 
-  // Announce the download stage immediately (0%) so the frontend can render
-  // the progress bar as soon as the install reaches the download phase,
-  // rather than only after the first archive finishes. The byte-level fill
-  // is driven by "download-speed-status" events emitted from ServiceFiles.
+impl FullArchiveSource<'_> {
+  async fn download(&self, names: &[String]) -> Result<Vec<PathBuf>> {
+    let assets: Vec<&RepoReleaseAsset> = self.patch.release.assets.iter().filter(|a| names.contains(&a.name)).collect();
+    if let Some(name) = names.iter().find(|name| !assets.iter().any(|a| &a.name == *name)) {
+      bail!(
+        "Patch '{}' needs the full copy '{}', but it is not published",
+        &self.patch.release.tag_name,
+        name
+      );
+    }
+    let mut tally = DownloadTally {
+      done: 0,
+      total: assets.iter().filter_map(|a| a.size).sum(),
+    };
+    download_patch_archives(
+      self.ctx,
+      self.api_client,
+      self.cancel_tx,
+      self.version_name,
+      self.patch,
+      &assets,
+      self.dir,
+      &mut tally,
+    )
+    .await
+  }
+}
+
+/// Announces the download stage at 0% so the frontend can render the progress
+/// bar as soon as the install reaches the download phase, rather than only
+/// after the first archive finishes. The byte-level fill is driven by
+/// "download-speed-status" events emitted from ServiceFiles.
+fn emit_download_start(app: &tauri::AppHandle, version_name: &str) {
   let _ = app.emit(
     EVT_INSTALL_PROGRESS,
     PatchInstallProgress {
       stage: "download".to_string(),
-      version: version_name_owned.clone(),
+      version: version_name.to_string(),
       file: String::new(),
       file_progress: 0.0,
       total_progress: 0.0,
     },
   );
+}
 
-  for (i, asset) in data_assets.iter().enumerate() {
-    // Cancel check.
-    if cancel_tx.receiver_count() > 0 {
-      let mut probe = cancel_tx.subscribe();
-      if probe.try_recv().is_ok() {
-        install_log(app, "Patch install cancelled.".to_string());
-        return Err(anyhow::anyhow!("USER_CANCELLED"));
-      }
-    }
+fn emit_install_done(app: &tauri::AppHandle, version_name: &str) {
+  let _ = app.emit(
+    EVT_INSTALL_PROGRESS,
+    PatchInstallProgress {
+      stage: "done".to_string(),
+      version: version_name.to_string(),
+      file: String::new(),
+      file_progress: 100.0,
+      total_progress: 100.0,
+    },
+  );
+}
+
+/// Installed path of a version and its patch markers, oldest first.
+async fn installed_state(ctx: &DownloadContext, version_name: &str) -> Result<(PathBuf, Vec<InstalledPatch>)> {
+  let installed_path = {
+    let cfg = ctx.config.lock().await;
+    cfg
+      .installed_versions
+      .values()
+      .find(|v| v.name == version_name)
+      .ok_or_else(|| anyhow::anyhow!("Version '{}' not found", version_name))?
+      .installed_path
+      .clone()
+  };
+  let installed_path = PathBuf::from(installed_path);
+  let patches = read_installed_patches(&installed_path);
+  Ok((installed_path, patches))
+}
 
-    let file_path = patches_dir.join(&asset.name);
-    let total_size = asset.size.unwrap_or(0);
+/// Runs one patch install; called by the download job manager.
+pub(crate) async fn install_patch(ctx: &DownloadContext, cancel_tx: &broadcast::Sender<()>, version_name: &str, patch_name: &str) -> Result<()> {
+  let app = &ctx.app;
+  let api_client = {
+    let svc = ctx.service.lock().await;
+    svc.api_client.clone()
+  };
+  let provider_id = api_client.current_provider()?.id().to_string();
 
-    // For GitLab: fetch size via HEAD if not available.
-    let actual_size = if total_size == 0 {
-      api.get_file_content_size(&asset.download_link).await.unwrap_or(0)
-    } else {
-      total_size
-    };
+  let (installed_path, installed) = installed_state(ctx, version_name).await?;
+  let last_installed_patch = installed.last().map(InstalledPatch::chain_tip);
+
+  // Resolve the patch (index first), download and validate the manifest.
+  let mut patch = PatchSource::load(&api_client, version_name).await.fetch(patch_name).await?;
+  check_patch_base(&patch, last_installed_patch)?;
+  patch.select_full_archives(&installed_path, &HashSet::new()).await?;
 
+  install_log(app, format!("Installing patch '{}' for version '{}' ...", patch_name, version_name));
+
+  let patches_dir = installed_path.join(".patches").join(patch_name);
+  let grand_total = patch.archives_size();
+  patch_plan(&installed_path, grand_total, &patch.manifest)?
+    .ensure()
+    .map_err(|e| anyhow::anyhow!(e))?;
+
+  emit_download_start(app, version_name);
+  let mut tally = DownloadTally { done: 0, total: grand_total };
+  let assets = patch.archive_assets();
+  let archives = download_patch_archives(ctx, &api_client, cancel_tx, version_name, &patch, &assets, &patches_dir, &mut tally).await?;
+
+  let full_source = FullArchiveSource {
+    ctx,
+    api_client: &api_client,
+    cancel_tx,
+    version_name,
+    patch: &patch,
+    dir: &patches_dir,
+  };
+  apply_patch(
+    app,
+    &ctx.unpack,
+    &installed_path,
+    version_name,
+    patch_name,
+    &archives,
+    &patch.manifest,
+    patch.marker(&provider_id),
+    Some(&full_source),
+    50.0..100.0,
+  )
+  .await?;
+
+  // Cleanup: remove the patches dir on success.
+  if let Err(e) = std::fs::remove_dir_all(&patches_dir) {
+    log::warn!("Cannot remove patch dir {:?}: {}", patches_dir, e);
+  }
+
+  install_log(app, format!("Patch '{}' installed successfully!", patch_name));
+  emit_install_done(app, version_name);
+
+  Ok(())
+}
+
+/// Installs `patches` in order; called by the download job manager. All
+/// archives are downloaded before the first patch is applied, so a network
+/// failure never leaves the chain half applied. Patches already installed
+/// (by an interrupted run of the same job) are skipped.
+pub(crate) async fn install_patch_chain(
+  ctx: &DownloadContext,
+  cancel_tx: &broadcast::Sender<()>,
+  version_name: &str,
+  patches: &[String],
+) -> Result<()> {
+  let app = &ctx.app;
+  let mut cancel_rx = cancel_tx.subscribe();
+  let api_client = {
+    let svc = ctx.service.lock().await;
+    svc.api_client.clone()
+  };
+  let provider_id = api_client.current_provider()?.id().to_string();
+
+  let (installed_path, installed) = installed_state(ctx, version_name).await?;
+  let pending: Vec<&String> = patches.iter().filter(|name| !installed.iter().any(|p| &p.name == *name)).collect();
+  if pending.is_empty() {
+    emit_install_done(app, version_name);
+    return Ok(());
+  }
+
+  // One index load (and at most one updates repo lookup) for the whole chain.
+  let mut source = PatchSource::load(&api_client, version_name).await;
+
+  let mut chain: Vec<PatchRelease> = Vec::with_capacity(pending.len());
+  let mut shipped: HashSet<String> = HashSet::new();
+  for name in &pending {
+    let mut patch = source.fetch(name).await?;
+    let base = chain
+      .last()
+      .map(PatchRelease::chain_tip)
+      .or(installed.last().map(InstalledPatch::chain_tip));
+    check_patch_base(&patch, base)?;
+    patch.select_full_archives(&installed_path, &shipped).await?;
+    shipped.extend(patch.shipped_paths().cloned());
+    chain.push(patch);
+  }
+
+  install_log(
+    app,
+    format!(
+      "Installing {} patches for version '{}': {}",
+      chain.len(),
+      version_name,
+      pending.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")
+    ),
+  );
+
+  let sizes: Vec<(u64, &ReleaseManifest)> = chain.iter().map(|p| (p.archives_size(), &p.manifest)).collect();
+  patch_chain_plan(&installed_path, &sizes)?.ensure().map_err(|e| anyhow::anyhow!(e))?;
+
+  emit_download_start(app, version_name);
+  let mut tally = DownloadTally {
+    done: 0,
+    total: sizes.iter().map(|(size, _)| size).sum(),
+  };
+  let mut downloaded: Vec<(PathBuf, Vec<PathBuf>)> = Vec::with_capacity(chain.len());
+  for (i, patch) in chain.iter().enumerate() {
     install_log(
       app,
-      format!("Downloading {} ({}/{})", &asset.name, i + 1, data_assets.len()),
+      format!("Downloading patch '{}' ({}/{})", &patch.release.tag_name, i + 1, chain.len()),
     );
+    let dir = installed_path.join(".patches").join(&patch.release.tag_name);
+    let assets = patch.archive_assets();
+    let archives = download_patch_archives(ctx, &api_client, cancel_tx, version_name, patch, &assets, &dir, &mut tally).await?;
+    downloaded.push((dir, archives));
+  }
 
-    let file_name = asset.name.clone();
-
-    let outcome = service_files
-      .download_blob_to_file(
-        &api_client,
-        version_name,
-        &asset.download_link,
-        &actual_size,
-        &file_path,
-        &None,
-        cancel_tx.subscribe(),
-      )
-      .await?;
-
-    match outcome {
-      DownloadOutcome::Completed => {
-        downloaded_total += actual_size;
-        let _ = app.emit(
-          EVT_INSTALL_PROGRESS,
-          PatchInstallProgress {
-            stage: "download".to_string(),
-            version: version_name_owned.clone(),
-            file: file_name,
-            file_progress: 100.0,
-            total_progress: if grand_total > 0 {
-              (downloaded_total as f64 / grand_total as f64) * 50.0
-            } else {
-              50.0
-            },
-          },
-        );
-      }
-      DownloadOutcome::Interrupted => {
-        install_log(app, "Download interrupted.".to_string());
-        return Err(anyhow::anyhow!("USER_CANCELLED"));
-      }
+  // Apply in chain order; each patch takes an equal share of the second half.
+  let share = 50.0 / chain.len() as f64;
+  for (i, (patch, (dir, archives))) in chain.iter().zip(&downloaded).enumerate() {
+    if cancel_rx.try_recv().is_ok() {
+      install_log(app, "Patch install cancelled.".to_string());
+      return Err(anyhow::anyhow!("USER_CANCELLED"));
+    }
+
+    let patch_name = &patch.release.tag_name;
+    install_log(app, format!("Applying patch '{}' ({}/{})", patch_name, i + 1, chain.len()));
+    let start = 50.0 + share * i as f64;
+    let full_source = FullArchiveSource {
+      ctx,
+      api_client: &api_client,
+      cancel_tx,
+      version_name,
+      patch,
+      dir,
+    };
+    apply_patch(
+      app,
+      &ctx.unpack,
+      &installed_path,
+      version_name,
+      patch_name,
+      archives,
+      &patch.manifest,
+      patch.marker(&provider_id),
+      Some(&full_source),
+      start..start + share,
+    )
+    .await?;
+
+    if let Err(e) = std::fs::remove_dir_all(dir) {
+      log::warn!("Cannot remove patch dir {:?}: {}", dir, e);
+    }
+  }
+
+  install_log(app, format!("{} patches installed successfully!", chain.len()));
+  emit_install_done(app, version_name);
+
+  Ok(())
+}
+
+/// Applies downloaded patch archives to an installed version: backs up the
+/// files it replaces, unpacks the archives into the game root, applies the
+/// binary deltas, deletes `manifest.deleted_files` and writes the patch marker. A failure puts the
+/// backed up files back. Progress events cover `progress` (percent of the whole
+/// install). Shared by the online install and the offline bundle import, which
+/// has no `full_source` to download missing `fullN.zip` archives from.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn apply_patch(
+  app: &tauri::AppHandle,
+  unpack: &Arc<ServiceUnpacker>,
+  installed_path: &Path,
+  version_name: &str,
+  patch_name: &str,
+  archives: &[PathBuf],
+  manifest: &ReleaseManifest,
+  mut marker: InstalledPatch,
+  full_source: Option<&FullArchiveSource<'_>>,
+  progress: Range<f64>,
+) -> Result<()> {
+  // fullN.zip archives only serve the deltas that cannot be applied.
+  let (full_archives, data_archives): (Vec<PathBuf>, Vec<PathBuf>) = archives
+    .iter()
+    .cloned()
+    .partition(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("full")));
+
+  install_log(app, "Backing up replaced files ...".to_string());
+  let mut replaced = manifest.deleted_files.clone();
+  replaced.extend(manifest.deltas.iter().map(|d| d.path.clone()));
+  let (path, name, archive_paths) = (installed_path.to_path_buf(), patch_name.to_string(), data_archives.clone());
+  let backup = tokio::task::spawn_blocking(move || patch_backup::create(&path, &name, &archive_paths, &replaced)).await??;
+  marker.backup = Some(backup.clone());
+
+  let applied = apply_files(
+    app,
+    unpack,
+    installed_path,
+    version_name,
+    &data_archives,
+    &full_archives,
+    full_source,
+    manifest,
+    progress,
+  )
+  .await
+  .and_then(|()| write_patch_marker(installed_path, &marker));
+  if let Err(e) = applied {
+    install_log(app, format!("Patch '{}' failed, restoring the replaced files ...", patch_name));
+    let _ = std::fs::remove_dir_all(installed_path.join(DELTA_DIR));
+    let (path, name) = (installed_path.to_path_buf(), patch_name.to_string());
+    match tokio::task::spawn_blocking(move || patch_backup::restore(&path, &name, &backup)).await {
+      Ok(Ok(())) => {}
+      Ok(Err(re)) => log::error!("Cannot restore the files of patch '{}': {}", patch_name, re),
+      Err(re) => log::error!("Restore task of patch '{}' failed: {}", patch_name, re),
     }
+    return Err(e);
   }
+  log::info!("Patch '{}' applied to {:?}", patch_name, installed_path);
 
+  Ok(())
+}
+
+/// Unpacks the patch archives, applies the deltas and deletes the files the
+/// patch removes.
+#[allow(clippy::too_many_arguments)]
+async fn apply_files(
+  app: &tauri::AppHandle,
+  unpack: &Arc<ServiceUnpacker>,
+  installed_path: &Path,
+  version_name: &str,
+  archives: &[PathBuf],
+  full_archives: &[PathBuf],
+  full_source: Option<&FullArchiveSource<'_>>,
+  manifest: &ReleaseManifest,
+  progress: Range<f64>,
+) -> Result<()> {
+  // The first 80% of the span for unpacking, the rest for deleting.
+  let span = progress.end - progress.start;
   // Unpack all archives into the game root.
   install_log(app, "Unpacking archives ...".to_string());
   let mut unpack_progress = 0u32;
-  let total_archives = data_assets.len() as u32;
-  let svc = service_unpack.inner().clone();
+  let total_archives = archives.len().max(1) as u32;
 
-  for asset in &data_assets {
-    let archive_path = patches_dir.join(&asset.name);
-    let extract_to = PathBuf::from(&installed_path);
-    let unpack_name = asset.name.clone();
-    let svc = svc.clone();
-    let vn = version_name_owned.clone();
+  for archive_path in archives {
+    let archive_path = archive_path.clone();
+    let extract_to = installed_path.to_path_buf();
+    let unpack_name = archive_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let file = unpack_name.clone();
+    let svc = unpack.clone();
+    let vn = version_name.to_string();
 
     tokio::task::spawn_blocking(move || {
       svc.extract_zip(&vn, &unpack_name, &archive_path, &extract_to)
@@ -567,14 +1142,37 @@ async fn start_install_patch_inner(
       EVT_INSTALL_PROGRESS,
       PatchInstallProgress {
         stage: "unpack".to_string(),
-        version: version_name_owned.clone(),
-        file: asset.name.clone(),
+        version: version_name.to_string(),
+        file,
         file_progress: 100.0,
-        total_progress: 50.0 + (unpack_progress as f64 / total_archives as f64) * 40.0,
+        total_progress: progress.start + (unpack_progress as f64 / total_archives as f64) * span * 0.8,
       },
     );
   }
 
+  if !manifest.deltas.is_empty() {
+    install_log(app, format!("Applying {} binary deltas ...", manifest.deltas.len()));
+    let (path, deltas) = (installed_path.to_path_buf(), manifest.deltas.clone());
+    let from_full = tokio::task::spawn_blocking(move || patch_delta::apply(&path, &deltas))
+      .await
+      .map_err(|e| anyhow::anyhow!("Delta task failed: {}", e))?;
+
+    // A delta whose result is wrong although its base matched needs a full copy nobody planned for.
+    let mut full = full_archives.to_vec();
+    let missing = patch_delta::missing_archives(&from_full, &full);
+    if let Some(source) = full_source
+      && !missing.is_empty()
+    {
+      install_log(app, format!("Some deltas did not apply, downloading {}", missing.join(", ")));
+      full.extend(source.download(&missing).await?);
+    }
+
+    let (svc, vn, path) = (unpack.clone(), version_name.to_string(), installed_path.to_path_buf());
+    tokio::task::spawn_blocking(move || patch_delta::apply_full(&svc, &vn, &path, &from_full, &full))
+      .await
+      .map_err(|e| anyhow::anyhow!("Delta task failed: {}", e))??;
+  }
+
   // Delete files listed in deleted_files (after successful unpack — atomicity).
   install_log(app, "Deleting removed files ...".to_string());
   for (idx, rel_path) in manifest.deleted_files.iter().enumerate() {
@@ -585,7 +1183,7 @@ async fn start_install_patch_inner(
       continue;
     }
 
-    let abs_path = Path::new(&installed_path).join(rel_path);
+    let abs_path = installed_path.join(rel_path);
     if abs_path.exists() {
       if let Err(e) = std::fs::remove_file(&abs_path) {
         install_log(app, format!("Warning: cannot delete '{}': {}", rel_path, e));
@@ -598,54 +1196,299 @@ async fn start_install_patch_inner(
       EVT_INSTALL_PROGRESS,
       PatchInstallProgress {
         stage: "delete".to_string(),
-        version: version_name_owned.clone(),
+        version: version_name.to_string(),
         file: rel_path.clone(),
         file_progress: 100.0,
-        total_progress: 90.0 + (idx as f64 / manifest.deleted_files.len().max(1) as f64) * 10.0,
+        total_progress: progress.start + span * 0.8 + (idx as f64 / manifest.deleted_files.len().max(1) as f64) * span * 0.2,
       },
     );
   }
 
-  // Record the patch as installed via marker file.
-  write_patch_marker(Path::new(&installed_path), &InstalledPatch {
-    name: patch_name.to_string(),
-    provider_id,
-    installed_at: Some(chrono::Local::now().to_rfc3339()),
-    notes: release.body.clone(),
-  })?;
-
-  // Cleanup: remove the patches dir on success.
-  if let Err(e) = std::fs::remove_dir_all(&patches_dir) {
-    log::warn!("Cannot remove patch dir {:?}: {}", patches_dir, e);
-  }
+  Ok(())
+}
 
-  install_log(app, format!("Patch '{}' installed successfully!", patch_name));
-  let _ = app.emit(
-    EVT_INSTALL_PROGRESS,
-    PatchInstallProgress {
-      stage: "done".to_string(),
-      version: version_name_owned.clone(),
-      file: String::new(),
-      file_progress: 100.0,
-      total_progress: 100.0,
-    },
-  );
+// ---------------------------------------------------------------------------
+// cancel_install_patch
+// ---------------------------------------------------------------------------
 
+#[tauri::command]
+pub async fn cancel_install_patch(jobs: tauri::State<'_, Arc<DownloadJobs>>, versionName: String) -> Result<(), String> {
+  let ids: Vec<String> = jobs
+    .list()
+    .into_iter()
+    .filter(|j| j.version_name == versionName && matches!(j.kind, DownloadJobKind::Patch { .. } | DownloadJobKind::PatchChain { .. }))
+    .map(|j| j.id)
+    .collect();
+  for id in ids {
+    jobs.cancel(&id).await?;
+  }
   Ok(())
 }
 
 // ---------------------------------------------------------------------------
-// cancel_install_patch
+// uninstall_last_patch
 // ---------------------------------------------------------------------------
 
+/// Takes the most recently installed patch off a version: removes the files it
+/// added, restores the ones it replaced or deleted and drops its marker, so the
+/// previous patch is the last one of the chain again. Returns the patch name.
 #[tauri::command]
-pub async fn cancel_install_patch(
-  cancel_map: tauri::State<'_, UploadCancelMap>,
+pub async fn uninstall_last_patch(
+  app: tauri::AppHandle,
+  app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+  jobs: tauri::State<'_, Arc<DownloadJobs>>,
   versionName: String,
-) -> Result<(), String> {
-  let key = format!("patch-install:{}", &versionName);
-  if let Some(tx) = crate::utils::locks::lock(&cancel_map).get(&key) {
-    let _ = tx.send(());
+) -> Result<String, String> {
+  let busy = jobs
+    .list()
+    .iter()
+    .any(|j| j.version_name == versionName && matches!(j.state, DownloadJobState::Queued | DownloadJobState::Running));
+  if busy {
+    return Err("PATCH_INSTALL_ALREADY_RUNNING".to_string());
+  }
+
+  let result: Result<String> = async {
+    let installed_path = {
+      let cfg = app_config.lock().await;
+      cfg
+        .installed_versions
+        .values()
+        .find(|v| v.name == versionName)
+        .map(|v| PathBuf::from(&v.installed_path))
+        .ok_or_else(|| anyhow::anyhow!("Version '{}' not found", &versionName))?
+    };
+
+    let patch = read_installed_patches(&installed_path)
+      .pop()
+      .ok_or_else(|| anyhow::anyhow!("Version '{}' has no installed patches", &versionName))?;
+    let backup = patch
+      .backup
+      .clone()
+      .ok_or_else(|| anyhow::anyhow!("Patch '{}' was installed without a backup and cannot be uninstalled", &patch.name))?;
+
+    install_log(&app, format!("Uninstalling patch '{}' from version '{}' ...", &patch.name, &versionName));
+    let name = patch.name.clone();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+      patch_backup::restore(&installed_path, &name, &backup)?;
+      remove_patch_marker(&installed_path, &name)
+    })
+    .await??;
+
+    install_log(&app, format!("Patch '{}' uninstalled.", &patch.name));
+    Ok(patch.name)
+  }
+  .await;
+
+  result.map_err(|e| {
+    log_full_error(&e);
+    e.to_string()
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::providers::fake::Fake;
+  use crate::service::index::{IndexAsset, LauncherIndex, ReleaseIndex, ReleaseIndexEntry};
+
+  fn patch(name: &str, supersedes: &[&str]) -> PatchInfo {
+    PatchInfo {
+      name: name.to_owned(),
+      notes: None,
+      size: None,
+      is_next: false,
+      supersedes: supersedes.iter().map(|s| s.to_string()).collect(),
+    }
+  }
+
+  fn installed(name: &str, supersedes: &[&str]) -> InstalledPatch {
+    InstalledPatch {
+      name: name.to_owned(),
+      provider_id: "github".to_owned(),
+      installed_at: None,
+      notes: None,
+      backup: None,
+      supersedes: supersedes.iter().map(|s| s.to_string()).collect(),
+    }
+  }
+
+  /// p1 → p2 → p3 → p4, with c3 replacing p1..p3 and c2 replacing p1..p2.
+  fn chain() -> Vec<PatchInfo> {
+    vec![
+      patch("p1", &[]),
+      patch("p2", &[]),
+      patch("c2", &["p1", "p2"]),
+      patch("p3", &[]),
+      patch("c3", &["p1", "p2", "p3"]),
+      patch("p4", &[]),
+    ]
+  }
+
+  fn next(patches: &[PatchInfo]) -> Vec<&str> {
+    patches.iter().filter(|p| p.is_next).map(|p| p.name.as_str()).collect()
+  }
+
+  #[test]
+  fn fresh_install_jumps_to_the_longest_cumulative_patch() {
+    let mut patches = chain();
+    assert_eq!(plan_missing(&mut patches, &[]), vec!["c3", "p4"]);
+    assert_eq!(next(&patches), vec!["c3"]);
+  }
+
+  #[test]
+  fn installed_chain_skips_cumulative_patches() {
+    let mut patches = chain();
+    assert_eq!(plan_missing(&mut patches, &[installed("p1", &[])]), vec!["p2", "p3", "p4"]);
+    assert_eq!(next(&patches), vec!["p2"]);
+  }
+
+  #[test]
+  fn installed_cumulative_patch_counts_what_it_replaced() {
+    let mut patches = chain();
+    assert_eq!(plan_missing(&mut patches, &[installed("c2", &["p1", "p2"])]), vec!["p3", "p4"]);
+  }
+
+  #[test]
+  fn fresh_install_without_cumulative_patches_takes_the_chain() {
+    let mut patches = vec![patch("p1", &[]), patch("p2", &[])];
+    assert_eq!(plan_missing(&mut patches, &[]), vec!["p1", "p2"]);
+    assert_eq!(next(&patches), vec!["p1"]);
+  }
+
+  #[test]
+  fn up_to_date_install_has_nothing_next() {
+    let mut patches = chain();
+    patches[5].is_next = true;
+    let done = [installed("c3", &["p1", "p2", "p3"]), installed("p4", &[])];
+
+    assert!(plan_missing(&mut patches, &done).is_empty());
+    assert!(next(&patches).is_empty());
+  }
+
+  fn manifest_json(patch_name: &str) -> Vec<u8> {
+    let manifest = ReleaseManifest {
+      patch_name: Some(patch_name.to_owned()),
+      ..Default::default()
+    };
+    serde_json::to_vec(&manifest).unwrap()
+  }
+
+  /// Index of `GW 1` listing patch `p1`, whose manifest is at `https://index/p1/manifest.json`.
+  fn index_with_p1() -> ReleaseIndex {
+    ReleaseIndex {
+      schema: 1,
+      generated_at: "".to_owned(),
+      launcher: LauncherIndex {
+        version: "".to_owned(),
+        assets: vec![],
+        bg_etag: None,
+      },
+      releases: vec![ReleaseIndexEntry {
+        name: "GW 1".to_owned(),
+        path: "gw-1".to_owned(),
+        tag: "gw-1".to_owned(),
+        exe_path: None,
+        manifest: "https://index/manifest.json".to_owned(),
+        assets: vec![],
+        patches: vec![IndexPatch {
+          tag: "p1".to_owned(),
+          base_patch: None,
+          notes: Some("from the index".to_owned()),
+          manifest: Some("https://index/p1/manifest.json".to_owned()),
+          assets: vec![IndexAsset {
+            name: "data0.zip".to_owned(),
+            size: 0,
+            url: "https://index/p1/data0.zip".to_owned(),
+            sha256: None,
+          }],
+          supersedes: vec![],
+        }],
+        total_files_count: 0,
+        total_size: 0,
+        compressed_size: 0,
+      }],
+    }
+  }
+
+  /// Updates repo `gw-1-updates` with releases `p1` and `p2`, manifests under `https://api/`.
+  fn with_updates_repo(mut fake: Fake) -> Fake {
+    fake.updates_repos = vec![Project {
+      id: 7,
+      name: "gw-1-updates".to_owned(),
+      path: "gw-1-updates".to_owned(),
+      ssh_remote_url: "".to_owned(),
+      marked_for_deletion_on: None,
+    }];
+    for tag in ["p1", "p2"] {
+      let link = format!("https://api/{}/{}", tag, MANIFEST_NAME);
+      fake.blobs.insert(link.clone(), manifest_json(tag));
+      fake.repo_releases.push(RepoReleaseInfo {
+        tag_name: tag.to_owned(),
+        name: tag.to_owned(),
+        body: Some("from the API".to_owned()),
+        created_at: None,
+        assets: vec![RepoReleaseAsset {
+          name: MANIFEST_NAME.to_owned(),
+          size: None,
+          download_link: link,
+        }],
+      });
+    }
+    fake
+  }
+
+  fn client(fake: &Fake) -> ApiClient {
+    let mut client = ApiClient::new(Arc::new(|_: &str| {}));
+    client.register_provider(fake.clone());
+    client.set_current_provider(fake.id).unwrap();
+    client
+  }
+
+  fn api_calls(fake: &Fake) -> usize {
+    fake.calls().iter().filter(|c| c.starts_with("repo_releases")).count()
+  }
+
+  #[tokio::test]
+  async fn patch_source_prefers_the_release_index() {
+    let mut fake = with_updates_repo(Fake::new("fake", Some(10)));
+    fake.index = Some(index_with_p1());
+    fake.blobs.insert("https://index/p1/manifest.json".to_owned(), manifest_json("p1"));
+    let client = client(&fake);
+
+    let patch = PatchSource::load(&client, "GW 1").await.fetch("p1").await.unwrap();
+    assert_eq!(patch.release.body.as_deref(), Some("from the index"));
+    assert_eq!(patch.release.assets[0].download_link, "https://index/p1/data0.zip");
+    assert_eq!(patch.release.assets[0].size, None);
+    assert_eq!(patch.manifest.patch_name.as_deref(), Some("p1"));
+    assert_eq!(fake.calls(), vec!["blob https://index/p1/manifest.json"]);
+  }
+
+  #[tokio::test]
+  async fn patch_source_falls_back_to_the_api_for_a_stale_index() {
+    // The index lists p1, but its manifest is gone.
+    let mut fake = with_updates_repo(Fake::new("fake", Some(10)));
+    fake.index = Some(index_with_p1());
+    let client = client(&fake);
+
+    let patch = PatchSource::load(&client, "gw-1").await.fetch("p1").await.unwrap();
+    assert_eq!(patch.release.body.as_deref(), Some("from the API"));
+    assert_eq!(patch.manifest.patch_name.as_deref(), Some("p1"));
+    assert_eq!(api_calls(&fake), 1);
+  }
+
+  #[tokio::test]
+  async fn patch_source_lists_the_updates_repo_once() {
+    // No index at all: every patch comes from the API.
+    let fake = with_updates_repo(Fake::new("fake", Some(10)));
+    let client = client(&fake);
+
+    let mut source = PatchSource::load(&client, "GW 1").await;
+    assert_eq!(source.fetch("p1").await.unwrap().release.tag_name, "p1");
+    assert_eq!(source.fetch("p2").await.unwrap().release.tag_name, "p2");
+    let err = source.fetch("p3").await.unwrap_err();
+    assert!(err.to_string().contains("not found in updates repo"), "{}", err);
+
+    assert_eq!(api_calls(&fake), 1);
+    assert!(fake.calls().contains(&"updates_repos GW 1".to_owned()));
   }
-  Ok(())
 }
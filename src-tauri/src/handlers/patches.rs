@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Instant;
@@ -11,15 +12,17 @@ use tokio::{fs::File, sync::broadcast, sync::Mutex};
 use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 
-use crate::consts::{DEFAULT_BRANCH, MANIFEST_NAME};
+use crate::consts::MANIFEST_NAME;
 use crate::handlers::compress::pack_split_archives;
-use crate::handlers::dto::{PatchMeta, ReleaseManifestFile, UploadProgressPayload};
-use crate::handlers::patch_install::{project_id_for, resolve_updates_project};
+use crate::handlers::dto::{PatchDelta, PatchMeta, ReleaseManifest, ReleaseManifestEntry, ReleaseManifestFile, UploadProgressPayload};
+use crate::handlers::patch_install::{download_manifest, project_id_for, resolve_updates_project};
 use crate::handlers::upload_v2::{UploadCancelMap, build_asset_url, make_tag_name};
-use crate::providers::dto::CreateReleaseAsset;
+use crate::providers::ApiClient::ApiClient::ApiClient;
+use crate::providers::dto::{CreateReleaseAsset, RepoReleaseInfo};
 use crate::service::main::Service;
+use crate::utils::delta::DELTA_DIR;
 use crate::utils::errors::log_full_error;
-use crate::utils::patch_collect::{self, RepoTagReport};
+use crate::utils::patch_collect::{self, RepoPatchStatus, RepoTagReport};
 
 /// Patch archives are tiny compared to full releases; keep the same chunk
 /// limit as full releases for consistency (well below the 2 GiB asset limit).
@@ -40,12 +43,22 @@ fn patch_upload_log(app: &tauri::AppHandle, message: String) {
 /// Collects a partial-update patch from the game git repositories:
 /// committed changes (latest reachable tag -> HEAD) of every repo found
 /// under the selected folder. Heavy git/fs work runs on a blocking thread.
+/// `delta_min_size` (bytes) enables binary deltas for big modified files.
 #[tauri::command]
-pub async fn collect_patch(source_dir: String, exclude_patterns: Vec<String>) -> Result<patch_collect::PatchCollectResult, String> {
-  log::info!("collect_patch: source_dir: {}, exclude_patterns: {}", source_dir, exclude_patterns.len());
+pub async fn collect_patch(
+  source_dir: String,
+  exclude_patterns: Vec<String>,
+  delta_min_size: Option<u64>,
+) -> Result<patch_collect::PatchCollectResult, String> {
+  log::info!(
+    "collect_patch: source_dir: {}, exclude_patterns: {}, delta_min_size: {:?}",
+    source_dir,
+    exclude_patterns.len(),
+    delta_min_size
+  );
 
   let result =
-    tokio::task::spawn_blocking(move || patch_collect::collect_patch(std::path::PathBuf::from(source_dir), exclude_patterns))
+    tokio::task::spawn_blocking(move || patch_collect::collect_patch(std::path::PathBuf::from(source_dir), exclude_patterns, delta_min_size))
       .await
       .map_err(|e| e.to_string())?
       .map_err(|e| {
@@ -63,6 +76,70 @@ pub async fn collect_patch(source_dir: String, exclude_patterns: Vec<String>) ->
   Ok(result)
 }
 
+/// Collects a cumulative patch of the release `name`: one patch replacing the
+/// whole published chain, for players who have not installed any patch yet.
+/// The chain comes from the updates repo; the game repos must be checked out
+/// at the tag of its last patch. Published with `upload_patch` (`supersedes`).
+#[tauri::command]
+pub async fn collect_cumulative_patch(
+  service: tauri::State<'_, Arc<Mutex<Service>>>,
+  name: String,
+  source_dir: String,
+  exclude_patterns: Vec<String>,
+  delta_min_size: Option<u64>,
+) -> Result<patch_collect::PatchCollectResult, String> {
+  log::info!("collect_cumulative_patch: release: {}, source_dir: {}", name, source_dir);
+  let api_client = {
+    let service_guard = service.lock().await;
+    service_guard.api_client.clone()
+  };
+
+  let chain = async {
+    let updates_project = resolve_updates_project(&api_client, &name).await?;
+    let project_id = project_id_for(&api_client, &updates_project)?;
+    let releases = api_client.current_provider()?.get_repo_releases(&project_id).await?;
+    regular_patch_chain(&api_client, releases).await
+  }
+  .await
+  .map_err(|e| {
+    log_full_error(&e);
+    e.to_string()
+  })?;
+  if chain.len() < 2 {
+    return Err(format!("Release '{}' has {} patches, nothing to squash", name, chain.len()));
+  }
+
+  let result = tokio::task::spawn_blocking(move || {
+    patch_collect::collect_cumulative_patch(std::path::PathBuf::from(source_dir), exclude_patterns, delta_min_size, chain)
+  })
+  .await
+  .map_err(|e| e.to_string())?
+  .map_err(|e| {
+    log_full_error(&e);
+    e.to_string()
+  })?;
+
+  // A repo left out would make the patch claim a state it does not ship.
+  let failed: Vec<String> = result
+    .repos
+    .iter()
+    .filter(|r| matches!(r.status, RepoPatchStatus::Error))
+    .map(|r| format!("'{}': {}", r.repo_rel_path, r.message.as_deref().unwrap_or("error")))
+    .collect();
+  if !failed.is_empty() {
+    return Err(format!("Cumulative patch is incomplete: {}", failed.join("; ")));
+  }
+
+  log::info!(
+    "collect_cumulative_patch done: supersedes: {}, changed: {}, deleted: {}",
+    result.supersedes.join(", "),
+    result.changed,
+    result.deleted
+  );
+
+  Ok(result)
+}
+
 /// Cancels an in-progress patch upload by patch tag name.
 #[tauri::command]
 pub async fn cancel_patch_upload(cancel_map: tauri::State<'_, UploadCancelMap>, patchName: String) -> Result<(), String> {
@@ -83,6 +160,13 @@ pub async fn cancel_patch_upload(cancel_map: tauri::State<'_, UploadCancelMap>,
 ///
 /// No resume: patches are small, and the resume infrastructure of upload_v2
 /// is bound to the single `progress_upload` slot in the config.
+///
+/// `deltas` / `fullDir` come from a delta-mode collect: the full copies of the
+/// delta files are packed as `fullN.zip` next to the `dataN.zip` archives.
+///
+/// `supersedes` comes from a cumulative collect: the patch then has no
+/// `base_patch` and the game repositories are not tagged (their state is the
+/// last superseded patch, tagged already).
 #[tauri::command]
 pub async fn upload_patch(
   app: tauri::AppHandle,
@@ -94,6 +178,9 @@ pub async fn upload_patch(
   gameSourceDir: Option<String>,
   deletedFiles: Vec<String>,
   baseReleaseTag: Option<String>,
+  deltas: Vec<PatchDelta>,
+  fullDir: Option<String>,
+  supersedes: Vec<String>,
 ) -> Result<PatchUploadResult, String> {
   let patch_name_raw = patchName.trim().to_string();
   if patch_name_raw.is_empty() {
@@ -103,6 +190,10 @@ pub async fn upload_patch(
   if !Path::new(&patchDir).is_dir() {
     return Err(format!("Patch dir does not exist: {}", patchDir));
   }
+  let full_dir = fullDir.filter(|d| !d.is_empty());
+  if !deltas.is_empty() && !full_dir.as_deref().is_some_and(|d| Path::new(d).is_dir()) {
+    return Err("Patch has binary deltas but the folder with the full files is missing".to_string());
+  }
 
   // Cancel map guard (keyed distinctly from full-release uploads).
   let cancel_key = format!("patch:{}", tag_name);
@@ -139,33 +230,44 @@ pub async fn upload_patch(
     e.to_string()
   })?;
 
-  patch_upload_log(&app, format!("Updates repo: {}", &project_id));
+  let branch = api.repo_branch(&project_id).await;
+  patch_upload_log(&app, format!("Updates repo: {} (branch {})", &project_id, &branch));
 
   // ------------------------------------------------------------------
-  // 2. Detect base_patch = latest existing patch release in the chain.
+  // 2. Detect base_patch = latest regular patch release in the chain.
   // ------------------------------------------------------------------
-  let mut repo_releases = api.get_repo_releases(&project_id).await.map_err(|e| {
+  let repo_releases = api.get_repo_releases(&project_id).await.map_err(|e| {
     log_full_error(&e);
     e.to_string()
   })?;
-  // Newest first (None sorts last).
-  repo_releases.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-  let base_patch = repo_releases.first().map(|r| r.tag_name.clone());
   let already_exists = repo_releases.iter().any(|r| r.tag_name == tag_name);
-  if let Some(bp) = &base_patch {
+  let is_first_release = repo_releases.is_empty();
+  let base_patch = if supersedes.is_empty() {
+    // Skips the patch itself when retrying an interrupted upload.
+    let others = repo_releases.into_iter().filter(|r| r.tag_name != tag_name).collect();
+    latest_regular_patch(&api_client, others).await.map_err(|e| {
+      log_full_error(&e);
+      e.to_string()
+    })?
+  } else {
+    None
+  };
+  if !supersedes.is_empty() {
+    patch_upload_log(&app, format!("Cumulative patch, supersedes: {}", supersedes.join(", ")));
+  } else if let Some(bp) = &base_patch {
     patch_upload_log(&app, format!("Base patch: {}", bp));
   } else {
     patch_upload_log(&app, "First patch after full release".to_string());
   }
 
   // Ensure the updates repo has at least one commit so that
-  // create_tag (GitLab, which needs ref=master) and create_release
+  // create_tag (GitLab, which needs an existing ref) and create_release
   // (GitHub, which needs target_commitish) work on freshly created
   // empty repos.
-  if base_patch.is_none() {
+  if is_first_release {
     patch_upload_log(&app, "Initializing empty updates repo ...".to_string());
     let _ = api
-      .add_file_to_repo(&project_id, ".gitkeep", "", "Initialize updates repo", DEFAULT_BRANCH)
+      .add_file_to_repo(&project_id, ".gitkeep", "", "Initialize updates repo", &branch)
       .await;
   }
 
@@ -179,6 +281,8 @@ pub async fn upload_patch(
     base_patch: base_patch.clone(),
     base_release_tag: baseReleaseTag.clone().filter(|s| !s.is_empty()),
     deleted_files: deletedFiles.clone(),
+    deltas,
+    supersedes: supersedes.clone(),
   };
 
   patch_upload_log(&app, "Packing patch archives ...".to_string());
@@ -193,12 +297,38 @@ pub async fn upload_patch(
   )
   .await?;
 
+  // Delta mode: full copies of the delta files for installs whose local file
+  // differs from the delta base.
+  if let Some(full_dir) = &full_dir {
+    patch_upload_log(&app, "Packing full copies of the delta files ...".to_string());
+    let full_pack_dir = pack_dir.join("full");
+    let full = pack_split_archives(
+      &app,
+      full_dir.clone(),
+      full_pack_dir.to_string_lossy().into_owned(),
+      PATCH_CHUNK_SIZE_MB,
+      vec![],
+      None,
+      None,
+    )
+    .await?;
+    merge_full_archives(&mut manifest, full, &pack_dir, &full_pack_dir)?;
+  }
+  if !manifest.deltas.is_empty() {
+    // The delta files themselves are not game files.
+    let prefix = format!("{}/", DELTA_DIR);
+    manifest.entries.retain(|e| !e.path.starts_with(&prefix));
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    fs::write(pack_dir.join(MANIFEST_NAME), json).map_err(|e| e.to_string())?;
+  }
+
   // The patch manifest itself is uploaded as a release asset (NOT committed
   // into the repo: full releases already own the single manifest.json path).
   let manifest_size = fs::metadata(pack_dir.join(MANIFEST_NAME)).map(|m| m.len()).unwrap_or(0);
   manifest.files.push(ReleaseManifestFile {
     name: MANIFEST_NAME.to_string(),
     size: manifest_size,
+    sha256: None,
   });
 
   // ------------------------------------------------------------------
@@ -208,7 +338,7 @@ pub async fn upload_patch(
     patch_upload_log(&app, format!("Tag '{}' already exists (retry after interrupted upload), skipping tag creation", &tag_name));
   } else {
     patch_upload_log(&app, format!("Creating tag '{}' in updates repo ...", &tag_name));
-    if let Err(e) = api.create_tag(&project_id, &tag_name, DEFAULT_BRANCH).await {
+    if let Err(e) = api.create_tag(&project_id, &tag_name, &branch).await {
       patch_upload_log(&app, format!("Warning: create_tag '{}' failed (may already exist): {}", &tag_name, e));
     }
   }
@@ -226,7 +356,7 @@ pub async fn upload_patch(
     .collect();
 
   patch_upload_log(&app, format!("Creating release '{}' ...", &tag_name));
-  let created_release = match api.create_release(&project_id, &tag_name, first_assets).await {
+  let created_release = match api.create_release(&project_id, &tag_name, &branch, first_assets).await {
     Ok(r) => r,
     Err(e) => {
       if already_exists {
@@ -333,6 +463,7 @@ pub async fn upload_patch(
   // ------------------------------------------------------------------
   let mut warnings: Vec<String> = Vec::new();
   let repos: Vec<RepoTagReport> = match gameSourceDir.as_deref().filter(|s| !s.is_empty()) {
+    Some(_) if !supersedes.is_empty() => Vec::new(),
     Some(source) => {
       patch_upload_log(&app, format!("Tagging game repositories with '{}' ...", &tag_name));
       let source = source.to_string();
@@ -362,3 +493,75 @@ pub async fn upload_patch(
 
   Ok(PatchUploadResult { repos, warnings })
 }
+
+/// Manifest of a patch release, None when the release has none.
+async fn patch_manifest(api_client: &ApiClient, release: &RepoReleaseInfo) -> anyhow::Result<Option<ReleaseManifest>> {
+  match release.assets.iter().find(|a| a.name == MANIFEST_NAME) {
+    Some(asset) => Ok(Some(download_manifest(api_client, &asset.download_link).await?)),
+    None => Ok(None),
+  }
+}
+
+/// Newest regular patch of the updates repo (cumulative patches are not links
+/// of the chain), None when there is none yet.
+async fn latest_regular_patch(api_client: &ApiClient, mut releases: Vec<RepoReleaseInfo>) -> anyhow::Result<Option<String>> {
+  // Newest first (None sorts last).
+  releases.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+  for release in releases {
+    let cumulative = patch_manifest(api_client, &release).await?.is_some_and(|m| !m.supersedes.is_empty());
+    if !cumulative {
+      return Ok(Some(release.tag_name));
+    }
+  }
+  Ok(None)
+}
+
+/// Regular patch chain of the updates repo, oldest first: the `base_patch`
+/// links followed back from the newest regular patch.
+async fn regular_patch_chain(api_client: &ApiClient, releases: Vec<RepoReleaseInfo>) -> anyhow::Result<Vec<String>> {
+  let by_tag: HashMap<String, RepoReleaseInfo> = releases.iter().map(|r| (r.tag_name.clone(), r.clone())).collect();
+  let mut chain: Vec<String> = Vec::new();
+  let mut cursor = latest_regular_patch(api_client, releases).await?;
+
+  while let Some(tag) = cursor.take() {
+    if chain.contains(&tag) {
+      anyhow::bail!("Patch chain has a cycle at '{}'", tag);
+    }
+    let release = by_tag
+      .get(&tag)
+      .ok_or_else(|| anyhow::anyhow!("Patch '{}' of the chain is not published", tag))?;
+    let manifest = patch_manifest(api_client, release)
+      .await?
+      .ok_or_else(|| anyhow::anyhow!("Patch '{}' has no manifest.json", tag))?;
+    cursor = manifest.base_patch.filter(|b| !b.is_empty());
+    chain.push(tag);
+  }
+
+  chain.reverse();
+  Ok(chain)
+}
+
+/// Moves the archives packed from the full copies of the delta files next to
+/// the patch archives as `fullN.zip` and records them in the patch manifest.
+fn merge_full_archives(manifest: &mut ReleaseManifest, full: ReleaseManifest, pack_dir: &Path, full_pack_dir: &Path) -> Result<(), String> {
+  let rename = |name: &str| name.replacen("data", "full", 1);
+
+  for file in full.files {
+    let name = rename(&file.name);
+    fs::rename(full_pack_dir.join(&file.name), pack_dir.join(&name)).map_err(|e| e.to_string())?;
+    manifest.compressed_size += file.size;
+    manifest.files.push(ReleaseManifestFile { name, ..file });
+  }
+  for entry in full.entries {
+    let archive = rename(&entry.archive);
+    if let Some(delta) = manifest.deltas.iter_mut().find(|d| d.path == entry.path) {
+      delta.archive = archive.clone();
+    }
+    manifest.entries.push(ReleaseManifestEntry { archive, ..entry });
+  }
+
+  if let Err(e) = fs::remove_dir_all(full_pack_dir) {
+    log::warn!("cannot remove {:?}: {}", full_pack_dir, e);
+  }
+  Ok(())
+}
@@ -25,9 +25,16 @@ pub fn register_handlers(app: Builder<Wry>) -> Builder<Wry> {
     handlers::configs::get_tokens,
     handlers::configs::set_default_install_path,
     handlers::configs::set_default_download_path,
+    handlers::configs::set_download_settings,
     handlers::configs::set_current_game_version,
     handlers::configs::set_current_api_provider,
     handlers::configs::get_api_providers_stats,
+    handlers::configs::get_providers_health,
+    handlers::configs::set_local_provider_path,
+    handlers::configs::set_gitea_host,
+    handlers::configs::set_mirror_base_url,
+    handlers::configs::set_proxy_settings,
+    handlers::configs::get_distribution_profile,
     // user.ltx
     handlers::user_ltx::userltx_set_path,
     // service
@@ -38,7 +45,7 @@ pub fn register_handlers(app: Builder<Wry>) -> Builder<Wry> {
     handlers::service::get_launcher_bg,
     handlers::service::set_token_for_provider,
     handlers::service::get_provider_ids,
-    handlers::service::check_available_disk_space,
+    handlers::service::plan_download_space,
     handlers::service::remove_download_version,
     handlers::service::remove_install_dir,
     handlers::service::move_version,
@@ -47,8 +54,14 @@ pub fn register_handlers(app: Builder<Wry>) -> Builder<Wry> {
     // releases
     handlers::start_download_version::start_download_version,
     handlers::start_download_version::cancel_download_version,
+    handlers::start_download_version::pause_download_version,
     handlers::start_download_version::cancel_all_downloads_and_save,
     handlers::continue_download_version::continue_download_version,
+    handlers::download_jobs::get_download_jobs,
+    handlers::download_jobs::pause_download_job,
+    handlers::download_jobs::resume_download_job,
+    handlers::download_jobs::cancel_download_job,
+    handlers::download_jobs::move_download_job,
     handlers::release::get_available_versions,
     handlers::release::create_release_repos,
     handlers::release::get_release_manifest,
@@ -67,12 +80,22 @@ pub fn register_handlers(app: Builder<Wry>) -> Builder<Wry> {
     handlers::upload_v2::continue_upload_v2,
     // patches (developer: collect & upload)
     handlers::patches::collect_patch,
+    handlers::patches::collect_cumulative_patch,
     handlers::patches::upload_patch,
     handlers::patches::cancel_patch_upload,
     // patches (player: check & install)
     handlers::patch_install::get_version_patches,
     handlers::patch_install::start_install_patch,
+    handlers::patch_install::start_install_patch_chain,
     handlers::patch_install::cancel_install_patch,
+    handlers::patch_install::uninstall_last_patch,
+    // offline bundles
+    handlers::bundle::export_bundle,
+    handlers::bundle::cancel_export_bundle,
+    handlers::bundle::import_bundle,
+    // install verify / repair
+    handlers::verify::verify_installation,
+    handlers::verify::cancel_verify_installation,
     // Profiles
     handlers::profiles::add_profile,
     handlers::profiles::delete_profile,
@@ -2,7 +2,7 @@ use crate::{
   configs::AppConfig::{AppConfig, Version},
   consts::BIN_DIR,
   handlers::dto::{DownlaodFileStat, ReleaseManifest},
-  service::{create_release::ServiceRelease, get_release::ServiceGetRelease, main::Service},
+  service::{create_release::ServiceRelease, get_release::ServiceGetRelease, main::Service, part_file},
   utils::{errors::log_full_error, git::grouping::group_files_by_size, patch_markers::{read_installed_patches, write_patch_marker}, resources::game_exe},
 };
 use anyhow::Context;
@@ -442,10 +442,7 @@ pub async fn emit_file_list_stats(
 
       if file_path.exists() {
         let size = if file_part_path.exists() {
-          match tokio::fs::read_to_string(&file_part_path).await {
-            Ok(content) => content.trim().parse::<u64>().unwrap_or(0),
-            Err(_) => 0,
-          }
+          part_file::downloaded_bytes(&file_part_path).unwrap_or(0)
         } else {
           // No .part → treat as finished download; prefer on-disk size.
           match tokio::fs::metadata(&file_path).await {
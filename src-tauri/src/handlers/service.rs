@@ -6,9 +6,15 @@ use tokio::sync::Mutex;
 
 use crate::{
   configs::AppConfig::AppConfig,
-  handlers::dto::ProgressPayload,
+  handlers::dto::{ProgressPayload, ReleaseManifest},
   providers::dto::ProviderStatus,
-  service::{files::ServiceFiles, main::Service},
+  service::{
+    disk_space::{SpacePlan, fresh_release_plan},
+    download_jobs::DownloadJobs,
+    files::ServiceFiles,
+    main::Service,
+    staging,
+  },
   utils::encoding::*,
 };
 
@@ -132,18 +138,12 @@ pub async fn get_launcher_bg(
   service: tauri::State<'_, Arc<Mutex<Service>>>,
   app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>,
 ) -> Result<Vec<u8>, String> {
-  let (api_client, provider_id) = {
-    let service_guard = service.lock().await;
-    let api = service_guard.api_client.current_provider().map_err(|e| e.to_string())?;
-    (service_guard.api_client.clone(), api.id().to_string())
-  };
-  let url = api_client.current_provider().map_err(|e| e.to_string())?.launcher_bg_url();
+  let api_client = { service.lock().await.api_client.clone() };
+  let api = api_client.current_provider().map_err(|e| e.to_string())?;
+  let url = api.launcher_bg_url().await;
 
   // Fast path: index bg_etag matches the saved one -> serve from disk, no network.
-  let index_bg_etag = crate::service::index::load_index(&provider_id)
-    .await
-    .ok()
-    .and_then(|i| i.launcher.bg_etag);
+  let index_bg_etag = api.release_index().await.ok().and_then(|i| i.launcher.bg_etag);
   let saved_etag = { app_config.lock().await.bg_etag.clone() };
   if let (Some(idx), Some(saved)) = (&index_bg_etag, &saved_etag)
     && idx == saved
@@ -153,14 +153,16 @@ pub async fn get_launcher_bg(
     return Ok(bytes);
   }
 
+  // Local / LAN provider: the bg is a plain file, no HTTP cache involved.
+  if !url.starts_with("http://") && !url.starts_with("https://") {
+    return api.get_launcher_bg().await.map_err(|e| format!("Cannot read launcher bg: {}", e));
+  }
+
   // Slow path: fetch via the ETag disk cache.
-  let cached = crate::utils::http_cache::fetch(
-    &crate::utils::http_cache::SHARED_CLIENT,
-    &url,
-    std::time::Duration::from_secs(crate::consts::CACHE_TTL_BACKGROUND_SECS),
-  )
-  .await
-  .map_err(|e| format!("Cannot fetch launcher bg: {}", e))?;
+  let client = crate::utils::http_cache::shared_client().map_err(|e| format!("Cannot fetch launcher bg: {}", e))?;
+  let cached = crate::utils::http_cache::fetch(&client, &url, std::time::Duration::from_secs(crate::consts::CACHE_TTL_BACKGROUND_SECS))
+    .await
+    .map_err(|e| format!("Cannot fetch launcher bg: {}", e))?;
 
   // Persist the served etag for the fast path next time.
   if let Some(etag) = crate::utils::http_cache::read_etag(&url) {
@@ -215,20 +217,24 @@ pub async fn get_provider_ids(app: tauri::AppHandle) -> Result<Vec<String>, Stri
   Ok(service_guard.api_client.get_provider_ids())
 }
 
+/// Space a fresh download of `manifest` needs on the download and install
+/// volumes; `start_download_version` refuses with the same plan when short.
 #[tauri::command]
-pub async fn check_available_disk_space(path: String, needed: u64) -> Result<bool, String> {
-  let path = Path::new(&path);
-  let bytes = fs4::available_space(path).map_err(|e| e.to_string())?;
-
-  if bytes > needed {
-    return Ok(true);
-  }
-
-  Ok(false)
+pub async fn plan_download_space(manifest: ReleaseManifest, downloadPath: String, installPath: String) -> Result<SpacePlan, String> {
+  fresh_release_plan(Path::new(&downloadPath), Path::new(&installPath), &manifest).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn remove_download_version(app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>, versionName: String) -> Result<(), String> {
+pub async fn remove_download_version(
+  app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+  jobs: tauri::State<'_, Arc<DownloadJobs>>,
+  versionName: String,
+) -> Result<(), String> {
+  // A paused job would otherwise resume into the deleted directory.
+  if jobs.list().iter().any(|j| j.id == versionName) {
+    jobs.cancel(&versionName).await?;
+  }
+
   let version = {
     let cfg = app_config.lock().await;
     cfg
@@ -259,7 +265,7 @@ pub async fn remove_download_version(app_config: tauri::State<'_, Arc<Mutex<AppC
   Ok(())
 }
 
-// Cancel-only cleanup of the partial install dir (where archives were being
+// Cancel-only cleanup of the partial install (where archives were being
 // unpacked). Reads installed_path from progress_download, so it must be called
 // BEFORE remove_download_version/clear_progress_version (which delete that
 // entry). Best-effort: tolerate NotFound. NOTE: remove_download_version must
@@ -267,15 +273,24 @@ pub async fn remove_download_version(app_config: tauri::State<'_, Arc<Mutex<AppC
 // where the install dir is the installed game.
 #[tauri::command]
 pub async fn remove_install_dir(app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>, versionName: String) -> Result<(), String> {
-  let install_path = {
+  let (install_path, unpacked_in_place) = {
     let cfg = app_config.lock().await;
     cfg
       .progress_download
       .get(&versionName)
-      .map(|v| v.installed_path.clone())
+      .map(|v| {
+        let staged = staging::staging_path(Path::new(&v.installed_path)).exists();
+        (v.installed_path.clone(), !staged && v.files.values().any(|f| f.is_unpacked))
+      })
       .ok_or_else(|| format!("remove_install_dir: version not found: {}", &versionName))?
   };
 
+  // Archives are unpacked into the staging dir: the install dir still holds
+  // whatever was there before the download and is left alone.
+  if !unpacked_in_place {
+    return staging::discard(Path::new(&install_path)).map_err(|e| e.to_string());
+  }
+
   match fs::remove_dir_all(Path::new(&install_path)) {
     Ok(_) => {}
     Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
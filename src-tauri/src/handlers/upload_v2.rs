@@ -10,7 +10,7 @@ use tokio_util::io::ReaderStream;
 
 use crate::{
   configs::AppConfig::{AppConfig, VersionProgressUpload},
-  consts::{DEFAULT_BRANCH, MANIFEST_NAME},
+  consts::MANIFEST_NAME,
   handlers::dto::{ReleaseManifest, UploadProgressPayload},
   providers::dto::CreateReleaseAsset,
   service::get_release::ServiceGetRelease,
@@ -44,6 +44,8 @@ struct UploadContext {
   manifest_release: ReleaseManifest,
   tag_name: String,
   project_id: String,
+  /// Default branch of the repo: target of the manifest commit, tag and release.
+  branch: String,
   /// Asset URLs filled after `step_create_release` (template with <FILE_NAME>).
   upload_url: Arc<Mutex<String>>,
 }
@@ -71,7 +73,7 @@ pub(crate) fn build_asset_url(upload_template: &str, project_id: &str, namespace
 async fn step_manifest_upload(ctx: &UploadContext, api: &(dyn crate::providers::ApiProvider::ApiProvider + Send + Sync)) -> Result<(), String> {
   upload_log(&ctx.app, format!("Uploading {} ...", MANIFEST_NAME));
   api
-    .add_file_to_repo(&ctx.project_id, MANIFEST_NAME, &ctx.manifest_content, "Upload manifest.json", DEFAULT_BRANCH)
+    .add_file_to_repo(&ctx.project_id, MANIFEST_NAME, &ctx.manifest_content, "Upload manifest.json", &ctx.branch)
     .await
     .map_err(|e| {
       log_full_error(&e);
@@ -95,7 +97,7 @@ async fn step_manifest_upload(ctx: &UploadContext, api: &(dyn crate::providers::
 /// ------------------------------------------------------------------
 async fn step_create_tag(ctx: &UploadContext, api: &(dyn crate::providers::ApiProvider::ApiProvider + Send + Sync)) -> Result<(), String> {
   upload_log(&ctx.app, format!("Creating tag '{}' ...", &ctx.tag_name));
-  api.create_tag(&ctx.project_id, &ctx.tag_name, DEFAULT_BRANCH).await.map_err(|e| {
+  api.create_tag(&ctx.project_id, &ctx.tag_name, &ctx.branch).await.map_err(|e| {
     log_full_error(&e);
     format!("create_tag '{}' failed: {}", &ctx.tag_name, e)
   })?;
@@ -127,7 +129,7 @@ async fn step_create_release(ctx: &UploadContext, api: &(dyn crate::providers::A
     .collect();
 
   upload_log(&ctx.app, format!("Creating release for tag '{}' ...", &ctx.tag_name));
-  let created_release = api.create_release(&ctx.project_id, &ctx.tag_name, first_assets).await.map_err(|e| {
+  let created_release = api.create_release(&ctx.project_id, &ctx.tag_name, &ctx.branch, first_assets).await.map_err(|e| {
     log_full_error(&e);
     format!("create_release '{}' failed: {}", &ctx.tag_name, e)
   })?;
@@ -419,6 +421,7 @@ pub async fn upload_v2_release(
   let main_repos = api.get_release_repos_by_name(&release.name).await.map_err(|e| { log_full_error(&e); e.to_string() })?;
   let project = main_repos.first().ok_or_else(|| format!("No repositories found for release '{}'", &release.name))?;
   let project_id = if api.is_suppot_subgroups() { project.id.to_string() } else { project.name.clone() };
+  let branch = api.repo_branch(&project_id).await;
 
   let tag_name = make_tag_name(&name);
 
@@ -459,18 +462,20 @@ pub async fn upload_v2_release(
     manifest_release,
     tag_name,
     project_id,
+    branch,
     upload_url: Arc::new(Mutex::new(String::new())),
   };
 
-  // Run all steps in order.
+  // Run all steps in order, as a background job: a GitHub quota reset is waited for until cancelled.
   let api_ref = api_client.current_provider().map_err(|e| { log_full_error(&e); e.to_string() })?;
-  step_manifest_upload(&ctx, api_ref).await?;
-  step_create_tag(&ctx, api_ref).await?;
-  step_create_release(&ctx, api_ref).await?;
-  step_upload_assets(&ctx, api_ref).await?;
-  step_finalize(&ctx, api_ref, release_id).await?;
-
-  Ok(())
+  crate::utils::background::scope(&ctx.cancel_tx, async {
+    step_manifest_upload(&ctx, api_ref).await?;
+    step_create_tag(&ctx, api_ref).await?;
+    step_create_release(&ctx, api_ref).await?;
+    step_upload_assets(&ctx, api_ref).await?;
+    step_finalize(&ctx, api_ref, release_id).await
+  })
+  .await
 }
 
 // ==================================================================
@@ -537,6 +542,7 @@ pub async fn continue_upload_v2(
   // release_id is saved in progress — no need to re-fetch releases (which may not
   // include the unpublished release being uploaded).
   let release_id = progress.release_id.clone();
+  let branch = api.repo_branch(&progress.project_id).await;
 
   let ctx = UploadContext {
     app: app.clone(),
@@ -548,21 +554,23 @@ pub async fn continue_upload_v2(
     manifest_release,
     tag_name: progress.tag_name.clone(),
     project_id: progress.project_id.clone(),
+    branch,
     upload_url: Arc::new(Mutex::new(progress.upload_url.clone())),
   };
 
   // Run only unfinished steps.
-  if !progress.manifest_uploaded {
-    step_manifest_upload(&ctx, api).await?;
-  }
-  if !progress.tag_created {
-    step_create_tag(&ctx, api).await?;
-  }
-  if !progress.release_created {
-    step_create_release(&ctx, api).await?;
-  }
-  step_upload_assets(&ctx, api).await?;
-  step_finalize(&ctx, api, release_id).await?;
-
-  Ok(())
+  crate::utils::background::scope(&ctx.cancel_tx, async {
+    if !progress.manifest_uploaded {
+      step_manifest_upload(&ctx, api).await?;
+    }
+    if !progress.tag_created {
+      step_create_tag(&ctx, api).await?;
+    }
+    if !progress.release_created {
+      step_create_release(&ctx, api).await?;
+    }
+    step_upload_assets(&ctx, api).await?;
+    step_finalize(&ctx, api, release_id).await
+  })
+  .await
 }
@@ -0,0 +1,122 @@
+use std::{path::PathBuf, sync::Arc};
+
+use tauri::Emitter;
+use tokio::sync::{Mutex, broadcast};
+
+use crate::{
+  configs::AppConfig::{AppConfig, DownloadJobState},
+  handlers::upload_v2::UploadCancelMap,
+  service::{
+    download_jobs::DownloadJobs,
+    files::ServiceFiles,
+    main::Service,
+    unpack::ServiceUnpacker,
+    verify::{self, VerifyReport},
+  },
+  utils::errors::log_full_error,
+};
+
+const EVT_VERIFY_PROGRESS: &str = "verify-progress";
+const EVT_VERIFY_LOG: &str = "verify-log";
+
+fn cancel_key(version_name: &str) -> String {
+  format!("verify:{}", version_name)
+}
+
+/// Checks the installed files of a version against its manifest (and the
+/// manifests of its installed patches). With `repair` the missing and
+/// modified files are downloaded again; the returned report then lists what
+/// is still broken afterwards.
+#[tauri::command]
+pub async fn verify_installation(
+  app: tauri::AppHandle,
+  cancel_map: tauri::State<'_, UploadCancelMap>,
+  app_config: tauri::State<'_, Arc<Mutex<AppConfig>>>,
+  service: tauri::State<'_, Arc<Mutex<Service>>>,
+  service_files: tauri::State<'_, Arc<ServiceFiles>>,
+  service_unpack: tauri::State<'_, Arc<ServiceUnpacker>>,
+  jobs: tauri::State<'_, Arc<DownloadJobs>>,
+  versionName: String,
+  repair: bool,
+) -> Result<VerifyReport, String> {
+  // A patch being installed changes the files under the check; a queued one may start during it.
+  if jobs
+    .list()
+    .iter()
+    .any(|j| j.version_name == versionName && matches!(j.state, DownloadJobState::Queued | DownloadJobState::Running))
+  {
+    return Err("PATCH_INSTALL_ALREADY_RUNNING".to_string());
+  }
+
+  let key = cancel_key(&versionName);
+  if crate::utils::locks::lock(&cancel_map).contains_key(&key) {
+    return Err("VERIFY_ALREADY_RUNNING".to_string());
+  }
+  let (cancel_tx, _) = broadcast::channel::<()>(1);
+  crate::utils::locks::lock(&cancel_map).insert(key.clone(), cancel_tx.clone());
+  scopeguard::defer! { crate::utils::locks::lock(&cancel_map).remove(&key); };
+
+  let installed_path = {
+    let cfg = app_config.lock().await;
+    cfg
+      .installed_versions
+      .values()
+      .find(|v| v.name == versionName)
+      .map(|v| PathBuf::from(&v.installed_path))
+      .ok_or_else(|| format!("Version '{}' is not installed", &versionName))?
+  };
+  let api_client = service.lock().await.api_client.clone();
+
+  let result: anyhow::Result<VerifyReport> = async {
+    let plan = Arc::new(verify::load_plan(&api_client, &versionName, &installed_path).await?);
+
+    let (app_c, vn, path, plan_c, cancel) = (
+      app.clone(),
+      versionName.clone(),
+      installed_path.clone(),
+      plan.clone(),
+      cancel_tx.subscribe(),
+    );
+    let report = tokio::task::spawn_blocking(move || {
+      verify::verify(&vn, &path, &plan_c, None, cancel, |done, total| {
+        // Every 64th file is plenty for a progress bar over thousands of files.
+        if done % 64 == 0 || done == total {
+          let _ = app_c.emit(EVT_VERIFY_PROGRESS, (&vn, done, total));
+        }
+      })
+    })
+    .await??;
+
+    if !repair || report.is_intact() {
+      return Ok(report);
+    }
+    verify::repair(
+      &api_client,
+      &service_files,
+      &service_unpack,
+      &installed_path,
+      &plan,
+      &report,
+      &cancel_tx,
+      |msg| {
+        log::info!("{}", &msg);
+        let _ = app.emit(EVT_VERIFY_LOG, msg);
+      },
+    )
+    .await
+  }
+  .await;
+
+  result.map_err(|e| {
+    log_full_error(&e);
+    e.to_string()
+  })
+}
+
+#[tauri::command]
+pub async fn cancel_verify_installation(cancel_map: tauri::State<'_, UploadCancelMap>, versionName: String) -> Result<(), String> {
+  if let Some(tx) = crate::utils::locks::lock(&cancel_map).get(&cancel_key(&versionName)) {
+    let _ = tx.send(());
+  }
+  Ok(())
+}
@@ -2,8 +2,8 @@ use tauri::Manager;
 use tauri_plugin_window_state::{AppHandleExt, StateFlags};
 
 use crate::configs::AppConfig::AppConfig;
-use crate::handlers::start_download_version::CancelMap;
 use crate::handlers::upload_v2::UploadCancelMap;
+use crate::service::download_jobs::DownloadJobs;
 
 /// Cancels every active download, flushes the config, then exits.
 /// `app_exit` is invoked from synchronous contexts (frontend invoke), so the
@@ -32,19 +32,7 @@ pub fn app_exit(app: tauri::AppHandle) {
 /// one final defensive save of the whole config. This is the fix for the bug
 /// where closing the launcher via `process::exit` was dropping in-memory progress.
 pub async fn graceful_shutdown(app: &tauri::AppHandle) {
-  // Cancel all active downloads so workers stop writing and flush their .part files.
-  if let Some(channel_map) = app.try_state::<CancelMap>() {
-    let senders: Vec<tokio::sync::broadcast::Sender<()>> = {
-      let map = crate::utils::locks::lock(&channel_map);
-      map.iter().map(|(_, v)| v.clone()).collect()
-    };
-
-    for tx in senders {
-      let _ = tx.send(());
-    }
-  }
-
-  // Cancel all active uploads as well.
+  // Cancel all active uploads.
   if let Some(upload_map) = app.try_state::<UploadCancelMap>() {
     let senders: Vec<tokio::sync::broadcast::Sender<()>> = {
       let map = crate::utils::locks::lock(&upload_map);
@@ -56,17 +44,19 @@ pub async fn graceful_shutdown(app: &tauri::AppHandle) {
     }
   }
 
-  // Wait until cancel maps are empty (workers finished) or timeout.
+  // Stop running download jobs; they stay queued and resume on the next start.
+  // Waits until the workers flushed their .part files (bounded).
+  if let Some(jobs) = app.try_state::<std::sync::Arc<DownloadJobs>>() {
+    jobs.shutdown().await;
+  }
+
+  // Wait until the upload map is empty (workers finished) or timeout.
   for _ in 0..20 {
-    let downloads_busy = app
-      .try_state::<CancelMap>()
-      .map(|m| !crate::utils::locks::lock(&m).is_empty())
-      .unwrap_or(false);
     let uploads_busy = app
       .try_state::<UploadCancelMap>()
       .map(|m| !crate::utils::locks::lock(&m).is_empty())
       .unwrap_or(false);
-    if !downloads_busy && !uploads_busy {
+    if !uploads_busy {
       break;
     }
     tokio::time::sleep(std::time::Duration::from_millis(100)).await;
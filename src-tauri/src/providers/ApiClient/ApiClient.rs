@@ -27,6 +27,14 @@ impl ApiClient {
     self.providers.insert(id, Box::new(provider));
   }
 
+  /// Removes a provider; the current selection is dropped when it pointed to it.
+  pub fn unregister_provider(&mut self, id: &str) {
+    self.providers.remove(id);
+    if self.current_provider_id.as_deref() == Some(id) {
+      self.current_provider_id = None;
+    }
+  }
+
   pub fn set_current_provider(&mut self, id: &str) -> anyhow::Result<()> {
     log::info!("set_current_provider, id: {}", &id);
     if self.providers.contains_key(id) {
@@ -105,6 +113,15 @@ impl ApiClient {
     available
   }
 
+  /// Rebuilds the HTTP client of every provider, e.g. after the proxy settings changed.
+  pub fn rebuild_clients(&self) -> Result<()> {
+    for provider in self.providers.values() {
+      provider.rebuild_client()?;
+    }
+
+    Ok(())
+  }
+
   pub async fn set_tokens(&self, tokens: HashMap<String, String>) -> Result<()> {
     for (id, token) in tokens {
       let provider = self.get_provider(&id)?;
@@ -5,7 +5,12 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use futures_util::Stream;
 
-use crate::providers::dto::*;
+use crate::{consts::DEFAULT_BRANCH, providers::dto::*, service::index::ReleaseIndex};
+
+/// Error of an operation the provider cannot do (read-only providers, no groups, ...).
+pub fn not_supported(provider_id: &str, op: &str) -> anyhow::Error {
+  anyhow::anyhow!("'{}' is not supported by provider '{}'", op, provider_id)
+}
 
 #[async_trait]
 pub trait ApiProvider: Send + Sync {
@@ -23,12 +28,18 @@ pub trait ApiProvider: Send + Sync {
 
   fn set_token(&self, token: String) -> Result<()>;
   fn get_token(&self) -> String;
+  /// Rebuilds the HTTP client with the current proxy settings (token kept).
+  fn rebuild_client(&self) -> Result<()>;
 
   async fn load_manifest(&self) -> Result<()>;
+  /// Static release index of this provider (`service::index`).
+  async fn release_index(&self) -> Result<ReleaseIndex> {
+    crate::service::index::load_index(self.id()).await
+  }
   fn get_manifest(&self) -> Result<Manifest>;
 
   async fn get_launcher_bg(&self) -> Result<Vec<u8>>;
-  fn launcher_bg_url(&self) -> String;
+  async fn launcher_bg_url(&self) -> String;
   async fn get_file_raw(&self, project_id: &str, file_path: &str) -> Result<Vec<u8>>;
   async fn get_blob_stream(
     &self,
@@ -41,30 +52,68 @@ pub trait ApiProvider: Send + Sync {
   async fn tree(&self, repo_id: &str, search_params: HashMap<String, String>) -> Result<Vec<TreeItem>>;
   async fn get_full_tree(&self, repo_id: String) -> Result<Vec<TreeItem>>;
   async fn get_file_content_size(&self, direct_url: &str) -> Result<u64>;
-  async fn add_file_to_repo(&self, repo_id: &str, file_name: &str, content: &str, commmit_msg: &str, branch: &str) -> Result<()>;
+
+  // Write operations and issues: the defaults are those of a read-only
+  // provider (Mirror, Local), the git providers override them.
+  async fn add_file_to_repo(&self, _repo_id: &str, _file_name: &str, _content: &str, _commmit_msg: &str, _branch: &str) -> Result<()> {
+    Err(not_supported(self.id(), "add_file_to_repo"))
+  }
   async fn upload_release_file(
     &self,
-    url: &str,
-    content_length: u64,
-    stream: Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Unpin>,
-  ) -> Result<()>;
+    _url: &str,
+    _content_length: u64,
+    _stream: Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Unpin>,
+  ) -> Result<()> {
+    Err(not_supported(self.id(), "upload_release_file"))
+  }
 
-  async fn find_issue(&self, repo_id: &str, search_params: HashMap<String, String>) -> Result<Vec<Issue>>;
-  async fn find_user(&self, repo_id: &str, uuid: &str) -> Result<Option<Issue>>;
+  /// No issue tracker: behaves like "nothing found".
+  async fn find_issue(&self, _repo_id: &str, _search_params: HashMap<String, String>) -> Result<Vec<Issue>> {
+    Ok(vec![])
+  }
+  async fn find_user(&self, _repo_id: &str, _uuid: &str) -> Result<Option<Issue>> {
+    Ok(None)
+  }
 
-  async fn create_tag(&self, repo_id: &str, tag_name: &str, branch: &str) -> Result<()>;
-  async fn create_release(&self, repo_id: &str, tag_name: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse>;
+  /// Default branch of a repo (cached per repo): the target of manifest commits, tags and releases.
+  async fn get_default_branch(&self, _repo_id: &str) -> Result<String> {
+    Err(not_supported(self.id(), "get_default_branch"))
+  }
+  /// `get_default_branch`, or `DEFAULT_BRANCH` when the provider cannot tell.
+  /// Costs an API call on the first use: for uploads and publishing only, raw
+  /// reads use a fixed ref.
+  async fn repo_branch(&self, repo_id: &str) -> String {
+    match self.get_default_branch(repo_id).await {
+      Ok(branch) => branch,
+      Err(e) => {
+        log::warn!("Cannot get default branch of '{}', using '{}': {:#}", repo_id, DEFAULT_BRANCH, e);
+        DEFAULT_BRANCH.to_owned()
+      }
+    }
+  }
+  async fn create_tag(&self, _repo_id: &str, _tag_name: &str, _branch: &str) -> Result<()> {
+    Err(not_supported(self.id(), "create_tag"))
+  }
+  async fn create_release(&self, _repo_id: &str, _tag_name: &str, _branch: &str, _assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
+    Err(not_supported(self.id(), "create_release"))
+  }
   fn get_asset_url(&self) -> String;
 
   async fn get_launcher_latest_release(&self, owner: &str, project_id: &str) -> Result<ReleaseGit>;
   async fn get_releases(&self, cashed: bool) -> Result<Vec<Release>>;
-  async fn set_release_visibility(&self, release_id: &str, visibility: bool) -> Result<()>;
+  async fn set_release_visibility(&self, _release_id: &str, _visibility: bool) -> Result<()> {
+    Err(not_supported(self.id(), "set_release_visibility"))
+  }
   async fn get_release_repos_by_name(&self, release_id: &str) -> Result<Vec<Project>>;
   async fn get_updates_repos_by_name(&self, release_name: &str) -> Result<Vec<Project>>;
   async fn get_repo_releases(&self, project_id: &str) -> Result<Vec<RepoReleaseInfo>>;
 
-  async fn create_group(&self, name: &str, parent_id: &u32) -> Result<CreategGroupResponse>;
-  async fn create_repo(&self, name: &str, description: &str, parent_id: &str) -> Result<CreateRepoResponse>;
+  async fn create_group(&self, _name: &str, _parent_id: &u32) -> Result<CreategGroupResponse> {
+    Err(not_supported(self.id(), "create_group"))
+  }
+  async fn create_repo(&self, _name: &str, _description: &str, _parent_id: &str) -> Result<CreateRepoResponse> {
+    Err(not_supported(self.id(), "create_repo"))
+  }
 
   //
   fn clone_box(&self) -> Box<dyn ApiProvider + Send + Sync>;
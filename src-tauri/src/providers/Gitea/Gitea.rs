@@ -0,0 +1,304 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::Stream;
+use rand_agents::user_agent;
+use reqwest::{
+  Client,
+  header::{AUTHORIZATION, HeaderMap, HeaderValue},
+};
+
+use crate::{
+  configs::DistributionProfile::GiteaProfile,
+  consts::{DEFAULT_BRANCH, GITEA_PID},
+  providers::{
+    ApiProvider::ApiProvider,
+    Gitea::{files::*, issues::*, launcher::*, models::*, release::*, repo::*},
+    dto::{Issue, *},
+  },
+  service::main::LogCallback,
+  utils::http_cache,
+};
+
+/// Gitea / Forgejo provider. Both expose the same `/api/v1` REST API, the
+/// layout of the mirror follows GitHub: one organization, flat repos named
+/// `<release>_main_N` / `<release>_updates_N` with the release name in the
+/// repo description.
+#[derive(Clone)]
+pub struct Gitea {
+  /// API root, e.g. `https://git.example.org/api/v1`.
+  pub host: String,
+  /// Web root, e.g. `https://git.example.org` (raw files, clone urls).
+  pub web_host: String,
+  pub profile: GiteaProfile,
+  pub client: Arc<Mutex<Client>>,
+  pub suppot_subgroups: bool,
+
+  pub status: Arc<Mutex<ProviderStatus>>,
+  pub manifest: Arc<Mutex<Manifest>>,
+  /// Default branch per repo id, see `get_default_branch`.
+  pub default_branches: Arc<Mutex<HashMap<String, String>>>,
+  pub projects_map: Arc<Mutex<HashMap<u32, ProjectGitea>>>,
+
+  pub logger: LogCallback,
+
+  token: Arc<Mutex<String>>,
+}
+
+impl Gitea {
+  pub fn new(profile: &GiteaProfile, suppot_subgroups: bool, logger: LogCallback) -> Result<Self> {
+    let user_agent = user_agent();
+    log::info!("Start init Gitea client with User-Agent: {}", &user_agent);
+
+    let mut headers = HeaderMap::new();
+    headers.insert("User-Agent", HeaderValue::from_str(&user_agent)?);
+
+    let client = crate::utils::proxy::client_builder()?.default_headers(headers).build()?;
+    let web_host = profile.host.trim_end_matches('/').to_string();
+
+    Ok(Self {
+      host: format!("{}/api/v1", &web_host),
+      web_host,
+      profile: profile.clone(),
+      client: Arc::new(Mutex::new(client)),
+      status: Arc::new(Mutex::new(ProviderStatus {
+        available: false,
+        latency_ms: None,
+        rate_limit: None,
+      })),
+      suppot_subgroups,
+      projects_map: Arc::new(Mutex::new(HashMap::new())),
+      manifest: Arc::new(Mutex::new(Manifest { root_id: None, max_size: 0 })),
+      default_branches: Arc::new(Mutex::new(HashMap::new())),
+      token: Arc::new(Mutex::new("".to_owned())),
+      logger,
+    })
+  }
+
+  pub fn get_client(&self) -> Client {
+    crate::utils::locks::lock(&self.client).clone()
+  }
+  pub fn get(&self, url: &str) -> reqwest::RequestBuilder {
+    self.get_client().get(url)
+  }
+  pub fn post(&self, url: &str) -> reqwest::RequestBuilder {
+    self.get_client().post(url)
+  }
+  pub fn put(&self, url: &str) -> reqwest::RequestBuilder {
+    self.get_client().put(url)
+  }
+  pub fn patch(&self, url: &str) -> reqwest::RequestBuilder {
+    self.get_client().patch(url)
+  }
+  pub fn head(&self, url: &str) -> reqwest::RequestBuilder {
+    self.get_client().head(url)
+  }
+
+  /// GET with ETag disk cache (same semantics as Github::get_cached).
+  pub async fn get_cached(
+    &self,
+    url: &str,
+    ttl: Duration,
+  ) -> anyhow::Result<http_cache::CachedBody> {
+    let client = self.get_client();
+    http_cache::fetch(&client, url, ttl).await
+  }
+
+  /// Raw file url on the web host: `{web}/{org}/{repo}/raw/branch/{DEFAULT_BRANCH}/{path}`.
+  pub fn raw_url(&self, repo: &str, file_path: &str) -> String {
+    format!(
+      "{}/{}/{}/raw/branch/{}/{}",
+      self.web_host, self.profile.org, repo, DEFAULT_BRANCH, file_path
+    )
+  }
+}
+
+#[async_trait]
+impl ApiProvider for Gitea {
+  fn set_token(&self, token: String) -> Result<()> {
+    *crate::utils::locks::lock(&self.token) = token.clone();
+
+    let user_agent = user_agent();
+    let mut headers = HeaderMap::new();
+    if !token.is_empty() {
+      headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("token {}", token))?);
+    }
+    headers.insert("User-Agent", HeaderValue::from_str(&user_agent)?);
+
+    *crate::utils::locks::lock(&self.client) = crate::utils::proxy::client_builder()?.default_headers(headers).build()?;
+
+    Ok(())
+  }
+  fn get_token(&self) -> String {
+    crate::utils::locks::lock(&self.token).clone()
+  }
+  fn rebuild_client(&self) -> Result<()> {
+    self.set_token(self.get_token())
+  }
+
+  fn id(&self) -> &'static str {
+    GITEA_PID
+  }
+  async fn ping(&self) -> ProviderStatus {
+    // `/version` is public and cheap on both Gitea and Forgejo.
+    let url = format!("{}/version", &self.host);
+    log::info!("Start PING provider: {}, url: {}", self.id(), &url);
+
+    let start = Instant::now();
+    let res = self
+      .get(&url)
+      .timeout(Duration::from_secs(10))
+      .send()
+      .await;
+
+    let elapsed = start.elapsed();
+    let latency_ms = elapsed.as_millis() as u64;
+    let available = match &res {
+      Ok(r) => r.status().is_success(),
+      Err(_) => false,
+    };
+    let status_str = match &res {
+      Ok(r) => format!("{}", r.status()),
+      Err(e) => format!("error: {}", e),
+    };
+
+    log::info!("PING provider {} result, status: {}, ms: {}", self.id(), status_str, &latency_ms);
+
+    let new_status = ProviderStatus {
+      available,
+      latency_ms: if available { Some(latency_ms) } else { None },
+      rate_limit: None,
+    };
+
+    *crate::utils::locks::lock(&self.status) = new_status.clone();
+    new_status
+  }
+  fn status(&self) -> ProviderStatus {
+    crate::utils::locks::lock(&self.status).clone()
+  }
+  fn is_available(&self) -> bool {
+    self.status().available
+  }
+  fn is_suppot_subgroups(&self) -> bool {
+    self.suppot_subgroups
+  }
+
+  async fn load_manifest(&self) -> Result<()> {
+    __load_manifest(self).await
+  }
+  fn get_manifest(&self) -> Result<Manifest> {
+    Ok(crate::utils::locks::lock(&self.manifest).clone())
+  }
+
+  fn get_asset_url(&self) -> String {
+    // Release attachments get their urls only after the upload.
+    "".to_string()
+  }
+
+  // Files API
+  async fn get_file_content_size(&self, direct_url: &str) -> Result<u64> {
+    __get_file_content_size(self, direct_url).await
+  }
+  async fn get_launcher_bg(&self) -> Result<Vec<u8>> {
+    __get_launcher_bg(self).await
+  }
+  async fn launcher_bg_url(&self) -> String {
+    self.raw_url(&self.profile.launcher_repo, &self.profile.bg_path)
+  }
+  async fn get_file_raw(&self, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
+    __get_file_raw(self, project_id, file_path).await
+  }
+  async fn get_blob_stream(
+    &self,
+    project_id: &str,
+    blob_sha: &str,
+    seek: &Option<u64>,
+  ) -> Result<BlobStreamWithOffset> {
+    __get_blob_stream(self, project_id, blob_sha, seek).await
+  }
+  async fn get_blob_direct_url(&self, project_id: &str, blob_sha: &str) -> String {
+    __get_blob_direct_url(self, project_id, blob_sha).await
+  }
+  async fn get_blob_by_url_stream(&self, link: &str, seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+    __get_blob_by_url_stream(self, link, seek).await
+  }
+  async fn tree(&self, repo_id: &str, search_params: HashMap<String, String>) -> Result<Vec<TreeItem>> {
+    __tree(self, repo_id, search_params).await
+  }
+  async fn get_full_tree(&self, repo_id: String) -> Result<Vec<TreeItem>> {
+    __get_full_tree(self, &repo_id).await
+  }
+  async fn add_file_to_repo(&self, repo_id: &str, file_name: &str, content: &str, commmit_msg: &str, branch: &str) -> Result<()> {
+    __add_file_to_repo(self, repo_id, file_name, content, commmit_msg, branch).await
+  }
+  async fn upload_release_file(
+    &self,
+    url: &str,
+    content_length: u64,
+    stream: Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Unpin>,
+  ) -> Result<()> {
+    __upload_release_file(self, url, content_length, stream).await
+  }
+
+  async fn create_tag(&self, repo_id: &str, tag_name: &str, branch: &str) -> Result<()> {
+    __create_tag(self, repo_id, tag_name, branch).await
+  }
+
+  async fn get_default_branch(&self, repo_id: &str) -> Result<String> {
+    __get_default_branch(self, repo_id).await
+  }
+
+  async fn create_release(&self, repo_id: &str, tag_name: &str, branch: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
+    __create_release(self, repo_id, tag_name, branch, assets).await
+  }
+
+  // Issues API
+  async fn find_issue(&self, repo_id: &str, search_params: HashMap<String, String>) -> Result<Vec<Issue>> {
+    __find_issue(self, repo_id, search_params).await
+  }
+  async fn find_user(&self, repo_id: &str, uuid: &str) -> Result<Option<Issue>> {
+    __find_user(self, repo_id, uuid).await
+  }
+
+  // Repo API
+  async fn create_repo(&self, name: &str, description: &str, _parent_id: &str) -> Result<CreateRepoResponse> {
+    __create_repo(self, name, description).await
+  }
+
+  // Groups API
+  async fn create_group(&self, _name: &str, _parent_id: &u32) -> Result<CreategGroupResponse> {
+    // Releases are flat repos inside the Gitea org.
+    Err(anyhow!("create_group is not supported by Gitea"))
+  }
+
+  // Release
+  async fn get_launcher_latest_release(&self, owner: &str, project_id: &str) -> Result<ReleaseGit> {
+    __get_launcher_latest_release(self, owner, project_id).await
+  }
+
+  async fn get_releases(&self, cashed: bool) -> Result<Vec<Release>> {
+    __get_releases(self, cashed).await
+  }
+  async fn set_release_visibility(&self, release_name: &str, visibility: bool) -> Result<()> {
+    __set_release_visibility(self, release_name, visibility).await
+  }
+  async fn get_release_repos_by_name(&self, release_id: &str) -> Result<Vec<Project>> {
+    __get_release_repos_by_name(self, release_id).await
+  }
+  async fn get_updates_repos_by_name(&self, release_name: &str) -> Result<Vec<Project>> {
+    __get_updates_repos_by_name(self, release_name).await
+  }
+  async fn get_repo_releases(&self, project_id: &str) -> Result<Vec<RepoReleaseInfo>> {
+    __get_repo_releases(self, project_id).await
+  }
+
+  fn clone_box(&self) -> Box<dyn ApiProvider + Send + Sync> {
+    Box::new(self.clone())
+  }
+}
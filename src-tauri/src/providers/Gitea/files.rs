@@ -0,0 +1,442 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use std::collections::HashMap;
+
+use crate::{
+  consts::*,
+  providers::{
+    Gitea::{Gitea::Gitea, issues::*, models::*, repo::__get_default_branch},
+    dto::{BlobStreamWithOffset, Manifest, TreeItem},
+  },
+  utils::errors::HttpStatusError,
+};
+
+pub async fn __get_file_raw(s: &Gitea, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
+  let url = s.raw_url(project_id, file_path);
+  let cached = s.get_cached(&url, Duration::from_secs(CACHE_TTL_RAW_FILE_SECS)).await?;
+  Ok(cached.bytes)
+}
+
+pub async fn __get_blob_stream(
+  s: &Gitea,
+  project_id: &str,
+  file_path: &str,
+  seek: &Option<u64>,
+) -> Result<BlobStreamWithOffset> {
+  let url = s.raw_url(project_id, file_path);
+
+  __get_blob_by_url_stream(s, &url, seek).await
+}
+pub async fn __get_blob_direct_url(s: &Gitea, project_id: &str, file_path: &str) -> String {
+  s.raw_url(project_id, file_path)
+}
+
+pub async fn __get_blob_by_url_stream(s: &Gitea, url: &str, seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+  crate::utils::paths::assert_download_url_allowed(url)?;
+
+  // Only ask for a Range when there is a real resume offset.
+  let resume_from = seek.filter(|bytes| *bytes > 0);
+
+  let response = match resume_from {
+    Some(bytes) => s
+      .get(url)
+      .header("Range", format!("bytes={}-", bytes))
+      .send()
+      .await
+      .context("Failed to send blob download request")?,
+    None => s.get(url).send().await.context("Failed to send blob download request")?,
+  };
+
+  crate::utils::paths::assert_download_url_allowed(response.url().as_str())?;
+
+  if !response.status().is_success() {
+    return Err(
+      HttpStatusError::from_response("__get_blob_by_url_stream, Error API Gitea", response)
+        .await
+        .into(),
+    );
+  }
+
+  // A server may ignore the Range header and answer 200 with the FULL body.
+  // Appending that body at the resume offset would corrupt the file, so report
+  // the real stream start and let the caller restart from scratch when needed.
+  let stream_start = if let Some(bytes) = resume_from {
+    if response.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+      response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(crate::utils::parse_strings::parse_content_range_start)
+        .unwrap_or(bytes)
+    } else {
+      log::warn!(
+        "__get_blob_by_url_stream: server ignored Range (status {}), stream starts at 0",
+        response.status()
+      );
+      0
+    }
+  } else {
+    0
+  };
+
+  Ok((
+    Box::new(response.bytes_stream().map(|res| res.context("Error reading chunk from response stream"))),
+    stream_start,
+  ))
+}
+
+/// Contents API type ("file", "dir") as the GitLab tree type `TreeItem` uses
+/// ("blob", "tree"), the same as `__get_full_tree` returns.
+fn tree_item_type(file_type: &str) -> String {
+  match file_type {
+    "file" => "blob",
+    "dir" => "tree",
+    other => other,
+  }
+  .to_owned()
+}
+
+pub async fn __tree(s: &Gitea, repo_id: &str, search_params: HashMap<String, String>) -> Result<Vec<TreeItem>> {
+  let params = search_params.iter().map(|v| format!("{}={}", v.0, v.1)).collect::<Vec<_>>().join("&");
+  let mut url = format!("{}/repos/{}/{}/contents", s.host, s.profile.org, repo_id);
+
+  if search_params.len() > 0 {
+    url = format!("{}?{}", &url, &params);
+  }
+
+  let resp = s
+    .get(&url)
+    .send()
+    .await
+    .with_context(|| format!("Failed to fetch file list of repository {} tree, params: {:?}", &repo_id, &params))?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    let body = resp.text().await?;
+    bail!("Gitea API error ({}): {} url: {}", status, body, url);
+  }
+
+  let items: Vec<ContentsItemGitea> = resp
+    .json()
+    .await
+    .with_context(|| format!("Failed to parse JSON while get file liest, repo: {}, params: {}", &repo_id, &params))?;
+
+  // Like on GitHub, the file path is the blob id: `get_blob_stream` resolves
+  // it through the raw endpoint, which supports Range requests.
+  let common: Vec<TreeItem> = items
+    .iter()
+    .map(|item| TreeItem {
+      id: item.path.clone(),
+      project_id: repo_id.to_string(),
+      name: item.name.clone(),
+      path: item.path.clone(),
+      item_type: tree_item_type(&item.file_type),
+    })
+    .collect();
+
+  Ok(common)
+}
+
+/// Every file of the repo, nested dirs included, from the recursive git tree
+/// of the default branch. The tree is paged, pages are read until all
+/// `total_count` entries are in.
+pub async fn __get_full_tree(s: &Gitea, repo_id: &str) -> Result<Vec<TreeItem>> {
+  let branch = __get_default_branch(s, repo_id).await?;
+  let mut items: Vec<TreeItem> = vec![];
+
+  for page in 1.. {
+    let url = format!(
+      "{}/repos/{}/{}/git/trees/{}?recursive=1&page={}",
+      s.host,
+      s.profile.org,
+      repo_id,
+      urlencoding::encode(&branch),
+      page
+    );
+    let resp = s
+      .get(&url)
+      .send()
+      .await
+      .with_context(|| format!("Failed to fetch of {} repository tree", repo_id))?;
+
+    if !resp.status().is_success() {
+      let status = resp.status();
+      let body = resp.text().await?;
+      bail!("Gitea API error ({}): {} url: {}", status, body, url);
+    }
+
+    let tree: GitTreeGitea = resp
+      .json()
+      .await
+      .with_context(|| format!("Failed to parse the tree of repo {}, page {}", repo_id, page))?;
+    let last_page = tree.tree.is_empty();
+
+    items.extend(tree.tree.into_iter().map(|entry| TreeItem {
+      id: entry.path.clone(),
+      project_id: repo_id.to_string(),
+      name: entry.path.rsplit('/').next().unwrap_or_default().to_owned(),
+      path: entry.path,
+      item_type: entry.entry_type,
+    }));
+
+    if last_page || !tree.truncated || items.len() >= tree.total_count {
+      break;
+    }
+  }
+
+  Ok(items)
+}
+
+pub async fn __load_manifest(s: &Gitea) -> Result<()> {
+  let max_size = { crate::utils::locks::lock(&s.manifest).max_size.clone() };
+
+  if max_size > 0 {
+    return Ok(());
+  }
+
+  let search_params = HashMap::from([("q".to_owned(), "mainfest.json".to_owned())]);
+  let issue = __find_issue(s, &s.profile.launcher_repo, search_params)
+    .await?
+    .into_iter()
+    .find(|issue| issue.title.contains("mainfest.json"));
+
+  let Some(issue) = issue else {
+    bail!("Issue mainfest.json NOT FOUND!")
+  };
+
+  let manifest: Manifest = serde_json::from_str(&issue.description)?;
+
+  *crate::utils::locks::lock(&s.manifest) = manifest;
+
+  Ok(())
+}
+
+pub async fn __get_launcher_bg(s: &Gitea) -> Result<Vec<u8>> {
+  __get_file_raw(s, &s.profile.launcher_repo, &s.profile.bg_path).await
+}
+
+pub async fn __get_file_content_size(s: &Gitea, direct_url: &str) -> Result<u64> {
+  let resp = s
+    .head(direct_url)
+    .send()
+    .await
+    .context("Failed to send request to Gitea (__get_file_content_size)")?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_else(|_| "No body".to_string());
+    bail!("__get_file_content_size, Gitea API error {}: {} url: {}", status, body, direct_url);
+  }
+
+  let mut size: u64 = 0;
+  if let Some(header) = resp.headers().get("content-length") {
+    size = header.to_str()?.parse()?;
+  };
+
+  Ok(size)
+}
+
+pub async fn __add_file_to_repo(s: &Gitea, repo_id: &str, file_name: &str, content: &str, commmit_msg: &str, branch: &str) -> Result<()> {
+  let url = format!("{}/repos/{}/{}/contents/{}", s.host, s.profile.org, repo_id, file_name);
+  let content_base64 = general_purpose::STANDARD.encode(content);
+
+  // Gitea Contents API: POST = create, PUT = update (requires the current `sha`).
+  // GET the current metadata first; a 404 means the file does not exist yet.
+  let existing_sha: Option<String> = {
+    let resp = s
+      .get(&format!("{}?ref={}", &url, branch))
+      .send()
+      .await
+      .context("Failed to send request to Gitea (__add_file_to_repo GET)")?;
+    if resp.status().is_success() {
+      resp.json::<ContentsItemGitea>().await.map(|f| Some(f.sha)).unwrap_or(None)
+    } else {
+      None
+    }
+  };
+
+  let data = AddFileContentBodyGitea {
+    content: content_base64,
+    message: commmit_msg.to_string(),
+    branch: branch.to_string(),
+    sha: existing_sha.clone(),
+  };
+
+  let request = if existing_sha.is_some() { s.put(&url) } else { s.post(&url) };
+  let resp = request
+    .json(&data)
+    .send()
+    .await
+    .context("Failed to send request to Gitea (__add_file_to_repo)")?;
+
+  if resp.status().is_success() {
+    return Ok(());
+  }
+
+  let status = resp.status();
+  let body = resp.text().await.unwrap_or_else(|_| "No body".to_string());
+  bail!("__add_file_to_repo, Gitea API error {}: {} url: {}", status, body, url);
+}
+
+/// Uploads a release attachment. The API only accepts `multipart/form-data`
+/// with an `attachment` field, so the file stream is wrapped into a single
+/// part by hand (keeps the upload streaming and the Content-Length exact).
+/// The attachment name comes from the `name` query param of `url`.
+pub async fn __upload_release_file(
+  s: &Gitea,
+  url: &str,
+  content_length: u64,
+  stream: Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Unpin>,
+) -> Result<()> {
+  let file_name = url::Url::parse(url)?
+    .query_pairs()
+    .find(|(k, _)| k == "name")
+    .map(|(_, v)| v.to_string())
+    .context("Gitea upload url has no `name` query param")?;
+
+  let boundary = format!("gw-launcher-{}", uuid::Uuid::new_v4().simple());
+  let head = Bytes::from(format!(
+    "--{}\r\nContent-Disposition: form-data; name=\"attachment\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+    &boundary,
+    file_name.replace('"', "")
+  ));
+  let tail = Bytes::from(format!("\r\n--{}--\r\n", &boundary));
+  let total_length = head.len() as u64 + content_length + tail.len() as u64;
+
+  let body = futures_util::stream::iter(vec![Ok(head)])
+    .chain(stream)
+    .chain(futures_util::stream::iter(vec![Ok(tail)]));
+
+  let response = s
+    .post(url)
+    .header("Content-Type", format!("multipart/form-data; boundary={}", &boundary))
+    .header("Content-Length", total_length.to_string())
+    .body(reqwest::Body::wrap_stream(body))
+    .send()
+    .await?;
+
+  if response.status().is_success() {
+    Ok(())
+  } else {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_else(|_| "No body".to_string());
+    log::error!("{:?}", body);
+    Err(anyhow::anyhow!("Upload failed, url: {}: {}", url, status))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+  };
+
+  use super::*;
+  use crate::configs::DistributionProfile::GiteaProfile;
+
+  const REPO: &str = "/api/v1/repos/Global-War-Releases/gw-1-data-0";
+
+  /// Responses recorded from a Gitea instance, keyed by the request target.
+  fn recorded(target: &str) -> Option<&'static str> {
+    match target.strip_prefix(REPO)? {
+      "" => Some(include_str!("testdata/repo.json")),
+      "/contents" => Some(include_str!("testdata/contents.json")),
+      "/git/trees/main?recursive=1&page=1" => Some(include_str!("testdata/tree_page1.json")),
+      "/git/trees/main?recursive=1&page=2" => Some(include_str!("testdata/tree_page2.json")),
+      _ => None,
+    }
+  }
+
+  /// Stand-in Gitea serving the recorded responses, 404 for anything else.
+  async fn gitea_stand_in() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+      loop {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        tokio::spawn(async move {
+          let mut head = Vec::new();
+          let mut buf = [0u8; 1024];
+          while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = socket.read(&mut buf).await.unwrap();
+            if n == 0 {
+              return;
+            }
+            head.extend_from_slice(&buf[..n]);
+          }
+
+          let head = String::from_utf8_lossy(&head);
+          let target = head.split_whitespace().nth(1).unwrap_or_default();
+          let (status, body) = match recorded(target) {
+            Some(body) => ("200 OK", body),
+            None => ("404 Not Found", r#"{"message":"not found"}"#),
+          };
+          let resp = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+          );
+          socket.write_all(resp.as_bytes()).await.unwrap();
+        });
+      }
+    });
+
+    format!("http://{}", addr)
+  }
+
+  #[tokio::test]
+  async fn full_tree_reads_every_page_of_the_default_branch() {
+    let profile = GiteaProfile {
+      host: gitea_stand_in().await,
+      ..GiteaProfile::default()
+    };
+    let gitea = Gitea::new(&profile, false, Arc::new(|_: &str| {})).unwrap();
+
+    let items = __get_full_tree(&gitea, "gw-1-data-0").await.unwrap();
+    let entries: Vec<(&str, &str, &str)> = items.iter().map(|i| (i.path.as_str(), i.name.as_str(), i.item_type.as_str())).collect();
+
+    assert_eq!(
+      entries,
+      vec![
+        ("README.md", "README.md", "blob"),
+        ("gamedata", "gamedata", "tree"),
+        ("gamedata/data.zip.001", "data.zip.001", "blob"),
+      ]
+    );
+    assert!(items.iter().all(|i| i.id == i.path && i.project_id == "gw-1-data-0"));
+  }
+
+  #[tokio::test]
+  async fn tree_uses_the_same_item_types_as_the_full_tree() {
+    let profile = GiteaProfile {
+      host: gitea_stand_in().await,
+      ..GiteaProfile::default()
+    };
+    let gitea = Gitea::new(&profile, false, Arc::new(|_: &str| {})).unwrap();
+
+    let items = __tree(&gitea, "gw-1-data-0", HashMap::new()).await.unwrap();
+    let entries: Vec<(&str, &str)> = items.iter().map(|i| (i.path.as_str(), i.item_type.as_str())).collect();
+
+    assert_eq!(entries, vec![("README.md", "blob"), ("gamedata", "tree")]);
+  }
+
+  #[tokio::test]
+  async fn full_tree_fails_on_an_unknown_repo() {
+    let profile = GiteaProfile {
+      host: gitea_stand_in().await,
+      ..GiteaProfile::default()
+    };
+    let gitea = Gitea::new(&profile, false, Arc::new(|_: &str| {})).unwrap();
+
+    assert!(__get_full_tree(&gitea, "gw-missing").await.is_err());
+  }
+}
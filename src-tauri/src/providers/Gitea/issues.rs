@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use urlencoding::encode;
+
+use crate::{
+  consts::CACHE_TTL_SEARCH_API_SECS,
+  providers::{
+    Gitea::{Gitea::Gitea, models::*},
+    dto::Issue,
+  },
+};
+
+/// Lists issues of `repo_id` in the Gitea org. Numeric ids (GitLab-style
+/// `REPO_LAUNCGER_ID` passed by shared callers) are mapped to the launcher repo.
+pub async fn __find_issue(s: &Gitea, repo_id: &str, search_params: HashMap<String, String>) -> Result<Vec<Issue>> {
+  let repo = if repo_id.is_empty() || repo_id.chars().all(|c| c.is_ascii_digit()) {
+    s.profile.launcher_repo.as_str()
+  } else {
+    repo_id
+  };
+
+  let mut params = vec!["type=issues".to_owned(), "state=all".to_owned()];
+  params.extend(search_params.iter().map(|v| format!("{}={}", v.0, encode(v.1))));
+
+  let url = format!("{}/repos/{}/{}/issues?{}", s.host, s.profile.org, repo, params.join("&"));
+
+  let cached = s.get_cached(&url, Duration::from_secs(CACHE_TTL_SEARCH_API_SECS)).await?;
+  let issues: Vec<IssueGitea> = serde_json::from_slice(&cached.bytes)?;
+
+  let common: Vec<Issue> = issues
+    .iter()
+    .map(|issue| Issue {
+      title: issue.title.to_owned(),
+      description: issue.body.to_owned(),
+    })
+    .collect();
+
+  Ok(common)
+}
+
+pub async fn __find_user(s: &Gitea, repo_id: &str, uuid: &str) -> Result<Option<Issue>> {
+  let search_params = HashMap::from([("q".to_owned(), uuid.to_owned())]);
+
+  // `q` also matches the issue body, keep only title hits (same as GitHub `in:title`).
+  let issue = __find_issue(s, repo_id, search_params)
+    .await?
+    .into_iter()
+    .find(|issue| issue.title.contains(uuid));
+
+  Ok(issue)
+}
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use crate::{
+  consts::*,
+  providers::{
+    Gitea::{Gitea::Gitea, models::*},
+    dto::{ReleaseAssetGit, ReleaseGit, ReleasePlatform},
+  },
+};
+
+use anyhow::{Context, Result};
+
+/// `owner` is ignored: the launcher repo is mirrored into the Gitea org.
+pub async fn __get_launcher_latest_release(s: &Gitea, _owner: &str, project_id: &str) -> Result<ReleaseGit> {
+  let url = format!("{}/repos/{}/{}/releases/latest", &s.host, s.profile.org, project_id);
+  let cached = s.get_cached(&url, Duration::from_secs(CACHE_TTL_RELEASE_SECS)).await?;
+  let release: ReleaseGitea = serde_json::from_slice(&cached.bytes)
+    .context("Failed to parse ReleaseGitea response as JSON")?;
+
+  let assets: Vec<ReleaseAssetGit> = release
+    .assets
+    .iter()
+    .map(|asset| ReleaseAssetGit {
+      name: asset.name.clone(),
+      platform: get_platform_type(&asset.name),
+      size: asset.size,
+      download_link: asset.browser_download_url.clone(),
+    })
+    .collect();
+
+  Ok(ReleaseGit {
+    name: release.name,
+    version: release.tag_name,
+    assets,
+  })
+}
+
+fn get_platform_type(asset_name: &str) -> ReleasePlatform {
+  if asset_name == EXE_WIN_NAME {
+    ReleasePlatform::Windows
+  } else if asset_name == EXE_LINUX_NAME {
+    ReleasePlatform::Linux
+  } else {
+    ReleasePlatform::MacOS
+  }
+}
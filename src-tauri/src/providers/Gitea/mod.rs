@@ -0,0 +1,7 @@
+pub mod Gitea;
+pub mod files;
+pub mod issues;
+pub mod launcher;
+pub mod models;
+pub mod release;
+pub mod repo;
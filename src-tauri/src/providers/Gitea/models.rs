@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectGitea {
+  pub id: u32,
+  pub name: String,
+  #[serde(default)]
+  pub description: Option<String>,
+  pub full_name: String,
+  pub ssh_url: String,
+  #[serde(default)]
+  pub private: bool,
+  #[serde(default)]
+  pub archived: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoInfoGitea {
+  pub default_branch: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRepoBodyGitea {
+  pub name: String,
+  pub description: String,
+  pub private: bool,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoOwnerGitea {
+  pub id: u32,
+  pub login: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRepoResponseGitea {
+  pub id: u32,
+  pub name: String,
+  pub full_name: String,
+  pub ssh_url: String,
+  pub private: bool,
+  pub owner: RepoOwnerGitea,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UpdateRepoDtoGitea {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub website: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub private: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseAttachmentGitea {
+  pub id: u32,
+  pub name: String,
+  pub size: u64,
+  pub browser_download_url: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseGitea {
+  pub id: u32,
+  pub name: String,
+  pub tag_name: String,
+  pub assets: Vec<ReleaseAttachmentGitea>,
+  #[serde(default)]
+  pub body: Option<String>,
+  #[serde(default)]
+  pub created_at: Option<String>,
+}
+
+/// Item of `GET /repos/{owner}/{repo}/contents` (directory listing).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentsItemGitea {
+  pub name: String,
+  pub path: String,
+  pub sha: String,
+  #[serde(default)]
+  pub size: u64,
+  #[serde(rename = "type")]
+  pub file_type: String, // "file", "dir", "symlink" или "submodule"
+}
+
+/// Entry of `GET /repos/{owner}/{repo}/git/trees/{sha}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitTreeEntryGitea {
+  pub path: String,
+  #[serde(rename = "type")]
+  pub entry_type: String, // "blob", "tree" или "commit"
+  #[serde(default)]
+  pub size: u64,
+}
+
+/// One page of a recursive tree; the server caps the entries per page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitTreeGitea {
+  #[serde(default)]
+  pub tree: Vec<GitTreeEntryGitea>,
+  #[serde(default)]
+  pub truncated: bool,
+  #[serde(default)]
+  pub total_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueGitea {
+  pub id: u32,
+  pub title: String,
+  #[serde(default)]
+  pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddFileContentBodyGitea {
+  pub message: String,
+  pub content: String,
+  pub branch: String,
+  /// Required by the Contents API when *updating* an existing file (PUT).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sha: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTagRequestGitea {
+  pub tag_name: String,
+  pub target: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateReleaseRequestGitea {
+  pub name: String,
+  pub tag_name: String,
+  pub target_commitish: String,
+  pub body: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateReleaseResponseGitea {
+  pub id: u32,
+  pub tag_name: String,
+}
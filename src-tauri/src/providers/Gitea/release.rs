@@ -0,0 +1,208 @@
+use std::{collections::HashMap, time::Duration, vec};
+
+use crate::{
+  consts::CACHE_TTL_RELEASE_SECS,
+  providers::{
+    Gitea::{Gitea::Gitea, models::*, repo::*},
+    dto::*,
+  },
+};
+
+use anyhow::{Context, Result, bail};
+
+/// Gitea caps `limit` at the server's MAX_RESPONSE_ITEMS (50 by default).
+const PAGE_LIMIT: usize = 50;
+
+async fn __fetch_releases(s: &Gitea, cashed: bool) -> Result<()> {
+  if cashed && crate::utils::locks::lock(&s.projects_map).len() > 0 {
+    return Ok(());
+  }
+
+  let mut map: HashMap<u32, ProjectGitea> = HashMap::new();
+  let mut page: u32 = 1;
+
+  loop {
+    let url = format!("{}/orgs/{}/repos?page={}&limit={}", s.host, s.profile.org, page, PAGE_LIMIT);
+
+    let resp = s.get(&url).send().await.context("Failed to send request to Gitea (__get_releases)")?;
+
+    if !resp.status().is_success() {
+      let status = resp.status();
+      let body = resp.text().await?;
+      bail!("__get_releases, Gitea API error ({}): {} url: {}", status, body, url);
+    }
+
+    let projects: Vec<ProjectGitea> = resp.json().await?;
+    let last_page = projects.len() < PAGE_LIMIT;
+
+    for project in projects {
+      map.insert(project.id, project);
+    }
+
+    if last_page {
+      break;
+    }
+
+    page += 1;
+  }
+
+  *crate::utils::locks::lock(&s.projects_map) = map;
+
+  Ok(())
+}
+
+pub async fn __get_releases(s: &Gitea, cashed: bool) -> Result<Vec<Release>> {
+  __fetch_releases(s, cashed).await?;
+
+  let cached_projects = crate::utils::locks::lock(&s.projects_map).clone();
+  let mut releases: Vec<Release> = vec![];
+  let mut exist_names: HashMap<String, bool> = HashMap::new();
+
+  for (id, project) in cached_projects {
+    let desc = project.description.clone().unwrap_or_default();
+    if desc.is_empty() { continue; }
+    if exist_names.get(&desc).is_none() {
+      releases.push(Release {
+        id,
+        name: desc.clone(),
+        path: crate::utils::parse_strings::WHITESPACE_RE.replace_all(&desc, "-").to_string(),
+      });
+      exist_names.insert(desc, true);
+    }
+  }
+
+  Ok(releases)
+}
+
+/// Repos of a release whose name contains `marker` (`_main_` / `_updates_`).
+async fn __get_repos_by_marker(s: &Gitea, release_name: &str, marker: &str) -> Result<Vec<Project>> {
+  __fetch_releases(s, true).await?;
+
+  let cached_projects = crate::utils::locks::lock(&s.projects_map).clone();
+  let mut repos: Vec<Project> = vec![];
+
+  for (id, project) in cached_projects {
+    if let Some(pos) = project.name.find(marker)
+      && pos > 0
+      && project.description.as_deref() == Some(release_name)
+    {
+      repos.push(Project {
+        id,
+        name: project.name.clone(),
+        path: project.name,
+        ssh_remote_url: project.ssh_url,
+        marked_for_deletion_on: None,
+      });
+    }
+  }
+
+  Ok(repos)
+}
+
+pub async fn __get_release_repos_by_name(s: &Gitea, release_name: &str) -> Result<Vec<Project>> {
+  __get_repos_by_marker(s, release_name, "_main_").await
+}
+
+pub async fn __get_updates_repos_by_name(s: &Gitea, release_name: &str) -> Result<Vec<Project>> {
+  __get_repos_by_marker(s, release_name, "_updates_").await
+}
+
+/// Lists all releases of a concrete repo (tag, name, body/notes, created_at).
+/// Used for patch chains in updates repos.
+pub async fn __get_repo_releases(s: &Gitea, project_id: &str) -> Result<Vec<RepoReleaseInfo>> {
+  let url = format!("{}/repos/{}/{}/releases", &s.host, s.profile.org, &project_id);
+  let cached = s.get_cached(&url, Duration::from_secs(CACHE_TTL_RELEASE_SECS)).await?;
+  let releases: Vec<ReleaseGitea> = serde_json::from_slice(&cached.bytes)
+    .context("Failed to parse Gitea releases response as JSON")?;
+  log::info!("Gitea __get_repo_releases: {} releases for project '{}' (cache: {:?})", releases.len(), project_id, cached.source);
+
+  Ok(releases
+    .into_iter()
+    .map(|r| RepoReleaseInfo {
+      tag_name: r.tag_name,
+      name: r.name,
+      body: r.body,
+      created_at: r.created_at,
+      assets: r.assets.into_iter().map(|a| RepoReleaseAsset {
+        name: a.name,
+        size: Some(a.size),
+        download_link: a.browser_download_url,
+      }).collect(),
+    })
+    .collect())
+}
+
+pub async fn __set_release_visibility(s: &Gitea, release_name: &str, visibility: bool) -> Result<()> {
+  __fetch_releases(s, true).await?;
+
+  let cached_projects = crate::utils::locks::lock(&s.projects_map).clone();
+
+  for (_, project) in cached_projects {
+    if project.description.as_deref() == Some(release_name) {
+      __update_repo(
+        s,
+        &project.name,
+        UpdateRepoDtoGitea {
+          private: Some(!visibility),
+          ..UpdateRepoDtoGitea::default()
+        },
+      )
+      .await?;
+    }
+  }
+
+  Ok(())
+}
+
+pub async fn __create_tag(s: &Gitea, repo_id: &str, tag_name: &str, branch: &str) -> Result<()> {
+  let url = format!("{}/repos/{}/{}/tags", s.host, s.profile.org, repo_id);
+  let body = CreateTagRequestGitea {
+    tag_name: tag_name.to_string(),
+    target: branch.to_string(),
+  };
+
+  let resp = s
+    .post(&url)
+    .json(&body)
+    .send()
+    .await
+    .context("Failed to send request to Gitea (__create_tag)")?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_else(|_| "No body".to_string());
+    bail!("__create_tag, Gitea API error {}: {} url: {}", status, body, url);
+  }
+
+  Ok(())
+}
+
+pub async fn __create_release(s: &Gitea, repo_id: &str, tag_name: &str, branch: &str, _assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
+  let url = format!("{}/repos/{}/{}/releases", s.host, s.profile.org, repo_id);
+  let body = CreateReleaseRequestGitea {
+    name: format!("Release {}", &tag_name),
+    tag_name: tag_name.to_string(),
+    target_commitish: branch.to_string(),
+    body: format!("Release {}", &tag_name),
+  };
+
+  let resp = s
+    .post(&url)
+    .json(&body)
+    .send()
+    .await
+    .context("Failed to send request to Gitea (__create_release)")?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_else(|_| "No body".to_string());
+    bail!("__create_release, Gitea API error {}: {} url: {}", status, body, url);
+  }
+
+  let response: CreateReleaseResponseGitea = resp.json().await?;
+
+  Ok(CreateReleaseResponse {
+    id: response.id,
+    upload_url: format!("{}/{}/assets?name=<FILE_NAME>", url, response.id),
+  })
+}
@@ -0,0 +1,88 @@
+use crate::{
+  providers::{
+    Gitea::{Gitea::Gitea, models::*},
+    dto::CreateRepoResponse,
+  },
+};
+
+use anyhow::{Context, Result, bail};
+
+pub async fn __create_repo(s: &Gitea, name: &str, description: &str) -> Result<CreateRepoResponse> {
+  let url = format!("{}/orgs/{}/repos", s.host, s.profile.org);
+  let data = CreateRepoBodyGitea {
+    name: name.to_owned(),
+    description: description.to_owned(),
+    private: true,
+  };
+
+  let resp = s
+    .post(&url)
+    .json(&data)
+    .send()
+    .await
+    .context(format!("Failed to send request to Gitea (create_repo) name: {}", name))?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    let body = resp.text().await?;
+    bail!("__create_repo, Gitea API error ({}): {} url: {}", status, body, url);
+  }
+
+  let response_text = resp.text().await.context("Failed to read response body")?;
+
+  let result: CreateRepoResponseGitea =
+    serde_json::from_str(&response_text).with_context(|| format!("Failed to parse Gitea response as JSON. Response body: {}", response_text))?;
+
+  Ok(CreateRepoResponse {
+    id: result.id,
+    name: result.name.clone(),
+    path: result.name,
+    ssh_url_to_repo: result.ssh_url,
+    visibility: if result.private { "private".to_owned() } else { "public".to_owned() },
+    lfs_enabled: true,
+    namespace_id: result.owner.id,
+  })
+}
+
+pub async fn __update_repo(s: &Gitea, repo_name: &str, data: UpdateRepoDtoGitea) -> Result<()> {
+  let url = format!("{}/repos/{}/{}", s.host, s.profile.org, repo_name);
+
+  let resp = s
+    .patch(&url)
+    .json(&data)
+    .send()
+    .await
+    .context(format!("Failed to send request to Gitea (update_repo) repo_name: {}", &repo_name))?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    let body = resp.text().await?;
+    bail!("__update_repo, Gitea API error ({}): {} url: {}", status, body, url);
+  }
+
+  Ok(())
+}
+
+pub async fn __get_default_branch(s: &Gitea, repo_name: &str) -> Result<String> {
+  if let Some(branch) = crate::utils::locks::lock(&s.default_branches).get(repo_name) {
+    return Ok(branch.clone());
+  }
+
+  let url = format!("{}/repos/{}/{}", s.host, s.profile.org, repo_name);
+  let resp = s
+    .get(&url)
+    .send()
+    .await
+    .context(format!("Failed to send request to Gitea (get_default_branch) repo_name: {}", &repo_name))?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    let body = resp.text().await?;
+    bail!("__get_default_branch, Gitea API error ({}): {} url: {}", status, body, url);
+  }
+
+  let repo: RepoInfoGitea = resp.json().await.context("Failed to parse Gitea repo")?;
+  crate::utils::locks::lock(&s.default_branches).insert(repo_name.to_owned(), repo.default_branch.clone());
+
+  Ok(repo.default_branch)
+}
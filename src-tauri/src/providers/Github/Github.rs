@@ -4,21 +4,22 @@ use std::{
   time::{Duration, Instant},
 };
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures_util::Stream;
 use rand_agents::user_agent;
 use reqwest::{
-  Client,
+  Client, RequestBuilder, Response, StatusCode, Url,
   header::{AUTHORIZATION, HeaderMap, HeaderValue},
 };
 
 use crate::{
-  consts::GITHUB_PID,
+  configs::DistributionProfile::GithubProfile,
+  consts::{GITHUB_PID, GITHUB_RATE_LIMIT_MAX_WAIT_SECS},
   providers::{
     ApiProvider::ApiProvider,
-    Github::{files::*, issues::*, launcher::*, models::*, release::*, repo::*},
+    Github::{files::*, issues::*, launcher::*, models::*, rate_limit, release::*, repo::*},
     dto::{Issue, *},
   },
   service::main::LogCallback,
@@ -28,11 +29,16 @@ use crate::{
 #[derive(Clone)]
 pub struct Github {
   pub host: String,
+  pub profile: GithubProfile,
   pub client: Arc<Mutex<Client>>,
   pub suppot_subgroups: bool,
 
   pub status: Arc<Mutex<ProviderStatus>>,
+  /// API quota per rate-limit resource, see `rate_limit`.
+  pub rate_limits: Arc<Mutex<rate_limit::RateLimits>>,
   pub manifest: Arc<Mutex<Manifest>>,
+  /// Default branch per repo id, see `get_default_branch`.
+  pub default_branches: Arc<Mutex<HashMap<String, String>>>,
   pub projects_map: Arc<Mutex<HashMap<u32, ProjectGithub>>>,
 
   pub logger: LogCallback,
@@ -41,25 +47,29 @@ pub struct Github {
 }
 
 impl Github {
-  pub fn new(h: &str, suppot_subgroups: bool, logger: LogCallback) -> Result<Self> {
+  pub fn new(profile: &GithubProfile, suppot_subgroups: bool, logger: LogCallback) -> Result<Self> {
     let user_agent = user_agent();
     log::info!("Start init Github client with User-Agent: {}", &user_agent);
 
     let mut headers = HeaderMap::new();
     headers.insert("User-Agent", HeaderValue::from_str(&user_agent)?);
 
-    let client = Client::builder().default_headers(headers).build()?;
+    let client = crate::utils::proxy::client_builder()?.default_headers(headers).build()?;
 
     Ok(Self {
-      host: h.to_string(),
+      host: profile.api_host.clone(),
+      profile: profile.clone(),
       client: Arc::new(Mutex::new(client)),
       status: Arc::new(Mutex::new(ProviderStatus {
         available: false,
         latency_ms: None,
+        rate_limit: None,
       })),
+      rate_limits: Arc::new(Mutex::new(HashMap::new())),
       suppot_subgroups,
       projects_map: Arc::new(Mutex::new(HashMap::new())),
       manifest: Arc::new(Mutex::new(Manifest { root_id: None, max_size: 0 })),
+      default_branches: Arc::new(Mutex::new(HashMap::new())),
       token: Arc::new(Mutex::new("".to_owned())),
       logger,
     })
@@ -84,6 +94,21 @@ impl Github {
     self.get_client().head(url)
   }
 
+  /// Raw url of a file on the default branch of `owner/repo` (`HEAD`, served by
+  /// the web host: neither the file nor the branch lookup counts against the API rate limit).
+  pub fn raw_url(&self, owner: &str, repo: &str, file_path: &str) -> String {
+    format!("{}/{}/{}/raw/HEAD/{}", self.profile.web_host, owner, repo, file_path)
+  }
+
+  /// Url of the launcher background in the launcher repo.
+  pub fn bg_url(&self) -> String {
+    self.raw_url(
+      &self.profile.launcher_owner,
+      &self.profile.launcher_repo,
+      &urlencoding::encode(&self.profile.bg_path),
+    )
+  }
+
   /// GET with ETag disk cache.  The cached body is returned when the TTL has
   /// not expired or when the server replies 304 Not Modified (which GitHub
   /// does NOT count against the API rate limit).
@@ -93,7 +118,100 @@ impl Github {
     ttl: Duration,
   ) -> anyhow::Result<http_cache::CachedBody> {
     let client = self.get_client();
-    http_cache::fetch(&client, url, ttl).await
+    let Some(resource) = Url::parse(url).ok().and_then(|u| self.api_resource(&u)) else {
+      return http_cache::fetch(&client, url, ttl).await;
+    };
+    let observe = |status: StatusCode, headers: &HeaderMap| {
+      self.record_rate_limit(resource, status, headers);
+    };
+
+    self.wait_rate_limit(resource).await?;
+    match http_cache::fetch_observed(&client, url, ttl, &observe).await {
+      // Rejected by the rate limit and nothing cached: retry once after the reset.
+      Err(_) if self.rate_limit_wait_secs(resource).is_some() => {
+        self.wait_rate_limit(resource).await?;
+        http_cache::fetch_observed(&client, url, ttl, &observe).await
+      }
+      res => res,
+    }
+  }
+
+  /// Sends a request, holding it back while the API quota is exhausted and
+  /// retrying it once after the reset when GitHub rejects it for the rate limit.
+  /// Requests to other hosts (raw files, release assets, CDN) are not counted
+  /// against the quota and go out as is.
+  pub async fn send(&self, req: RequestBuilder) -> Result<Response> {
+    let (client, req) = req.build_split();
+    let req = req?;
+    let Some(resource) = self.api_resource(req.url()) else {
+      return Ok(client.execute(req).await?);
+    };
+
+    self.wait_rate_limit(resource).await?;
+
+    // Streaming bodies cannot be cloned: such a request is sent only once.
+    let retry = req.try_clone();
+    let resp = client.execute(req).await?;
+    if !self.record_rate_limit(resource, resp.status(), resp.headers()) {
+      return Ok(resp);
+    }
+
+    match retry {
+      Some(req) => {
+        self.wait_rate_limit(resource).await?;
+        let resp = client.execute(req).await?;
+        self.record_rate_limit(resource, resp.status(), resp.headers());
+        Ok(resp)
+      }
+      None => Ok(resp),
+    }
+  }
+
+  /// Rate-limit resource of a call to the API host, None for any other host.
+  fn api_resource(&self, url: &Url) -> Option<&'static str> {
+    let api = Url::parse(&self.profile.api_host).ok()?;
+    (url.host_str() == api.host_str() && url.port_or_known_default() == api.port_or_known_default()).then(|| rate_limit::resource_of(url))
+  }
+
+  fn record_rate_limit(&self, resource: &str, status: StatusCode, headers: &HeaderMap) -> bool {
+    rate_limit::record(&mut crate::utils::locks::lock(&self.rate_limits), resource, status, headers)
+  }
+
+  fn rate_limit_wait_secs(&self, resource: &str) -> Option<u64> {
+    rate_limit::wait_secs(&crate::utils::locks::lock(&self.rate_limits), resource)
+  }
+
+  /// Core quota state shown in the provider status.
+  fn core_rate_limit(&self) -> RateLimitStatus {
+    crate::utils::locks::lock(&self.rate_limits)
+      .get(rate_limit::CORE_RESOURCE)
+      .cloned()
+      .unwrap_or_default()
+  }
+
+  /// Holds a call back while the quota of `resource` is exhausted. Only a
+  /// background job (`utils::background`) waits, up to
+  /// `GITHUB_RATE_LIMIT_MAX_WAIT_SECS` and until it is cancelled; an
+  /// interactive call fails right away with the reset time.
+  async fn wait_rate_limit(&self, resource: &str) -> Result<()> {
+    let Some(secs) = self.rate_limit_wait_secs(resource) else {
+      return Ok(());
+    };
+
+    let cancel_rx = crate::utils::background::cancel_receiver();
+    let Some(mut cancel_rx) = cancel_rx.filter(|_| secs <= GITHUB_RATE_LIMIT_MAX_WAIT_SECS) else {
+      bail!(
+        "GitHub API quota ({}) exhausted until {}",
+        resource,
+        rate_limit::format_time(rate_limit::now_secs() + secs)
+      );
+    };
+
+    log::warn!("Github API quota '{}' exhausted, waiting {}s for the reset", resource, secs);
+    tokio::select! {
+      _ = tokio::time::sleep(Duration::from_secs(secs)) => Ok(()),
+      _ = cancel_rx.recv() => bail!("USER_CANCELLED"),
+    }
   }
 }
 
@@ -110,25 +228,23 @@ impl ApiProvider for Github {
     }
     headers.insert("User-Agent", HeaderValue::from_str(&user_agent)?);
 
-    *crate::utils::locks::lock(&self.client) = Client::builder().default_headers(headers).build()?;
+    *crate::utils::locks::lock(&self.client) = crate::utils::proxy::client_builder()?.default_headers(headers).build()?;
 
     Ok(())
   }
   fn get_token(&self) -> String {
     crate::utils::locks::lock(&self.token).clone()
   }
+  fn rebuild_client(&self) -> Result<()> {
+    self.set_token(self.get_token())
+  }
 
   fn id(&self) -> &'static str {
     GITHUB_PID
   }
   async fn ping(&self) -> ProviderStatus {
     // HEAD to a raw file (does not count against the API rate limit).
-    let url = format!(
-      "{}/{}/{}/raw/master/src%2Fstatic%2Fbg.jpg",
-      crate::consts::GITHUB_HOST,
-      crate::consts::MAIN_DEVELOPER_NAME,
-      crate::consts::GITHUB_LAUNCHER_REPO_NAME,
-    );
+    let url = self.bg_url();
     log::info!("Start PING provider: {}, url: {}", self.id(), &url);
 
     let start = Instant::now();
@@ -154,13 +270,16 @@ impl ApiProvider for Github {
     let new_status = ProviderStatus {
       available,
       latency_ms: if available { Some(latency_ms) } else { None },
+      rate_limit: Some(self.core_rate_limit()),
     };
 
     *crate::utils::locks::lock(&self.status) = new_status.clone();
     new_status
   }
   fn status(&self) -> ProviderStatus {
-    crate::utils::locks::lock(&self.status).clone()
+    let mut status = crate::utils::locks::lock(&self.status).clone();
+    status.rate_limit = Some(self.core_rate_limit());
+    status
   }
   fn is_available(&self) -> bool {
     self.status().available
@@ -196,13 +315,8 @@ impl ApiProvider for Github {
   async fn get_launcher_bg(&self) -> Result<Vec<u8>> {
     __get_launcher_bg(self).await
   }
-  fn launcher_bg_url(&self) -> String {
-    format!(
-      "{}/{}/{}/raw/master/src%2Fstatic%2Fbg.jpg",
-      crate::consts::GITHUB_HOST,
-      crate::consts::MAIN_DEVELOPER_NAME,
-      crate::consts::GITHUB_LAUNCHER_REPO_NAME,
-    )
+  async fn launcher_bg_url(&self) -> String {
+    self.bg_url()
   }
   async fn get_file_raw(&self, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
     __get_file_raw(self, project_id, file_path).await
@@ -243,8 +357,12 @@ impl ApiProvider for Github {
     Ok(())
   }
 
-  async fn create_release(&self, repo_id: &str, tag_name: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
-    __create_release(self, repo_id, tag_name, assets).await
+  async fn get_default_branch(&self, repo_id: &str) -> Result<String> {
+    __get_default_branch(self, repo_id).await
+  }
+
+  async fn create_release(&self, repo_id: &str, tag_name: &str, branch: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
+    __create_release(self, repo_id, tag_name, branch, assets).await
   }
 
   // Issues API
@@ -12,17 +12,17 @@ use crate::{
     Github::{Github::Github, issues::*, models::*},
     dto::{BlobStreamWithOffset, Manifest, TreeItem},
   },
-  utils::http_cache,
+  utils::{errors::HttpStatusError, http_cache},
 };
 
 pub async fn __get_file_raw_github(s: &Github, parent_id: &str, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
-  let url = format!("{}/{}/{}/raw/master/{}", GITHUB_HOST, parent_id, project_id, file_path);
+  let url = s.raw_url(parent_id, project_id, file_path);
   let cached = s.get_cached(&url, Duration::from_secs(CACHE_TTL_RAW_FILE_SECS)).await?;
   Ok(cached.bytes)
 }
 
 pub async fn __get_file_raw(s: &Github, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
-  __get_file_raw_github(s, GITHUB_ORG, project_id, file_path).await
+  __get_file_raw_github(s, &s.profile.org, project_id, file_path).await
 }
 
 pub async fn __get_blob_stream(
@@ -31,14 +31,12 @@ pub async fn __get_blob_stream(
   file_path: &str,
   seek: &Option<u64>,
 ) -> Result<BlobStreamWithOffset> {
-  let url = format!("{}/{}/{}/raw/master/{}", GITHUB_HOST, GITHUB_ORG, project_id, file_path);
+  let url = __get_blob_direct_url(s, project_id, file_path).await;
 
   __get_blob_by_url_stream(s, &url, seek).await
 }
 pub async fn __get_blob_direct_url(s: &Github, project_id: &str, file_path: &str) -> String {
-  let url = format!("{}/{}/{}/raw/master/{}", GITHUB_HOST, GITHUB_ORG, project_id, file_path);
-
-  url
+  s.raw_url(&s.profile.org, project_id, file_path)
 }
 
 pub async fn __get_blob_by_url_stream(s: &Github, url: &str, seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
@@ -49,20 +47,20 @@ pub async fn __get_blob_by_url_stream(s: &Github, url: &str, seek: &Option<u64>)
 
   let response = match resume_from {
     Some(bytes) => s
-      .get(url)
-      .header("Range", format!("bytes={}-", bytes))
-      .send()
+      .send(s.get(url).header("Range", format!("bytes={}-", bytes)))
       .await
       .context("Failed to send blob download request")?,
-    None => s.get(url).send().await.context("Failed to send blob download request")?,
+    None => s.send(s.get(url)).await.context("Failed to send blob download request")?,
   };
 
   crate::utils::paths::assert_download_url_allowed(response.url().as_str())?;
 
   if !response.status().is_success() {
-    let status = response.status();
-    let body = response.text().await.unwrap_or_else(|_| "<failed to read response body>".to_string());
-    bail!("__get_blob_by_url_stream, Error API Github: {} – {}", status, body);
+    return Err(
+      HttpStatusError::from_response("__get_blob_by_url_stream, Error API Github", response)
+        .await
+        .into(),
+    );
   }
 
   // A server may ignore the Range header and answer 200 with the FULL body.
@@ -95,15 +93,14 @@ pub async fn __get_blob_by_url_stream(s: &Github, url: &str, seek: &Option<u64>)
 
 pub async fn __tree(s: &Github, repo_id: &str, search_params: HashMap<String, String>) -> Result<Vec<TreeItem>> {
   let params = search_params.iter().map(|v| format!("{}={}", v.0, v.1)).collect::<Vec<_>>().join("&");
-  let mut url = format!("{}/repos/{}/{}/contents", s.host, GITHUB_ORG, repo_id);
+  let mut url = format!("{}/repos/{}/{}/contents", s.host, s.profile.org, repo_id);
 
   if search_params.len() > 0 {
     url = format!("{}?{}", &url, &params);
   }
 
   let resp = s
-    .get(&url)
-    .send()
+    .send(s.get(&url))
     .await
     .with_context(|| format!("Failed to fetch file list of repository {} tree, params: {:?}", &repo_id, &params))?;
 
@@ -151,10 +148,10 @@ pub async fn __load_manifest(s: &Github) -> Result<()> {
     "q".to_owned(),
     format!(
       "mainfest.json in:title repo:{}/{} is:issue author:{}",
-      MAIN_DEVELOPER_NAME, GITHUB_LAUNCHER_REPO_NAME, MAIN_DEVELOPER_NAME
+      s.profile.launcher_owner, s.profile.launcher_repo, s.profile.launcher_owner
     ),
   )]);
-  let issue = __find_issue(s, &REPO_LAUNCGER_ID.to_string(), search_params).await?;
+  let issue = __find_issue(s, &s.profile.launcher_repo, search_params).await?;
 
   if issue.len() == 0 {
     bail!("Issue mainfest.json NOT FOUND!")
@@ -168,13 +165,13 @@ pub async fn __load_manifest(s: &Github) -> Result<()> {
 }
 
 pub async fn __get_launcher_bg(s: &Github) -> Result<Vec<u8>> {
-  __get_file_raw_github(s, MAIN_DEVELOPER_NAME, GITHUB_LAUNCHER_REPO_NAME, "src%2Fstatic%2Fbg.jpg").await
+  let cached = s.get_cached(&s.bg_url(), Duration::from_secs(CACHE_TTL_RAW_FILE_SECS)).await?;
+  Ok(cached.bytes)
 }
 
 pub async fn __get_file_content_size(s: &Github, direct_url: &str) -> Result<u64> {
   let resp = s
-    .head(direct_url)
-    .send()
+    .send(s.head(direct_url))
     .await
     .context("Failed to send request to Github (__get_file_content_size)")?;
 
@@ -193,7 +190,7 @@ pub async fn __get_file_content_size(s: &Github, direct_url: &str) -> Result<u64
 }
 
 pub async fn __add_file_to_repo(s: &Github, repo_id: &str, file_name: &str, content: &str, commmit_msg: &str, branch: &str) -> Result<()> {
-  let url = format!("{}/repos/{}/{}/contents/{}", s.host, GITHUB_ORG, repo_id, file_name);
+  let url = format!("{}/repos/{}/{}/contents/{}", s.host, s.profile.org, repo_id, file_name);
   let content_base64 = general_purpose::STANDARD.encode(content);
 
   // The GitHub Contents API requires the existing file's `sha` when updating.
@@ -201,8 +198,7 @@ pub async fn __add_file_to_repo(s: &Github, repo_id: &str, file_name: &str, cont
   // and we create it with a plain PUT (no `sha`).
   let existing_sha: Option<String> = {
     let resp = s
-      .get(&format!("{}?ref={}", &url, branch))
-      .send()
+      .send(s.get(&format!("{}?ref={}", &url, branch)))
       .await
       .context("Failed to send request to Github (__add_file_to_repo GET)")?;
     if resp.status().is_success() {
@@ -220,9 +216,7 @@ pub async fn __add_file_to_repo(s: &Github, repo_id: &str, file_name: &str, cont
   };
 
   let resp = s
-    .put(&url)
-    .json(&data)
-    .send()
+    .send(s.put(&url).json(&data))
     .await
     .context("Failed to send request to Github (__add_file_to_repo PUT)")?;
 
@@ -242,7 +236,7 @@ pub async fn __add_file_to_repo(s: &Github, repo_id: &str, file_name: &str, cont
       branch: branch.to_string(),
       sha: None,
     };
-    let resp2 = s.put(&url).json(&create_data).send().await
+    let resp2 = s.send(s.put(&url).json(&create_data)).await
       .context("Failed to send request to Github (__add_file_to_repo retry PUT)")?;
     if resp2.status().is_success() {
       return Ok(());
@@ -262,11 +256,12 @@ pub async fn __upload_release_file(
   stream: Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Unpin>,
 ) -> Result<()> {
   let response = s
-    .put(url)
-    .header("Content-Type", "application/zip")
-    .header("Content-Length", content_length.to_string())
-    .body(reqwest::Body::wrap_stream(stream))
-    .send()
+    .send(
+      s.put(url)
+        .header("Content-Type", "application/zip")
+        .header("Content-Length", content_length.to_string())
+        .body(reqwest::Body::wrap_stream(stream)),
+    )
     .await?;
 
   if response.status().is_success() {
@@ -5,7 +5,7 @@ use anyhow::{Result, bail};
 use urlencoding::encode;
 
 use crate::{
-  consts::{GITHUB_LAUNCHER_REPO_NAME, MAIN_DEVELOPER_NAME, CACHE_TTL_SEARCH_API_SECS},
+  consts::CACHE_TTL_SEARCH_API_SECS,
   providers::{
     Github::{Github::Github, models::*},
     dto::Issue,
@@ -50,7 +50,7 @@ pub async fn __find_user(s: &Github, repo_id: &str, uuid: &str) -> Result<Option
     "q".to_owned(),
     format!(
       "{} in:title repo:{}/{} is:issue author:{}",
-      uuid, MAIN_DEVELOPER_NAME, GITHUB_LAUNCHER_REPO_NAME, MAIN_DEVELOPER_NAME
+      uuid, s.profile.launcher_owner, s.profile.launcher_repo, s.profile.launcher_owner
     ),
   )]);
 
@@ -3,5 +3,6 @@ pub mod files;
 pub mod issues;
 pub mod launcher;
 pub mod models;
+pub mod rate_limit;
 pub mod release;
 pub mod repo;
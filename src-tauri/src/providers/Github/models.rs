@@ -12,6 +12,11 @@ pub struct ProjectGithub {
   pub disabled: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoInfoGithub {
+  pub default_branch: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateRepoBodyGithub {
   pub name: String,
@@ -0,0 +1,110 @@
+// GitHub API quota tracking.
+//
+// Every API response carries `X-RateLimit-Limit/Remaining/Reset`; a rejected
+// call (403/429) may carry `Retry-After` instead. Raw/CDN hosts send none of
+// them, so a response without the headers leaves the state untouched.
+//
+// GitHub keeps a separate quota per resource (`core`, `search`, `graphql`,
+// ...) and names it in `X-RateLimit-Resource`; an exhausted search quota must
+// not hold back the core calls, so the state is kept per resource.
+
+use std::{
+  collections::HashMap,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{StatusCode, Url, header::HeaderMap};
+
+use crate::providers::dto::RateLimitStatus;
+
+/// Fallback back-off when GitHub rejects a call without telling when to retry.
+const DEFAULT_BACKOFF_SECS: u64 = 60;
+
+/// Resource of the REST API calls, the one shown in the provider status.
+pub const CORE_RESOURCE: &str = "core";
+
+/// Quota state per rate-limit resource.
+pub type RateLimits = HashMap<String, RateLimitStatus>;
+
+pub fn now_secs() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+  headers.get(name).and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse::<u64>().ok())
+}
+
+/// Resource an API url is counted against, used until a response names it.
+pub fn resource_of(url: &Url) -> &'static str {
+  let path = url.path();
+  if path.starts_with("/search/") {
+    "search"
+  } else if path.starts_with("/graphql") {
+    "graphql"
+  } else {
+    CORE_RESOURCE
+  }
+}
+
+/// Updates the state of the resource the response names (`resource` when it
+/// names none). Returns true when the call was rejected because of the rate
+/// limit (the caller may retry after `wait_secs`).
+pub fn record(states: &mut RateLimits, resource: &str, status: StatusCode, headers: &HeaderMap) -> bool {
+  let now = now_secs();
+  let limit = header_u64(headers, "x-ratelimit-limit");
+  let remaining = header_u64(headers, "x-ratelimit-remaining");
+  let reset_at = header_u64(headers, "x-ratelimit-reset");
+  let retry_after = header_u64(headers, "retry-after");
+
+  if limit.is_none() && remaining.is_none() && retry_after.is_none() {
+    return false;
+  }
+
+  let resource = headers.get("x-ratelimit-resource").and_then(|v| v.to_str().ok()).unwrap_or(resource);
+  let state = states.entry(resource.to_string()).or_default();
+  if limit.is_some() {
+    state.limit = limit;
+  }
+  if remaining.is_some() {
+    state.remaining = remaining;
+  }
+  if reset_at.is_some() {
+    state.reset_at = reset_at;
+  }
+
+  let rejected = matches!(status, StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS) && (retry_after.is_some() || remaining == Some(0));
+
+  state.blocked_until = if let Some(secs) = retry_after.filter(|_| rejected) {
+    Some(now + secs)
+  } else if remaining == Some(0) {
+    // Quota used up: hold back the next calls until the window resets.
+    Some(state.reset_at.filter(|t| *t > now).unwrap_or(now + DEFAULT_BACKOFF_SECS))
+  } else {
+    None
+  };
+
+  if let Some(until) = state.blocked_until {
+    log::warn!(
+      "Github API rate limit of '{}' reached (status {}), calls are held back for {}s",
+      resource,
+      status,
+      until.saturating_sub(now)
+    );
+  }
+
+  rejected
+}
+
+/// Seconds until calls to `resource` may be sent again, None when its quota
+/// is not exhausted.
+pub fn wait_secs(states: &RateLimits, resource: &str) -> Option<u64> {
+  let now = now_secs();
+  states.get(resource)?.blocked_until.filter(|until| *until > now).map(|until| until - now)
+}
+
+/// Local "HH:MM" of a unix time, for error messages shown in the UI.
+pub fn format_time(unix_secs: u64) -> String {
+  chrono::DateTime::from_timestamp(unix_secs as i64, 0)
+    .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+    .unwrap_or_default()
+}
@@ -1,7 +1,7 @@
 use std::{collections::HashMap, vec};
 
 use crate::{
-  consts::{GITHUB_ORG, CACHE_TTL_ORG_REPOS_SECS, CACHE_TTL_RELEASE_SECS},
+  consts::{CACHE_TTL_ORG_REPOS_SECS, CACHE_TTL_RELEASE_SECS},
   providers::{
     ApiProvider::ApiProvider,
     Github::{Github::Github, models::*, repo::*},
@@ -29,15 +29,14 @@ async fn __fetch_releases(s: &Github, cashed: bool) -> Result<()> {
 
     let search_params = HashMap::from([("page".to_owned(), page.to_string()), ("per_page".to_owned(), "100".to_owned())]);
     let params = search_params.iter().map(|v| format!("{}={}", v.0, v.1)).collect::<Vec<_>>().join("&");
-    let mut url = format!("{}/orgs/{}/repos", s.host, GITHUB_ORG);
+    let mut url = format!("{}/orgs/{}/repos", s.host, s.profile.org);
 
     if search_params.len() > 0 {
       url = format!("{}?{}", &url, &params);
     }
 
     let resp = s
-      .get(&url)
-      .send()
+      .send(s.get(&url))
       .await
       .context(format!("Failed to send request to Github (__get_releases)"))?;
 
@@ -133,7 +132,7 @@ pub async fn __get_release_repos_by_name(s: &Github, release_name: &str) -> Resu
 /// Lists all releases of a concrete repo (tag, name, body/notes, created_at).
 /// Used for patch chains in updates repos.
 pub async fn __get_repo_releases(s: &Github, project_id: &str) -> Result<Vec<RepoReleaseInfo>> {
-  let url = format!("{}/repos/{}/{}/releases", &s.host, GITHUB_ORG, &project_id);
+  let url = format!("{}/repos/{}/{}/releases", &s.host, s.profile.org, &project_id);
   let cached = s.get_cached(&url, Duration::from_secs(CACHE_TTL_RELEASE_SECS)).await?;
   let releases: Vec<ReleaseGithub> = serde_json::from_slice(&cached.bytes)
     .context("Failed to parse Github releases response as JSON")?;
@@ -206,18 +205,16 @@ pub async fn __set_release_visibility(s: &Github, release_name: &str, visibility
   Ok(())
 }
 
-pub async fn __create_release(s: &Github, repo_id: &str, tag_name: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
-  let url = format!("{}/repos/{}/{}/releases", s.host, GITHUB_ORG, repo_id);
+pub async fn __create_release(s: &Github, repo_id: &str, tag_name: &str, branch: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
+  let url = format!("{}/repos/{}/{}/releases", s.host, s.profile.org, repo_id);
   let body = CreateReleaseRequestGithub {
     name: format!("Release {}", &tag_name),
     tag_name: tag_name.to_string(),
-    target_commitish: "master".to_string(),
+    target_commitish: branch.to_string(),
   };
 
   let resp = s
-    .post(&url)
-    .json(&body)
-    .send()
+    .send(s.post(&url).json(&body))
     .await
     .context("Failed to send request to Github (__create_release)")?;
 
@@ -1,7 +1,6 @@
 use std::collections::HashMap;
 
 use crate::{
-  consts::*,
   providers::{
     Github::{Github::Github, models::*},
     dto::CreateRepoResponse,
@@ -11,18 +10,16 @@ use crate::{
 use anyhow::{Context, Result, bail};
 
 pub async fn __create_repo(s: &Github, name: &str, description: &str) -> Result<CreateRepoResponse> {
-  let url = format!("{}/orgs/{}/repos", s.host, GITHUB_ORG);
+  let url = format!("{}/orgs/{}/repos", s.host, s.profile.org);
   let data = CreateRepoBodyGithub {
     name: name.to_owned(),
     description: description.to_owned(),
-    homepage: GITHUB_HOST.to_owned(),
+    homepage: s.profile.web_host.clone(),
     private: true,
   };
 
   let resp = s
-    .post(&url)
-    .json(&data)
-    .send()
+    .send(s.post(&url).json(&data))
     .await
     .context(format!("Failed to send request to Github (create_repo) name: {}", name))?;
 
@@ -49,12 +46,10 @@ pub async fn __create_repo(s: &Github, name: &str, description: &str) -> Result<
 }
 
 pub async fn __update_repo(s: &Github, repo_name: &str, data: UpdateRepoDtoGithub) -> Result<()> {
-  let url = format!("{}/repos/{}/{}", s.host, GITHUB_ORG, repo_name);
+  let url = format!("{}/repos/{}/{}", s.host, s.profile.org, repo_name);
 
   let resp = s
-    .patch(&url)
-    .json(&data)
-    .send()
+    .send(s.patch(&url).json(&data))
     .await
     .context(format!("Failed to send request to Github (update_repo) repo_name: {}", &repo_name))?;
 
@@ -66,3 +61,26 @@ pub async fn __update_repo(s: &Github, repo_name: &str, data: UpdateRepoDtoGithu
 
   Ok(())
 }
+
+pub async fn __get_default_branch(s: &Github, repo_name: &str) -> Result<String> {
+  if let Some(branch) = crate::utils::locks::lock(&s.default_branches).get(repo_name) {
+    return Ok(branch.clone());
+  }
+
+  let url = format!("{}/repos/{}/{}", s.host, s.profile.org, repo_name);
+  let resp = s
+    .send(s.get(&url))
+    .await
+    .context(format!("Failed to send request to Github (get_default_branch) repo_name: {}", &repo_name))?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    let body = resp.text().await?;
+    bail!("__get_default_branch, Github API error ({}): {} url: {}", status, body, url);
+  }
+
+  let repo: RepoInfoGithub = resp.json().await.context("Failed to parse Github repo")?;
+  crate::utils::locks::lock(&s.default_branches).insert(repo_name.to_owned(), repo.default_branch.clone());
+
+  Ok(repo.default_branch)
+}
@@ -14,7 +14,8 @@ use reqwest::{
 };
 
 use crate::{
-  consts::{GITLAB_PID, REPO_LAUNCGER_ID},
+  configs::DistributionProfile::GitlabProfile,
+  consts::{DEFAULT_BRANCH, GITLAB_PID},
   providers::{
     ApiProvider::ApiProvider,
     Gitlab::{files::*, group::*, issues::*, launcher::*, release::*, repo::*},
@@ -28,11 +29,14 @@ use rand_agents::user_agent;
 #[derive(Clone)]
 pub struct Gitlab {
   pub host: String,
+  pub profile: GitlabProfile,
   pub client: Arc<Mutex<Client>>,
   pub suppot_subgroups: bool,
 
   pub status: Arc<Mutex<ProviderStatus>>,
   pub manifest: Arc<Mutex<Manifest>>,
+  /// Default branch per repo id, see `get_default_branch`.
+  pub default_branches: Arc<Mutex<HashMap<String, String>>>,
 
   pub logger: LogCallback,
 
@@ -40,7 +44,7 @@ pub struct Gitlab {
 }
 
 impl Gitlab {
-  pub fn new(h: &str, suppot_subgroups: bool, logger: LogCallback) -> Result<Self> {
+  pub fn new(profile: &GitlabProfile, suppot_subgroups: bool, logger: LogCallback) -> Result<Self> {
     let ua = user_agent();
     log::info!("Start init Gitlab client with User-Agent: {}", &ua);
 
@@ -49,17 +53,20 @@ impl Gitlab {
       reqwest::header::USER_AGENT,
       reqwest::header::HeaderValue::from_str(&ua)?,
     );
-    let client = Client::builder().default_headers(headers).build()?;
+    let client = crate::utils::proxy::client_builder()?.default_headers(headers).build()?;
 
     Ok(Self {
-      host: h.to_string(),
+      host: profile.api_host.clone(),
+      profile: profile.clone(),
       client: Arc::new(Mutex::new(client)),
       status: Arc::new(Mutex::new(ProviderStatus {
         available: false,
         latency_ms: None,
+        rate_limit: None,
       })),
       suppot_subgroups,
       manifest: Arc::new(Mutex::new(Manifest { root_id: None, max_size: 0 })),
+      default_branches: Arc::new(Mutex::new(HashMap::new())),
       token: Arc::new(Mutex::new("".to_owned())),
       logger,
     })
@@ -105,13 +112,16 @@ impl ApiProvider for Gitlab {
       headers.insert(AUTHORIZATION, auth_value);
     }
 
-    *crate::utils::locks::lock(&self.client) = Client::builder().default_headers(headers).build()?;
+    *crate::utils::locks::lock(&self.client) = crate::utils::proxy::client_builder()?.default_headers(headers).build()?;
 
     Ok(())
   }
   fn get_token(&self) -> String {
     crate::utils::locks::lock(&self.token).clone()
   }
+  fn rebuild_client(&self) -> Result<()> {
+    self.set_token(self.get_token())
+  }
 
   fn id(&self) -> &'static str {
     GITLAB_PID
@@ -120,7 +130,7 @@ impl ApiProvider for Gitlab {
     // GitLab does not have a bg.jpg in the launcher repo, so we use the
     // projects API (unlike GitHub which pings via a raw file to avoid the
     // API rate limit).  GitLab's anonymous rate limit is more generous.
-    let url = format!("{}/projects/{}", &self.host, REPO_LAUNCGER_ID);
+    let url = format!("{}/projects/{}", &self.host, self.profile.launcher_project_id);
     log::info!("Start PING provider: {}, url: {}", self.id(), &url);
 
     let start = Instant::now();
@@ -146,6 +156,7 @@ impl ApiProvider for Gitlab {
     let new_status = ProviderStatus {
       available,
       latency_ms: if available { Some(latency_ms) } else { None },
+      rate_limit: None,
     };
 
     *crate::utils::locks::lock(&self.status) = new_status.clone();
@@ -188,10 +199,13 @@ impl ApiProvider for Gitlab {
   async fn get_launcher_bg(&self) -> Result<Vec<u8>> {
     __get_launcher_bg(self).await
   }
-  fn launcher_bg_url(&self) -> String {
+  async fn launcher_bg_url(&self) -> String {
     format!(
-      "{}/projects/{}/repository/files/data%2Fbg%2Fbg.jpg/raw?ref=master",
-      self.host, crate::consts::REPO_LAUNCGER_ID,
+      "{}/projects/{}/repository/files/{}/raw?ref={}",
+      self.host,
+      self.profile.launcher_project_id,
+      urlencoding::encode(&self.profile.bg_path),
+      DEFAULT_BRANCH,
     )
   }
   async fn get_file_raw(&self, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
@@ -233,8 +247,12 @@ impl ApiProvider for Gitlab {
     __create_tag(self, repo_id, tag_name, branch).await
   }
 
-  async fn create_release(&self, repo_id: &str, tag_name: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
-    __create_release(self, repo_id, tag_name, assets).await
+  async fn get_default_branch(&self, repo_id: &str) -> Result<String> {
+    __get_default_branch(self, repo_id).await
+  }
+
+  async fn create_release(&self, repo_id: &str, tag_name: &str, branch: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
+    __create_release(self, repo_id, tag_name, branch, assets).await
   }
 
   // Issues API
@@ -6,16 +6,19 @@ use futures_util::{Stream, StreamExt};
 use std::collections::HashMap;
 
 use crate::{
-  consts::{REPO_LAUNCGER_ID, CACHE_TTL_RAW_FILE_SECS},
+  consts::{CACHE_TTL_RAW_FILE_SECS, DEFAULT_BRANCH},
   providers::{
     Gitlab::{Gitlab::Gitlab, issues::*, models::*},
     dto::{BlobStreamWithOffset, Manifest, TreeItem},
   },
-  utils::http_cache,
+  utils::{errors::HttpStatusError, http_cache},
 };
 
 pub async fn __get_file_raw(s: &Gitlab, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
-  let url = format!("{}/projects/{}/repository/files/{}/raw?ref=master", s.host, project_id, file_path);
+  let url = format!(
+    "{}/projects/{}/repository/files/{}/raw?ref={}",
+    s.host, project_id, file_path, DEFAULT_BRANCH
+  );
   let cached = s.get_cached(&url, Duration::from_secs(CACHE_TTL_RAW_FILE_SECS)).await?;
   Ok(cached.bytes)
 }
@@ -55,9 +58,11 @@ pub async fn __get_blob_by_url_stream(s: &Gitlab, url: &str, seek: &Option<u64>)
   crate::utils::paths::assert_download_url_allowed(response.url().as_str())?;
 
   if !response.status().is_success() {
-    let status = response.status();
-    let body = response.text().await.unwrap_or_else(|_| "<failed to read response body>".to_string());
-    bail!("__get_blob_by_url_stream, Error API GitLab: {} – {}", status, body);
+    return Err(
+      HttpStatusError::from_response("__get_blob_by_url_stream, Error API GitLab", response)
+        .await
+        .into(),
+    );
   }
 
   // A server may ignore the Range header and answer 200 with the FULL body.
@@ -164,8 +169,10 @@ pub async fn __load_manifest(s: &Gitlab) -> Result<()> {
   //   bail!("Issue mainfest.json NOT FOUND!")
   // }
 
-  let str = "{\"root_id\": 122475050,\"max_size\": 10200547328}".to_owned();
-  let manifest: Manifest = serde_json::from_str(&str)?;
+  let manifest = Manifest {
+    root_id: Some(s.profile.root_group_id),
+    max_size: s.profile.max_repo_size,
+  };
   // let manifest: Manifest = serde_json::from_str(&issue[0].description)?;
 
   *crate::utils::locks::lock(&s.manifest) = manifest;
@@ -174,7 +181,7 @@ pub async fn __load_manifest(s: &Gitlab) -> Result<()> {
 }
 
 pub async fn __get_launcher_bg(s: &Gitlab) -> Result<Vec<u8>> {
-  __get_file_raw(s, &REPO_LAUNCGER_ID.to_string(), "data%2Fbg%2Fbg.jpg").await
+  __get_file_raw(s, &s.profile.launcher_project_id.to_string(), &urlencoding::encode(&s.profile.bg_path)).await
 }
 
 pub async fn __get_file_content_size(s: &Gitlab, direct_url: &str) -> Result<u64> {
@@ -42,6 +42,13 @@ pub struct ProjectGitlab {
   pub marked_for_deletion_on: Option<String>,
 }
 
+/// `default_branch` is null while the project has no commits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectInfoGitlab {
+  #[serde(default)]
+  pub default_branch: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Group {
   pub id: u32,
@@ -206,6 +213,9 @@ pub struct CreateReleaseAssetsGitlab {
 pub struct CreateReleaseRequestGitlab {
   pub name: String,
   pub tag_name: String,
+  /// Branch the tag is created from when it does not exist yet.
+  #[serde(rename = "ref")]
+  pub ref_name: String,
   pub description: String,
   pub assets: CreateReleaseAssetsGitlab,
 }
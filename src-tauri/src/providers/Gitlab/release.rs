@@ -222,11 +222,14 @@ pub async fn __create_tag(s: &Gitlab, repo_id: &str, tag_name: &str, branch: &st
   Ok(())
 }
 
-pub async fn __create_release(s: &Gitlab, repo_id: &str, tag_name: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
+/// The release is attached to the tag created by `__create_tag`; `branch` is
+/// sent as `ref` so GitLab creates the tag there if it is missing.
+pub async fn __create_release(s: &Gitlab, repo_id: &str, tag_name: &str, branch: &str, assets: Vec<CreateReleaseAsset>) -> Result<CreateReleaseResponse> {
   let url = format!("{}/projects/{}/releases", s.host, repo_id);
   let body = CreateReleaseRequestGitlab {
     name: format!("Release {}", &tag_name),
     tag_name: tag_name.to_string(),
+    ref_name: branch.to_string(),
     description: format!("Release {}", &tag_name),
     assets: CreateReleaseAssetsGitlab {
       links: assets
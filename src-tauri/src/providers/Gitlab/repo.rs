@@ -1,9 +1,12 @@
-use crate::providers::{
-  Gitlab::{
-    Gitlab::Gitlab,
-    models::{CreateRepoBodyGitlab, CreateRepoResponseGitlab, UpdateRepoDtoGitlab, Visibility},
+use crate::{
+  consts::DEFAULT_BRANCH,
+  providers::{
+    Gitlab::{
+      Gitlab::Gitlab,
+      models::{CreateRepoBodyGitlab, CreateRepoResponseGitlab, ProjectInfoGitlab, UpdateRepoDtoGitlab, Visibility},
+    },
+    dto::CreateRepoResponse,
   },
-  dto::CreateRepoResponse,
 };
 
 use anyhow::{Context, Result, bail};
@@ -65,3 +68,33 @@ pub async fn __update_repo(s: &Gitlab, repo_id: &str, data: UpdateRepoDtoGitlab)
 
   Ok(())
 }
+
+/// An empty project has no default branch yet: the instance default
+/// (`DEFAULT_BRANCH`) is returned and not cached, the first commit creates it.
+pub async fn __get_default_branch(s: &Gitlab, repo_id: &str) -> Result<String> {
+  if let Some(branch) = crate::utils::locks::lock(&s.default_branches).get(repo_id) {
+    return Ok(branch.clone());
+  }
+
+  let url = format!("{}/projects/{}", s.host, &repo_id);
+  let resp = s
+    .get(&url)
+    .send()
+    .await
+    .context(format!("Failed to send request to GitLab (get_default_branch) repo_id: {}", &repo_id))?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    let body = resp.text().await?;
+    bail!("__get_default_branch, GitLab API error ({}): {} url: {}", status, body, url);
+  }
+
+  let project: ProjectInfoGitlab = resp.json().await.context("Failed to parse GitLab project")?;
+  match project.default_branch {
+    Some(branch) => {
+      crate::utils::locks::lock(&s.default_branches).insert(repo_id.to_owned(), branch.clone());
+      Ok(branch)
+    }
+    None => Ok(DEFAULT_BRANCH.to_owned()),
+  }
+}
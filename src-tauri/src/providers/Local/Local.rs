@@ -0,0 +1,185 @@
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
+  time::Instant,
+};
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+
+use crate::{
+  consts::LOCAL_PID,
+  providers::{
+    ApiProvider::ApiProvider,
+    Local::{files::*, release::*},
+    dto::*,
+    index_backed,
+  },
+  service::{index::ReleaseIndex, main::LogCallback},
+};
+
+/// Read-only provider backed by a local or network-mounted (SMB/NFS) directory
+/// laid out like the release index: `index.json`, `bg.jpg` and the files the
+/// index points to. Asset links in the index are paths relative to the
+/// directory (or `file://` urls inside it).
+#[derive(Clone)]
+pub struct Local {
+  pub root: PathBuf,
+
+  pub status: Arc<Mutex<ProviderStatus>>,
+
+  pub logger: LogCallback,
+}
+
+impl Local {
+  pub fn new(root: &Path, logger: LogCallback) -> Result<Self> {
+    log::info!("Start init Local provider, root: {:?}", root);
+
+    Ok(Self {
+      root: root.to_path_buf(),
+      status: Arc::new(Mutex::new(ProviderStatus {
+        available: false,
+        latency_ms: None,
+        rate_limit: None,
+      })),
+      logger,
+    })
+  }
+
+  /// Maps an index link to the canonical path of a file inside `root`: a
+  /// path relative to the root, a `file://` url or an absolute path inside it
+  /// (as returned by `get_blob_direct_url`). Links escaping the root (`..`,
+  /// absolute paths elsewhere) are rejected.
+  pub fn resolve_path(&self, link: &str) -> Result<PathBuf> {
+    let root = self.root.canonicalize().with_context(|| format!("Local provider root {:?} is not accessible", &self.root))?;
+
+    let path = if link.starts_with("file://") {
+      url::Url::parse(link)?
+        .to_file_path()
+        .map_err(|_| anyhow::anyhow!("Invalid file url: {}", link))?
+    } else if link.starts_with("http://") || link.starts_with("https://") {
+      bail!("Local provider cannot serve remote url: {}", link);
+    } else if Path::new(link).starts_with(&root) {
+      PathBuf::from(link)
+    } else {
+      self.root.join(link.trim_start_matches('/'))
+    };
+
+    let path = path.canonicalize().with_context(|| format!("File {:?} not found in local provider", &path))?;
+
+    if !path.starts_with(&root) {
+      bail!("Path {:?} is outside of the local provider root {:?}", path, root);
+    }
+
+    Ok(path)
+  }
+}
+
+#[async_trait]
+impl ApiProvider for Local {
+  fn set_token(&self, _token: String) -> Result<()> {
+    Ok(())
+  }
+  fn get_token(&self) -> String {
+    "".to_owned()
+  }
+  fn rebuild_client(&self) -> Result<()> {
+    // Plain file access, no HTTP client.
+    Ok(())
+  }
+
+  fn id(&self) -> &'static str {
+    LOCAL_PID
+  }
+  async fn ping(&self) -> ProviderStatus {
+    let index_path = self.root.join("index.json");
+    log::info!("Start PING provider: {}, path: {:?}", self.id(), &index_path);
+
+    // A disconnected share makes metadata() hang or fail, so it doubles as a health check.
+    let start = Instant::now();
+    let available = tokio::fs::metadata(&index_path).await.map(|m| m.is_file()).unwrap_or(false);
+    let detail = if available { "index.json found" } else { "index.json not accessible" };
+    index_backed::record_ping(self.id(), &self.status, available, start.elapsed(), detail)
+  }
+  fn status(&self) -> ProviderStatus {
+    crate::utils::locks::lock(&self.status).clone()
+  }
+  fn is_available(&self) -> bool {
+    self.status().available
+  }
+  fn is_suppot_subgroups(&self) -> bool {
+    false
+  }
+
+  async fn load_manifest(&self) -> Result<()> {
+    // Nothing to load: releases come from index.json.
+    Ok(())
+  }
+  async fn release_index(&self) -> Result<ReleaseIndex> {
+    __load_index(self).await
+  }
+  fn get_manifest(&self) -> Result<Manifest> {
+    Ok(Manifest { root_id: None, max_size: 0 })
+  }
+
+  fn get_asset_url(&self) -> String {
+    "".to_string()
+  }
+
+  // Files API
+  async fn get_file_content_size(&self, direct_url: &str) -> Result<u64> {
+    __get_file_content_size(self, direct_url).await
+  }
+  async fn get_launcher_bg(&self) -> Result<Vec<u8>> {
+    __get_launcher_bg(self).await
+  }
+  async fn launcher_bg_url(&self) -> String {
+    url::Url::from_file_path(self.root.join("bg.jpg")).map(|u| u.to_string()).unwrap_or_default()
+  }
+  async fn get_file_raw(&self, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
+    __get_file_raw(self, project_id, file_path).await
+  }
+  async fn get_blob_stream(
+    &self,
+    project_id: &str,
+    blob_sha: &str,
+    seek: &Option<u64>,
+  ) -> Result<BlobStreamWithOffset> {
+    __get_blob_stream(self, project_id, blob_sha, seek).await
+  }
+  async fn get_blob_direct_url(&self, project_id: &str, blob_sha: &str) -> String {
+    __get_blob_direct_url(self, project_id, blob_sha).await
+  }
+  async fn get_blob_by_url_stream(&self, link: &str, seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+    __get_blob_by_url_stream(self, link, seek).await
+  }
+  async fn tree(&self, repo_id: &str, _search_params: HashMap<String, String>) -> Result<Vec<TreeItem>> {
+    __tree(self, repo_id).await
+  }
+  async fn get_full_tree(&self, repo_id: String) -> Result<Vec<TreeItem>> {
+    __tree(self, &repo_id).await
+  }
+
+  // Release
+  async fn get_launcher_latest_release(&self, owner: &str, project_id: &str) -> Result<ReleaseGit> {
+    __get_launcher_latest_release(self, owner, project_id).await
+  }
+
+  async fn get_releases(&self, cashed: bool) -> Result<Vec<Release>> {
+    __get_releases(self, cashed).await
+  }
+  async fn get_release_repos_by_name(&self, release_id: &str) -> Result<Vec<Project>> {
+    __get_release_repos_by_name(self, release_id).await
+  }
+  async fn get_updates_repos_by_name(&self, release_name: &str) -> Result<Vec<Project>> {
+    __get_updates_repos_by_name(self, release_name).await
+  }
+  async fn get_repo_releases(&self, project_id: &str) -> Result<Vec<RepoReleaseInfo>> {
+    __get_repo_releases(self, project_id).await
+  }
+
+  fn clone_box(&self) -> Box<dyn ApiProvider + Send + Sync> {
+    Box::new(self.clone())
+  }
+}
@@ -0,0 +1,96 @@
+use std::io::SeekFrom;
+
+use anyhow::{Context, Result, anyhow};
+use futures_util::StreamExt;
+use tokio::io::AsyncSeekExt;
+use tokio_util::io::ReaderStream;
+
+use crate::{
+  consts::MANIFEST_NAME,
+  providers::{
+    Local::{Local::Local, release::__find_entry},
+    dto::{BlobStreamWithOffset, TreeItem},
+    index_backed,
+  },
+};
+
+/// Read buffer of the blob stream: large sequential reads are much faster on SMB shares.
+const READ_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Link of a file of a release: `manifest.json` comes from the entry itself,
+/// anything else must be one of the entry's assets.
+async fn __file_link(s: &Local, project_id: &str, file_path: &str) -> Result<String> {
+  let entry = __find_entry(s, project_id).await?;
+
+  if file_path == MANIFEST_NAME {
+    return Ok(entry.manifest);
+  }
+
+  entry
+    .assets
+    .into_iter()
+    .find(|a| a.name == file_path)
+    .map(|a| a.url)
+    .ok_or_else(|| anyhow!("File '{}' of '{}' not found in local index", file_path, project_id))
+}
+
+pub async fn __get_file_raw(s: &Local, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
+  let path = s.resolve_path(&__file_link(s, project_id, file_path).await?)?;
+  tokio::fs::read(&path).await.with_context(|| format!("Cannot read {:?}", &path))
+}
+
+pub async fn __get_blob_stream(s: &Local, project_id: &str, file_path: &str, seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+  let link = __file_link(s, project_id, file_path).await?;
+
+  __get_blob_by_url_stream(s, &link, seek).await
+}
+/// Canonical path of the file, readable without the index link.
+pub async fn __get_blob_direct_url(s: &Local, project_id: &str, file_path: &str) -> String {
+  match __file_link(s, project_id, file_path).await.and_then(|link| s.resolve_path(&link)) {
+    Ok(path) => path.to_string_lossy().into_owned(),
+    Err(e) => {
+      log::warn!("__get_blob_direct_url: '{}' of '{}': {:#}", file_path, project_id, e);
+      String::new()
+    }
+  }
+}
+
+/// Plain file read; a resume offset past the end of the file restarts from 0
+/// (same contract as a server ignoring Range).
+pub async fn __get_blob_by_url_stream(s: &Local, link: &str, seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+  let path = s.resolve_path(link)?;
+  let mut file = tokio::fs::File::open(&path).await.with_context(|| format!("Cannot open {:?}", &path))?;
+  let len = file.metadata().await?.len();
+
+  let stream_start = match seek.filter(|bytes| *bytes > 0) {
+    Some(bytes) if bytes <= len => {
+      file.seek(SeekFrom::Start(bytes)).await.with_context(|| format!("Cannot seek {:?} to {}", &path, bytes))?;
+      bytes
+    }
+    Some(bytes) => {
+      log::warn!("__get_blob_by_url_stream: offset {} is past the end of {:?} ({} bytes), stream starts at 0", bytes, &path, len);
+      0
+    }
+    None => 0,
+  };
+
+  Ok((
+    Box::new(ReaderStream::with_capacity(file, READ_CHUNK_SIZE).map(|res| res.context("Error reading chunk from local file"))),
+    stream_start,
+  ))
+}
+
+/// Flat "tree" of a release: its assets from the index.
+pub async fn __tree(s: &Local, repo_id: &str) -> Result<Vec<TreeItem>> {
+  Ok(index_backed::tree(&__find_entry(s, repo_id).await?, repo_id))
+}
+
+pub async fn __get_launcher_bg(s: &Local) -> Result<Vec<u8>> {
+  let path = s.resolve_path("bg.jpg")?;
+  tokio::fs::read(&path).await.with_context(|| format!("Cannot read {:?}", &path))
+}
+
+pub async fn __get_file_content_size(s: &Local, direct_url: &str) -> Result<u64> {
+  let path = s.resolve_path(direct_url)?;
+  Ok(tokio::fs::metadata(&path).await?.len())
+}
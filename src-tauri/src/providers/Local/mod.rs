@@ -0,0 +1,3 @@
+pub mod Local;
+pub mod files;
+pub mod release;
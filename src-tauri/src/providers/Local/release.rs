@@ -0,0 +1,51 @@
+use anyhow::{Context, Result};
+
+use crate::{
+  consts::LOCAL_PID,
+  providers::{Local::Local::Local, dto::*, index_backed},
+  service::index::{ReleaseIndex, ReleaseIndexEntry, parse_index},
+};
+
+// Links are kept as written in the index: the download pipeline hands them
+// back to `get_blob_by_url_stream`, which resolves them inside the root.
+
+pub async fn __load_index(s: &Local) -> Result<ReleaseIndex> {
+  let path = s.resolve_path("index.json")?;
+  let bytes = tokio::fs::read(&path)
+    .await
+    .with_context(|| format!("Cannot read local release index {:?}", &path))?;
+  parse_index(&bytes, LOCAL_PID, "local file")
+}
+
+pub async fn __find_entry(s: &Local, project_id: &str) -> Result<ReleaseIndexEntry> {
+  index_backed::find_entry(__load_index(s).await?, project_id)
+}
+
+pub async fn __get_releases(s: &Local, _cashed: bool) -> Result<Vec<Release>> {
+  Ok(index_backed::releases(&__load_index(s).await?))
+}
+
+pub async fn __get_release_repos_by_name(s: &Local, release_name: &str) -> Result<Vec<Project>> {
+  Ok(index_backed::main_projects(&__find_entry(s, release_name).await?))
+}
+
+pub async fn __get_updates_repos_by_name(s: &Local, release_name: &str) -> Result<Vec<Project>> {
+  Ok(index_backed::updates_projects(&__find_entry(s, release_name).await?))
+}
+
+pub async fn __get_repo_releases(s: &Local, project_id: &str) -> Result<Vec<RepoReleaseInfo>> {
+  let entry = __find_entry(s, project_id).await?;
+  Ok(index_backed::repo_releases(entry, str::to_owned))
+}
+
+/// `project_id` of a release (or its `_main_1` project) returns the release
+/// assets, anything else returns the launcher self-update assets.
+pub async fn __get_launcher_latest_release(s: &Local, _owner: &str, project_id: &str) -> Result<ReleaseGit> {
+  let index = __load_index(s).await?;
+  let launcher = index.launcher.clone();
+
+  Ok(match index_backed::find_entry(index, project_id) {
+    Ok(entry) => index_backed::entry_release(entry, str::to_owned),
+    Err(_) => index_backed::launcher_release(launcher, str::to_owned),
+  })
+}
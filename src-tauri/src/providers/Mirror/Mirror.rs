@@ -0,0 +1,196 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rand_agents::user_agent;
+use reqwest::{
+  Client,
+  header::{HeaderMap, HeaderValue},
+};
+
+use crate::{
+  consts::MIRROR_PID,
+  providers::{
+    ApiProvider::ApiProvider,
+    Mirror::{files::*, release::*},
+    dto::*,
+    index_backed,
+  },
+  service::main::LogCallback,
+};
+
+/// Read-only provider backed by a plain static file server. Everything is
+/// resolved from the mirror's `index.json` (same schema as `service::index`);
+/// asset urls in the index may be absolute or relative to the base url.
+#[derive(Clone)]
+pub struct Mirror {
+  /// Base url without trailing slash, e.g. `https://mirror.example.org/gw`.
+  pub host: String,
+  pub client: Arc<Mutex<Client>>,
+
+  pub status: Arc<Mutex<ProviderStatus>>,
+
+  pub logger: LogCallback,
+}
+
+fn build_client() -> Result<Client> {
+  let user_agent = user_agent();
+  log::info!("Start init Mirror client with User-Agent: {}", &user_agent);
+
+  let mut headers = HeaderMap::new();
+  headers.insert("User-Agent", HeaderValue::from_str(&user_agent)?);
+
+  Ok(crate::utils::proxy::client_builder()?.default_headers(headers).build()?)
+}
+
+impl Mirror {
+  pub fn new(h: &str, logger: LogCallback) -> Result<Self> {
+    Ok(Self {
+      host: h.trim_end_matches('/').to_string(),
+      client: Arc::new(Mutex::new(build_client()?)),
+      status: Arc::new(Mutex::new(ProviderStatus {
+        available: false,
+        latency_ms: None,
+        rate_limit: None,
+      })),
+      logger,
+    })
+  }
+
+  pub fn get_client(&self) -> Client {
+    crate::utils::locks::lock(&self.client).clone()
+  }
+  pub fn get(&self, url: &str) -> reqwest::RequestBuilder {
+    self.get_client().get(url)
+  }
+  pub fn head(&self, url: &str) -> reqwest::RequestBuilder {
+    self.get_client().head(url)
+  }
+
+  /// Absolute url for a link from the index (relative links are resolved against the base url).
+  pub fn resolve_url(&self, link: &str) -> String {
+    if link.starts_with("http://") || link.starts_with("https://") {
+      link.to_string()
+    } else {
+      format!("{}/{}", self.host, link.trim_start_matches('/'))
+    }
+  }
+}
+
+#[async_trait]
+impl ApiProvider for Mirror {
+  fn set_token(&self, _token: String) -> Result<()> {
+    // Static mirrors are public.
+    Ok(())
+  }
+  fn get_token(&self) -> String {
+    "".to_owned()
+  }
+  fn rebuild_client(&self) -> Result<()> {
+    *crate::utils::locks::lock(&self.client) = build_client()?;
+    Ok(())
+  }
+
+  fn id(&self) -> &'static str {
+    MIRROR_PID
+  }
+  async fn ping(&self) -> ProviderStatus {
+    let url = format!("{}/index.json", &self.host);
+    log::info!("Start PING provider: {}, url: {}", self.id(), &url);
+
+    let start = Instant::now();
+    let res = self
+      .head(&url)
+      .timeout(Duration::from_secs(10))
+      .send()
+      .await;
+
+    let available = matches!(&res, Ok(r) if r.status().is_success());
+    let detail = match &res {
+      Ok(r) => format!("status: {}", r.status()),
+      Err(e) => format!("error: {}", e),
+    };
+    index_backed::record_ping(self.id(), &self.status, available, start.elapsed(), &detail)
+  }
+  fn status(&self) -> ProviderStatus {
+    crate::utils::locks::lock(&self.status).clone()
+  }
+  fn is_available(&self) -> bool {
+    self.status().available
+  }
+  fn is_suppot_subgroups(&self) -> bool {
+    false
+  }
+
+  async fn load_manifest(&self) -> Result<()> {
+    // Nothing to load: releases come from index.json, not from a root group.
+    Ok(())
+  }
+  fn get_manifest(&self) -> Result<Manifest> {
+    Ok(Manifest { root_id: None, max_size: 0 })
+  }
+
+  fn get_asset_url(&self) -> String {
+    "".to_string()
+  }
+
+  // Files API
+  async fn get_file_content_size(&self, direct_url: &str) -> Result<u64> {
+    __get_file_content_size(self, direct_url).await
+  }
+  async fn get_launcher_bg(&self) -> Result<Vec<u8>> {
+    __get_launcher_bg(self).await
+  }
+  async fn launcher_bg_url(&self) -> String {
+    format!("{}/bg.jpg", self.host)
+  }
+  async fn get_file_raw(&self, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
+    __get_file_raw(self, project_id, file_path).await
+  }
+  async fn get_blob_stream(
+    &self,
+    project_id: &str,
+    blob_sha: &str,
+    seek: &Option<u64>,
+  ) -> Result<BlobStreamWithOffset> {
+    __get_blob_stream(self, project_id, blob_sha, seek).await
+  }
+  async fn get_blob_direct_url(&self, project_id: &str, blob_sha: &str) -> String {
+    __get_blob_direct_url(self, project_id, blob_sha).await
+  }
+  async fn get_blob_by_url_stream(&self, link: &str, seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+    __get_blob_by_url_stream(self, link, seek).await
+  }
+  async fn tree(&self, repo_id: &str, _search_params: HashMap<String, String>) -> Result<Vec<TreeItem>> {
+    __tree(self, repo_id).await
+  }
+  async fn get_full_tree(&self, repo_id: String) -> Result<Vec<TreeItem>> {
+    __tree(self, &repo_id).await
+  }
+
+  // Release
+  async fn get_launcher_latest_release(&self, owner: &str, project_id: &str) -> Result<ReleaseGit> {
+    __get_launcher_latest_release(self, owner, project_id).await
+  }
+
+  async fn get_releases(&self, cashed: bool) -> Result<Vec<Release>> {
+    __get_releases(self, cashed).await
+  }
+  async fn get_release_repos_by_name(&self, release_id: &str) -> Result<Vec<Project>> {
+    __get_release_repos_by_name(self, release_id).await
+  }
+  async fn get_updates_repos_by_name(&self, release_name: &str) -> Result<Vec<Project>> {
+    __get_updates_repos_by_name(self, release_name).await
+  }
+  async fn get_repo_releases(&self, project_id: &str) -> Result<Vec<RepoReleaseInfo>> {
+    __get_repo_releases(self, project_id).await
+  }
+
+  fn clone_box(&self) -> Box<dyn ApiProvider + Send + Sync> {
+    Box::new(self.clone())
+  }
+}
@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use futures_util::StreamExt;
+
+use crate::{
+  consts::{CACHE_TTL_BACKGROUND_SECS, CACHE_TTL_RAW_FILE_SECS, MANIFEST_NAME},
+  providers::{
+    ApiProvider::ApiProvider,
+    Mirror::{Mirror::Mirror, release::__find_entry},
+    dto::{BlobStreamWithOffset, TreeItem},
+    index_backed,
+  },
+  utils::{errors::HttpStatusError, http_cache},
+};
+
+/// Url of a file of a release: `manifest.json` comes from the entry itself,
+/// anything else must be one of the entry's assets.
+async fn __file_url(s: &Mirror, project_id: &str, file_path: &str) -> Result<String> {
+  let entry = __find_entry(s, project_id).await?;
+
+  if file_path == MANIFEST_NAME {
+    return Ok(s.resolve_url(&entry.manifest));
+  }
+
+  entry
+    .assets
+    .iter()
+    .find(|a| a.name == file_path)
+    .map(|a| s.resolve_url(&a.url))
+    .ok_or_else(|| anyhow!("File '{}' of '{}' not found in mirror index", file_path, project_id))
+}
+
+pub async fn __get_file_raw(s: &Mirror, project_id: &str, file_path: &str) -> Result<Vec<u8>> {
+  let url = __file_url(s, project_id, file_path).await?;
+  let cached = http_cache::fetch(&s.get_client(), &url, Duration::from_secs(CACHE_TTL_RAW_FILE_SECS)).await?;
+  Ok(cached.bytes)
+}
+
+pub async fn __get_blob_stream(s: &Mirror, project_id: &str, file_path: &str, seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+  let url = __file_url(s, project_id, file_path).await?;
+
+  __get_blob_by_url_stream(s, &url, seek).await
+}
+pub async fn __get_blob_direct_url(s: &Mirror, project_id: &str, file_path: &str) -> String {
+  __file_url(s, project_id, file_path).await.unwrap_or_default()
+}
+
+pub async fn __get_blob_by_url_stream(s: &Mirror, link: &str, seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+  let url = s.resolve_url(link);
+  crate::utils::paths::assert_download_url_allowed(&url)?;
+
+  // Only ask for a Range when there is a real resume offset.
+  let resume_from = seek.filter(|bytes| *bytes > 0);
+
+  let response = match resume_from {
+    Some(bytes) => s
+      .get(&url)
+      .header("Range", format!("bytes={}-", bytes))
+      .send()
+      .await
+      .context("Failed to send blob download request")?,
+    None => s.get(&url).send().await.context("Failed to send blob download request")?,
+  };
+
+  crate::utils::paths::assert_download_url_allowed(response.url().as_str())?;
+
+  if !response.status().is_success() {
+    return Err(
+      HttpStatusError::from_response("__get_blob_by_url_stream, Error Mirror", response)
+        .await
+        .into(),
+    );
+  }
+
+  // A static server without Range support answers 200 with the FULL body.
+  // Report the real stream start and let the caller restart from scratch.
+  let stream_start = if let Some(bytes) = resume_from {
+    if response.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+      response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(crate::utils::parse_strings::parse_content_range_start)
+        .unwrap_or(bytes)
+    } else {
+      log::warn!(
+        "__get_blob_by_url_stream: server ignored Range (status {}), stream starts at 0",
+        response.status()
+      );
+      0
+    }
+  } else {
+    0
+  };
+
+  Ok((
+    Box::new(response.bytes_stream().map(|res| res.context("Error reading chunk from response stream"))),
+    stream_start,
+  ))
+}
+
+/// Flat "tree" of a release: its assets from the index.
+pub async fn __tree(s: &Mirror, repo_id: &str) -> Result<Vec<TreeItem>> {
+  Ok(index_backed::tree(&__find_entry(s, repo_id).await?, repo_id))
+}
+
+pub async fn __get_launcher_bg(s: &Mirror) -> Result<Vec<u8>> {
+  let url = s.launcher_bg_url().await;
+  let cached = http_cache::fetch(&s.get_client(), &url, Duration::from_secs(CACHE_TTL_BACKGROUND_SECS)).await?;
+  Ok(cached.bytes)
+}
+
+pub async fn __get_file_content_size(s: &Mirror, direct_url: &str) -> Result<u64> {
+  let url = s.resolve_url(direct_url);
+  let resp = s
+    .head(&url)
+    .send()
+    .await
+    .context("Failed to send request to Mirror (__get_file_content_size)")?;
+
+  if !resp.status().is_success() {
+    let status = resp.status();
+    bail!("__get_file_content_size, Mirror error {} url: {}", status, url);
+  }
+
+  let mut size: u64 = 0;
+  if let Some(header) = resp.headers().get("content-length") {
+    size = header.to_str()?.parse()?;
+  };
+
+  Ok(size)
+}
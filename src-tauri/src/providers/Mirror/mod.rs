@@ -0,0 +1,3 @@
+pub mod Mirror;
+pub mod files;
+pub mod release;
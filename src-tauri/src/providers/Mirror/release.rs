@@ -0,0 +1,44 @@
+use anyhow::Result;
+
+use crate::{
+  consts::MIRROR_PID,
+  providers::{Mirror::Mirror::Mirror, dto::*, index_backed},
+  service::index::{ReleaseIndex, ReleaseIndexEntry, load_index},
+};
+
+pub async fn __load_index(_s: &Mirror) -> Result<ReleaseIndex> {
+  load_index(MIRROR_PID).await
+}
+
+pub async fn __find_entry(s: &Mirror, project_id: &str) -> Result<ReleaseIndexEntry> {
+  index_backed::find_entry(__load_index(s).await?, project_id)
+}
+
+pub async fn __get_releases(s: &Mirror, _cashed: bool) -> Result<Vec<Release>> {
+  Ok(index_backed::releases(&__load_index(s).await?))
+}
+
+pub async fn __get_release_repos_by_name(s: &Mirror, release_name: &str) -> Result<Vec<Project>> {
+  Ok(index_backed::main_projects(&__find_entry(s, release_name).await?))
+}
+
+pub async fn __get_updates_repos_by_name(s: &Mirror, release_name: &str) -> Result<Vec<Project>> {
+  Ok(index_backed::updates_projects(&__find_entry(s, release_name).await?))
+}
+
+pub async fn __get_repo_releases(s: &Mirror, project_id: &str) -> Result<Vec<RepoReleaseInfo>> {
+  let entry = __find_entry(s, project_id).await?;
+  Ok(index_backed::repo_releases(entry, |url| s.resolve_url(url)))
+}
+
+/// `project_id` of a release (or its `_main_1` project) returns the release
+/// assets, anything else returns the launcher self-update assets.
+pub async fn __get_launcher_latest_release(s: &Mirror, _owner: &str, project_id: &str) -> Result<ReleaseGit> {
+  let index = __load_index(s).await?;
+  let launcher = index.launcher.clone();
+
+  Ok(match index_backed::find_entry(index, project_id) {
+    Ok(entry) => index_backed::entry_release(entry, |url| s.resolve_url(url)),
+    Err(_) => index_backed::launcher_release(launcher, |url| s.resolve_url(url)),
+  })
+}
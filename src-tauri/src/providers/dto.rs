@@ -20,6 +20,33 @@ pub enum ReleasePlatform {
 pub struct ProviderStatus {
   pub available: bool,
   pub latency_ms: Option<u64>,
+  /// API quota state (GitHub only, None for providers without a rate limit).
+  pub rate_limit: Option<RateLimitStatus>,
+}
+
+/// Last seen `X-RateLimit-*` / `Retry-After` values. Times are unix seconds.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct RateLimitStatus {
+  pub limit: Option<u64>,
+  pub remaining: Option<u64>,
+  pub reset_at: Option<u64>,
+  /// Calls are held back until this time; None = quota is not exhausted.
+  pub blocked_until: Option<u64>,
+}
+
+/// Rolling statistics of the background health checks (`service::health_monitor`).
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ProviderHealth {
+  /// Average latency of the successful checks in the window.
+  pub avg_latency_ms: Option<u64>,
+  /// Share of failed checks in the window, 0.0..=1.0.
+  pub error_rate: f64,
+  pub consecutive_failures: u32,
+  pub consecutive_successes: u32,
+  /// Checks in the window (at most `HEALTH_WINDOW`).
+  pub checks: u32,
+  /// Unix seconds of the last check.
+  pub last_checked_at: Option<u64>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
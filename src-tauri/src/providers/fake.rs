@@ -0,0 +1,150 @@
+// Provider test double shared by the service and handler tests.
+//
+// Serves a fixed status, release index, blobs by URL and updates repo
+// releases; everything else is `unimplemented!`. The API calls it answers
+// are recorded, so a test can check what was (not) requested.
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use crate::{
+  providers::{ApiProvider::ApiProvider, dto::*},
+  service::index::ReleaseIndex,
+};
+
+#[derive(Clone)]
+pub struct Fake {
+  pub id: &'static str,
+  pub status: ProviderStatus,
+  pub index: Option<ReleaseIndex>,
+  /// Bodies of `get_blob_by_url_stream`, by URL.
+  pub blobs: HashMap<String, Vec<u8>>,
+  /// Answer of `get_updates_repos_by_name`.
+  pub updates_repos: Vec<Project>,
+  /// Answer of `get_repo_releases`.
+  pub repo_releases: Vec<RepoReleaseInfo>,
+  /// Requests made, e.g. `blob <url>`; shared by the clones.
+  pub calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl Fake {
+  /// Available with `latency_ms`, or down when it is None.
+  pub fn new(id: &'static str, latency_ms: Option<u64>) -> Self {
+    Self {
+      id,
+      status: ProviderStatus {
+        available: latency_ms.is_some(),
+        latency_ms,
+        rate_limit: None,
+      },
+      index: None,
+      blobs: HashMap::new(),
+      updates_repos: vec![],
+      repo_releases: vec![],
+      calls: Arc::new(Mutex::new(vec![])),
+    }
+  }
+
+  pub fn calls(&self) -> Vec<String> {
+    crate::utils::locks::lock(&self.calls).clone()
+  }
+
+  fn record(&self, call: String) {
+    crate::utils::locks::lock(&self.calls).push(call);
+  }
+}
+
+#[async_trait]
+impl ApiProvider for Fake {
+  fn id(&self) -> &'static str {
+    self.id
+  }
+  async fn ping(&self) -> ProviderStatus {
+    self.status.clone()
+  }
+  fn status(&self) -> ProviderStatus {
+    self.status.clone()
+  }
+  fn is_available(&self) -> bool {
+    self.status.available
+  }
+  fn is_suppot_subgroups(&self) -> bool {
+    false
+  }
+  fn set_token(&self, _token: String) -> Result<()> {
+    Ok(())
+  }
+  fn get_token(&self) -> String {
+    "".to_owned()
+  }
+  fn rebuild_client(&self) -> Result<()> {
+    Ok(())
+  }
+  async fn load_manifest(&self) -> Result<()> {
+    Ok(())
+  }
+  async fn release_index(&self) -> Result<ReleaseIndex> {
+    self.index.clone().ok_or_else(|| anyhow!("no index"))
+  }
+  fn get_manifest(&self) -> Result<Manifest> {
+    Ok(Manifest { root_id: None, max_size: 0 })
+  }
+  async fn get_launcher_bg(&self) -> Result<Vec<u8>> {
+    unimplemented!()
+  }
+  async fn launcher_bg_url(&self) -> String {
+    unimplemented!()
+  }
+  async fn get_file_raw(&self, _project_id: &str, _file_path: &str) -> Result<Vec<u8>> {
+    unimplemented!()
+  }
+  async fn get_blob_stream(&self, _project_id: &str, _blob_sha: &str, _seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+    unimplemented!()
+  }
+  async fn get_blob_direct_url(&self, _project_id: &str, _blob_sha: &str) -> String {
+    unimplemented!()
+  }
+  async fn get_blob_by_url_stream(&self, link: &str, _seek: &Option<u64>) -> Result<BlobStreamWithOffset> {
+    self.record(format!("blob {}", link));
+    let body = self.blobs.get(link).cloned().ok_or_else(|| anyhow!("404 {}", link))?;
+    let chunk: Result<bytes::Bytes> = Ok(body.into());
+    Ok((Box::new(futures_util::stream::iter([chunk])), 0))
+  }
+  async fn tree(&self, _repo_id: &str, _search_params: HashMap<String, String>) -> Result<Vec<TreeItem>> {
+    unimplemented!()
+  }
+  async fn get_full_tree(&self, _repo_id: String) -> Result<Vec<TreeItem>> {
+    unimplemented!()
+  }
+  async fn get_file_content_size(&self, _direct_url: &str) -> Result<u64> {
+    unimplemented!()
+  }
+  fn get_asset_url(&self) -> String {
+    "".to_owned()
+  }
+  async fn get_launcher_latest_release(&self, _owner: &str, _project_id: &str) -> Result<ReleaseGit> {
+    unimplemented!()
+  }
+  async fn get_releases(&self, _cashed: bool) -> Result<Vec<Release>> {
+    unimplemented!()
+  }
+  async fn get_release_repos_by_name(&self, _release_id: &str) -> Result<Vec<Project>> {
+    unimplemented!()
+  }
+  async fn get_updates_repos_by_name(&self, release_name: &str) -> Result<Vec<Project>> {
+    self.record(format!("updates_repos {}", release_name));
+    Ok(self.updates_repos.clone())
+  }
+  async fn get_repo_releases(&self, project_id: &str) -> Result<Vec<RepoReleaseInfo>> {
+    self.record(format!("repo_releases {}", project_id));
+    Ok(self.repo_releases.clone())
+  }
+  fn clone_box(&self) -> Box<dyn ApiProvider + Send + Sync> {
+    Box::new(self.clone())
+  }
+}
@@ -0,0 +1,166 @@
+// Shared mapping of `service::index` entries to provider DTOs for providers
+// that have no API and serve everything from an `index.json` (Mirror, Local).
+//
+// Such a provider has no repos: every release entry is exposed as a
+// GitHub-like pair of virtual projects `<path>_main_1` / `<path>_updates_1`,
+// so the shared API fallbacks (is_main_repo, project_id_for, ...) keep working.
+
+use std::{sync::Mutex, time::Duration};
+
+use anyhow::{Result, anyhow};
+
+use crate::{
+  consts::{EXE_LINUX_NAME, EXE_WIN_NAME},
+  providers::dto::*,
+  service::index::{LauncherIndex, ReleaseIndex, ReleaseIndexEntry},
+};
+
+const MAIN_SUFFIX: &str = "_main_1";
+const UPDATES_SUFFIX: &str = "_updates_1";
+
+/// Finds the entry for a release name/path or one of its virtual project names.
+pub fn find_entry(index: ReleaseIndex, project_id: &str) -> Result<ReleaseIndexEntry> {
+  let release = project_id
+    .strip_suffix(MAIN_SUFFIX)
+    .or_else(|| project_id.strip_suffix(UPDATES_SUFFIX))
+    .unwrap_or(project_id);
+
+  index
+    .releases
+    .into_iter()
+    .find(|r| r.path == release || r.name == release)
+    .ok_or_else(|| anyhow!("Release '{}' not found in index", release))
+}
+
+pub fn releases(index: &ReleaseIndex) -> Vec<Release> {
+  index
+    .releases
+    .iter()
+    .enumerate()
+    .map(|(i, entry)| Release {
+      id: (i + 1) as u32,
+      name: entry.name.clone(),
+      path: entry.path.clone(),
+    })
+    .collect()
+}
+
+fn virtual_project(entry: &ReleaseIndexEntry, suffix: &str) -> Project {
+  let name = format!("{}{}", &entry.path, suffix);
+  Project {
+    id: 1,
+    name: name.clone(),
+    path: name,
+    ssh_remote_url: "".to_owned(),
+    marked_for_deletion_on: None,
+  }
+}
+
+pub fn main_projects(entry: &ReleaseIndexEntry) -> Vec<Project> {
+  vec![virtual_project(entry, MAIN_SUFFIX)]
+}
+
+pub fn updates_projects(entry: &ReleaseIndexEntry) -> Vec<Project> {
+  vec![virtual_project(entry, UPDATES_SUFFIX)]
+}
+
+/// Patch chain of a release (already ordered by the index writer).
+pub fn repo_releases(entry: ReleaseIndexEntry, resolve: impl Fn(&str) -> String) -> Vec<RepoReleaseInfo> {
+  entry
+    .patches
+    .into_iter()
+    .map(|p| RepoReleaseInfo {
+      name: p.tag.clone(),
+      tag_name: p.tag,
+      body: p.notes,
+      created_at: None,
+      assets: p
+        .assets
+        .into_iter()
+        .map(|a| RepoReleaseAsset {
+          name: a.name,
+          size: Some(a.size),
+          download_link: resolve(&a.url),
+        })
+        .collect(),
+    })
+    .collect()
+}
+
+fn get_platform_type(asset_name: &str) -> ReleasePlatform {
+  if asset_name == EXE_WIN_NAME {
+    ReleasePlatform::Windows
+  } else if asset_name == EXE_LINUX_NAME {
+    ReleasePlatform::Linux
+  } else {
+    ReleasePlatform::MacOS
+  }
+}
+
+pub fn entry_release(entry: ReleaseIndexEntry, resolve: impl Fn(&str) -> String) -> ReleaseGit {
+  ReleaseGit {
+    name: entry.name,
+    version: entry.tag,
+    assets: entry
+      .assets
+      .into_iter()
+      .map(|a| ReleaseAssetGit {
+        platform: get_platform_type(&a.name),
+        name: a.name,
+        size: a.size,
+        download_link: resolve(&a.url),
+      })
+      .collect(),
+  }
+}
+
+pub fn launcher_release(launcher: LauncherIndex, resolve: impl Fn(&str) -> String) -> ReleaseGit {
+  ReleaseGit {
+    name: launcher.version.clone(),
+    version: launcher.version,
+    assets: launcher
+      .assets
+      .into_iter()
+      .map(|a| ReleaseAssetGit {
+        platform: match a.platform.as_str() {
+          "windows" => ReleasePlatform::Windows,
+          "linux" => ReleasePlatform::Linux,
+          _ => ReleasePlatform::MacOS,
+        },
+        name: a.name,
+        size: a.size,
+        download_link: resolve(&a.url),
+      })
+      .collect(),
+  }
+}
+
+/// Flat "tree" of a release: its assets from the index.
+pub fn tree(entry: &ReleaseIndexEntry, repo_id: &str) -> Vec<TreeItem> {
+  entry
+    .assets
+    .iter()
+    .map(|a| TreeItem {
+      id: a.name.clone(),
+      project_id: repo_id.to_string(),
+      name: a.name.clone(),
+      path: a.name.clone(),
+      item_type: "blob".to_owned(),
+    })
+    .collect()
+}
+
+/// Logs and stores the result of a ping; these providers have no rate limit.
+pub fn record_ping(provider_id: &str, status: &Mutex<ProviderStatus>, available: bool, elapsed: Duration, detail: &str) -> ProviderStatus {
+  let latency_ms = elapsed.as_millis() as u64;
+  log::info!("PING provider {} result, {}, ms: {}", provider_id, detail, &latency_ms);
+
+  let new_status = ProviderStatus {
+    available,
+    latency_ms: if available { Some(latency_ms) } else { None },
+    rate_limit: None,
+  };
+
+  *crate::utils::locks::lock(status) = new_status.clone();
+  new_status
+}
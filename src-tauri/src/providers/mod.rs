@@ -1,5 +1,11 @@
 pub mod ApiClient;
 pub mod ApiProvider;
+pub mod Gitea;
 pub mod Github;
 pub mod Gitlab;
+pub mod Local;
+pub mod Mirror;
 pub mod dto;
+#[cfg(test)]
+pub mod fake;
+pub mod index_backed;
@@ -0,0 +1,287 @@
+// Global download bandwidth budget.
+//
+// One token bucket shared by every download worker (it lives in `ServiceFiles`),
+// so the speed cap holds for the sum of all parallel files. Tokens are bytes;
+// a chunk is always taken whole and the bucket may go into debt, the caller
+// then sleeps until the debt is paid back at the configured rate.
+//
+// The rate comes from a `BandwidthPolicy`: a base cap plus optional
+// time-of-day rules ("unlimited 01:00–07:00, 2 MB/s otherwise"). Downloads
+// re-evaluate it while running, so both a schedule boundary and a settings
+// change take effect without a restart.
+
+use std::{
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use chrono::{NaiveTime, Timelike};
+use serde::Serialize;
+
+use crate::configs::AppConfig::BandwidthRule;
+
+/// Burst allowed after an idle period, in seconds of the rate.
+const BURST_SECS: f64 = 0.5;
+
+struct BucketState {
+  /// Bytes per second, 0 = unlimited.
+  rate: u64,
+  tokens: f64,
+  last_refill: Instant,
+}
+
+pub struct TokenBucket {
+  state: Mutex<BucketState>,
+}
+
+impl TokenBucket {
+  pub fn new(rate: u64) -> Self {
+    Self {
+      state: Mutex::new(BucketState {
+        rate,
+        tokens: 0.0,
+        last_refill: Instant::now(),
+      }),
+    }
+  }
+
+  /// Changes the rate; running downloads pick it up on their next chunk.
+  pub fn set_rate(&self, rate: u64) {
+    let mut state = crate::utils::locks::lock(&self.state);
+    if state.rate != rate {
+      state.rate = rate;
+      state.tokens = 0.0;
+      state.last_refill = Instant::now();
+    }
+  }
+
+  pub fn rate(&self) -> u64 {
+    crate::utils::locks::lock(&self.state).rate
+  }
+
+  /// Takes `bytes` from the bucket and returns how long the caller must wait
+  /// before using them. Zero when unlimited or enough tokens were saved up.
+  fn take(&self, bytes: u64) -> Duration {
+    let mut state = crate::utils::locks::lock(&self.state);
+    if state.rate == 0 {
+      return Duration::ZERO;
+    }
+
+    let now = Instant::now();
+    let rate = state.rate as f64;
+    let refilled = state.tokens + now.duration_since(state.last_refill).as_secs_f64() * rate;
+    state.tokens = refilled.min(rate * BURST_SECS) - bytes as f64;
+    state.last_refill = now;
+
+    if state.tokens >= 0.0 {
+      Duration::ZERO
+    } else {
+      Duration::from_secs_f64(-state.tokens / rate)
+    }
+  }
+
+  /// Waits until `bytes` may be written.
+  pub async fn acquire(&self, bytes: u64) {
+    let wait = self.take(bytes);
+    if !wait.is_zero() {
+      tokio::time::sleep(wait).await;
+    }
+  }
+}
+
+/// Active cap, reported with every `download-speed-status` event.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ThrottleStatus {
+  /// Bytes per second, 0 = unlimited.
+  pub limit: u64,
+  /// True when the cap comes from a schedule rule rather than the base limit.
+  pub scheduled: bool,
+}
+
+struct ScheduleWindow {
+  from: u32,
+  to: u32,
+  limit: u64,
+}
+
+fn parse_time(value: &str) -> Result<u32> {
+  let time = NaiveTime::parse_from_str(value.trim(), "%H:%M").with_context(|| format!("Invalid time '{}', expected HH:MM", value))?;
+  Ok(time.num_seconds_from_midnight())
+}
+
+impl ScheduleWindow {
+  fn contains(&self, secs: u32) -> bool {
+    if self.from <= self.to {
+      secs >= self.from && secs < self.to
+    } else {
+      // Spans midnight, e.g. 23:00–07:00.
+      secs >= self.from || secs < self.to
+    }
+  }
+}
+
+#[derive(Default)]
+pub struct BandwidthPolicy {
+  base: u64,
+  windows: Vec<ScheduleWindow>,
+}
+
+impl BandwidthPolicy {
+  /// Fails on a rule with a malformed time, so the settings command can reject it.
+  pub fn new(base: u64, schedule: &[BandwidthRule]) -> Result<Self> {
+    let windows = schedule
+      .iter()
+      .map(|rule| {
+        Ok(ScheduleWindow {
+          from: parse_time(&rule.from)?,
+          to: parse_time(&rule.to)?,
+          limit: rule.limit,
+        })
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    Ok(Self { base, windows })
+  }
+
+  pub fn status_at(&self, time: NaiveTime) -> ThrottleStatus {
+    let secs = time.num_seconds_from_midnight();
+    match self.windows.iter().find(|w| w.contains(secs)) {
+      Some(w) => ThrottleStatus {
+        limit: w.limit,
+        scheduled: true,
+      },
+      None => ThrottleStatus {
+        limit: self.base,
+        scheduled: false,
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rule(from: &str, to: &str, limit: u64) -> BandwidthRule {
+    BandwidthRule {
+      from: from.to_owned(),
+      to: to.to_owned(),
+      limit,
+    }
+  }
+
+  fn at(h: u32, m: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(h, m, 0).unwrap()
+  }
+
+  #[test]
+  fn window_spanning_midnight() {
+    let policy = BandwidthPolicy::new(2_000_000, &[rule("23:00", "07:00", 0)]).unwrap();
+
+    for time in [at(23, 0), at(23, 59), at(0, 0), at(3, 30), at(6, 59)] {
+      let status = policy.status_at(time);
+      assert!(status.scheduled, "{} should be in the window", time);
+      assert_eq!(status.limit, 0);
+    }
+    for time in [at(7, 0), at(12, 0), at(22, 59)] {
+      let status = policy.status_at(time);
+      assert!(!status.scheduled, "{} should be outside the window", time);
+      assert_eq!(status.limit, 2_000_000);
+    }
+  }
+
+  #[test]
+  fn window_within_a_day() {
+    let policy = BandwidthPolicy::new(0, &[rule("09:00", "18:00", 500_000)]).unwrap();
+
+    assert_eq!(policy.status_at(at(9, 0)).limit, 500_000);
+    assert_eq!(policy.status_at(at(17, 59)).limit, 500_000);
+    assert!(!policy.status_at(at(18, 0)).scheduled);
+    assert!(!policy.status_at(at(8, 59)).scheduled);
+  }
+
+  #[test]
+  fn first_matching_rule_wins() {
+    let policy = BandwidthPolicy::new(0, &[rule("01:00", "07:00", 100), rule("00:00", "12:00", 200)]).unwrap();
+
+    assert_eq!(policy.status_at(at(2, 0)).limit, 100);
+    assert_eq!(policy.status_at(at(0, 30)).limit, 200);
+    assert_eq!(policy.status_at(at(13, 0)).limit, 0);
+  }
+
+  #[test]
+  fn malformed_time_is_rejected() {
+    assert!(BandwidthPolicy::new(0, &[rule("25:00", "07:00", 0)]).is_err());
+    assert!(BandwidthPolicy::new(0, &[rule("23:00", "7", 0)]).is_err());
+  }
+
+  /// Pretends the last refill happened `ago` before now.
+  fn idle(bucket: &TokenBucket, ago: Duration) {
+    crate::utils::locks::lock(&bucket.state).last_refill = Instant::now() - ago;
+  }
+
+  fn assert_near(actual: Duration, expected_secs: f64) {
+    assert!(
+      (actual.as_secs_f64() - expected_secs).abs() < 0.05,
+      "{:?} is not ~{}s",
+      actual,
+      expected_secs
+    );
+  }
+
+  #[test]
+  fn unlimited_bucket_never_waits() {
+    let bucket = TokenBucket::new(0);
+    assert!(bucket.take(u64::MAX / 2).is_zero());
+    assert!(bucket.take(1 << 30).is_zero());
+  }
+
+  #[test]
+  fn tokens_refill_at_the_rate() {
+    let bucket = TokenBucket::new(1_000);
+    idle(&bucket, Duration::from_millis(400));
+
+    // ~400 tokens saved up, 300 are taken.
+    assert!(bucket.take(300).is_zero());
+    let tokens = crate::utils::locks::lock(&bucket.state).tokens;
+    assert!((90.0..=150.0).contains(&tokens), "{} tokens left", tokens);
+  }
+
+  #[test]
+  fn refill_is_capped_at_the_burst() {
+    let bucket = TokenBucket::new(1_000);
+    idle(&bucket, Duration::from_secs(60));
+
+    // Only BURST_SECS of the rate (500 bytes) is kept, the rest is a debt of 1000.
+    assert_near(bucket.take(1_500), 1.0);
+  }
+
+  #[test]
+  fn debt_accumulates_across_takes() {
+    let bucket = TokenBucket::new(1_000);
+
+    assert_near(bucket.take(2_000), 2.0);
+    assert_near(bucket.take(1_000), 3.0);
+  }
+
+  #[test]
+  fn set_rate_drains_saved_tokens() {
+    let bucket = TokenBucket::new(1_000);
+    crate::utils::locks::lock(&bucket.state).tokens = 500.0;
+
+    bucket.set_rate(2_000);
+    assert_eq!(bucket.rate(), 2_000);
+    assert_eq!(crate::utils::locks::lock(&bucket.state).tokens, 0.0);
+    assert!(!bucket.take(1_000).is_zero());
+  }
+
+  #[test]
+  fn set_rate_to_the_same_value_keeps_tokens() {
+    let bucket = TokenBucket::new(1_000);
+    crate::utils::locks::lock(&bucket.state).tokens = 400.0;
+
+    bucket.set_rate(1_000);
+    assert_eq!(crate::utils::locks::lock(&bucket.state).tokens, 400.0);
+  }
+}
@@ -0,0 +1,478 @@
+// Offline install bundle.
+//
+// A bundle carries one release and its patch chain for machines without
+// internet: the archives, their manifests, the release index entry and the
+// patch notes. Layout (a directory, or the same tree in a `.zip` container):
+//
+//   bundle.json                      BundleInfo
+//   release/manifest.json, data*.zip
+//   patches/<tag>/manifest.json, data*.zip   (one dir per patch, chain order in bundle.json)
+//
+// Import unpacks the release, applies the patches through the normal patch
+// path and only then registers it in `installed_versions` like an online
+// install.
+
+use std::{
+  io::{BufReader, BufWriter},
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, broadcast};
+use zip::{CompressionMethod, ZipArchive, ZipWriter, write::FileOptions};
+
+use crate::{
+  configs::AppConfig::{AppConfig, Version},
+  consts::{BUNDLE_INFO_NAME, BUNDLE_SCHEMA_VERSION, MANIFEST_NAME},
+  handlers::{
+    dto::ReleaseManifest,
+    patch_install::{apply_patch, download_manifest},
+  },
+  providers::ApiClient::ApiClient::ApiClient,
+  service::{
+    disk_space::{SpacePlan, bundle_plan},
+    files::{ServiceFiles, fetch_asset},
+    get_release::manifest_from_index_entry,
+    index::{IndexPatch, ReleaseIndexEntry},
+    patch_backup, staging,
+    unpack::ServiceUnpacker,
+  },
+  utils::{
+    hash::sha256_file,
+    patch_markers::{InstalledPatch, read_installed_patches, remove_patch_marker},
+  },
+};
+
+const RELEASE_DIR: &str = "release";
+const PATCHES_DIR: &str = "patches";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundlePatch {
+  pub tag: String,
+  #[serde(default)]
+  pub base_patch: Option<String>,
+  #[serde(default)]
+  pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleInfo {
+  pub schema: u32,
+  pub created_at: String,
+  /// Provider the bundle was exported from; recorded in the patch markers.
+  pub provider_id: String,
+  pub release: ReleaseIndexEntry,
+  /// Patch chain in install order.
+  #[serde(default)]
+  pub patches: Vec<BundlePatch>,
+}
+
+/// Orders patches along the `base_patch` chain, starting from the full release.
+/// Patches outside the chain (and cumulative ones) are left out.
+fn patch_chain(patches: &[IndexPatch]) -> Vec<&IndexPatch> {
+  let mut chain: Vec<&IndexPatch> = vec![];
+  let mut base: Option<&str> = None;
+  while let Some(next) = patches
+    .iter()
+    .find(|p| p.supersedes.is_empty() && p.base_patch.as_deref().filter(|b| !b.is_empty()) == base)
+  {
+    if chain.iter().any(|p| p.tag == next.tag) {
+      break;
+    }
+    chain.push(next);
+    base = Some(next.tag.as_str());
+  }
+
+  if chain.len() < patches.len() {
+    log::warn!(
+      "Bundle: {} patches are not part of the chain and are skipped",
+      patches.len() - chain.len()
+    );
+  }
+  chain
+}
+
+fn is_container(path: &Path) -> bool {
+  path.extension().is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+}
+
+/// dataN.zip, or fullN.zip with the full copies of the delta files.
+fn is_patch_archive(name: &str) -> bool {
+  name.starts_with("data") || name.starts_with("full")
+}
+
+/// `data2.zip` before `data10.zip`: by name, then by the number ending the file stem.
+fn archive_order(path: &Path) -> (String, u64) {
+  let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+  let name = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+  let number = stem[name.len()..].parse().unwrap_or(0);
+  (name.to_owned(), number)
+}
+
+fn staging_dir(path: &Path) -> PathBuf {
+  PathBuf::from(format!("{}.tmp", path.display()))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+  let json = serde_json::to_string_pretty(value)?;
+  std::fs::write(path, json).with_context(|| format!("Cannot write {:?}", path))
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
+  let bytes = std::fs::read(path).with_context(|| format!("Cannot read {:?}", path))?;
+  serde_json::from_slice(&bytes).with_context(|| format!("Cannot parse {:?}", path))
+}
+
+/// Writes the bundle of `version_name` (release + patch chain of the current
+/// provider's index) to `dest`: a directory, or a container when `dest` ends
+/// with `.zip`. Re-running an interrupted export keeps what is already there.
+pub async fn export_bundle(
+  api_client: &ApiClient,
+  files: &ServiceFiles,
+  version_name: &str,
+  dest: &Path,
+  cancel_tx: &broadcast::Sender<()>,
+  log: impl Fn(String),
+) -> Result<()> {
+  let api = api_client.current_provider()?;
+  let provider_id = api.id().to_owned();
+  let index = api.release_index().await.context("Bundle export needs the release index")?;
+  let entry = index
+    .releases
+    .iter()
+    .find(|r| r.name == version_name || r.path == version_name)
+    .ok_or_else(|| anyhow!("Release '{}' not found in the index", version_name))?
+    .clone();
+
+  let root = if is_container(dest) { staging_dir(dest) } else { dest.to_path_buf() };
+  let release_dir = root.join(RELEASE_DIR);
+  std::fs::create_dir_all(&release_dir).with_context(|| format!("Cannot create {:?}", release_dir))?;
+
+  // Release manifest: the published one has exe_path etc.; the index fields are the fallback.
+  let manifest = match download_manifest(api_client, &entry.manifest).await {
+    Ok(m) => m,
+    Err(e) => {
+      log::warn!("Bundle: release manifest unavailable ({}), using index fields", e);
+      manifest_from_index_entry(&entry).unwrap_or_default()
+    }
+  };
+  write_json(&release_dir.join(MANIFEST_NAME), &manifest)?;
+
+  for (i, asset) in entry.assets.iter().enumerate() {
+    log(format!("Release: {} ({}/{})", &asset.name, i + 1, entry.assets.len()));
+    fetch_asset(api_client, files, &provider_id, version_name, asset, &release_dir, cancel_tx).await?;
+  }
+
+  let mut patches: Vec<BundlePatch> = vec![];
+  for patch in patch_chain(&entry.patches) {
+    crate::utils::paths::safe_download_join(&root, &patch.tag).map_err(|e| anyhow!(e))?;
+    let patch_dir = root.join(PATCHES_DIR).join(&patch.tag);
+    std::fs::create_dir_all(&patch_dir).with_context(|| format!("Cannot create {:?}", patch_dir))?;
+
+    let manifest = match &patch.manifest {
+      Some(url) => download_manifest(api_client, url).await?,
+      None => ReleaseManifest {
+        patch_name: Some(patch.tag.clone()),
+        base_patch: patch.base_patch.clone(),
+        ..ReleaseManifest::default()
+      },
+    };
+    write_json(&patch_dir.join(MANIFEST_NAME), &manifest)?;
+
+    // The full copies of delta files too: the target install is not known here.
+    for asset in patch.assets.iter().filter(|a| a.name != MANIFEST_NAME && is_patch_archive(&a.name)) {
+      log(format!("Patch {}: {}", &patch.tag, &asset.name));
+      fetch_asset(api_client, files, &provider_id, version_name, asset, &patch_dir, cancel_tx).await?;
+    }
+
+    patches.push(BundlePatch {
+      tag: patch.tag.clone(),
+      base_patch: patch.base_patch.clone(),
+      notes: patch.notes.clone(),
+    });
+  }
+
+  write_json(
+    &root.join(BUNDLE_INFO_NAME),
+    &BundleInfo {
+      schema: BUNDLE_SCHEMA_VERSION,
+      created_at: chrono::Local::now().to_rfc3339(),
+      provider_id,
+      release: entry,
+      patches,
+    },
+  )?;
+
+  if is_container(dest) {
+    log("Packing bundle ...".to_string());
+    let (from, to) = (root.clone(), dest.to_path_buf());
+    tokio::task::spawn_blocking(move || pack_container(&from, &to)).await??;
+    std::fs::remove_dir_all(&root).with_context(|| format!("Cannot remove {:?}", root))?;
+  }
+
+  log::info!("Bundle of '{}' written to {:?}", version_name, dest);
+  Ok(())
+}
+
+/// Stores the bundle tree in a zip without recompressing (the archives are compressed already).
+fn pack_container(root: &Path, zip_path: &Path) -> Result<()> {
+  let file = std::fs::File::create(zip_path).with_context(|| format!("Cannot create {:?}", zip_path))?;
+  let mut zip = ZipWriter::new(BufWriter::new(file));
+  let options: FileOptions<'_, ()> = FileOptions::default().compression_method(CompressionMethod::Stored).large_file(true);
+
+  for entry in walkdir::WalkDir::new(root)
+    .into_iter()
+    .filter_map(|e| e.ok())
+    .filter(|e| e.file_type().is_file())
+  {
+    let name = entry.path().strip_prefix(root)?.to_string_lossy().replace('\\', "/");
+    zip.start_file(name, options)?;
+    let mut reader = BufReader::new(std::fs::File::open(entry.path())?);
+    std::io::copy(&mut reader, &mut zip)?;
+  }
+
+  zip.finish()?;
+  Ok(())
+}
+
+async fn check_archive(path: &Path, expected: Option<&str>) -> Result<()> {
+  let Some(expected) = expected else {
+    return Ok(());
+  };
+  let owned = path.to_path_buf();
+  let actual = tokio::task::spawn_blocking(move || sha256_file(&owned)).await??;
+  if !actual.eq_ignore_ascii_case(expected) {
+    bail!("Bundle archive {:?} is corrupted (sha256 mismatch)", path);
+  }
+  Ok(())
+}
+
+async fn unpack_archive(unpack: &Arc<ServiceUnpacker>, version_name: &str, archive: &Path, extract_to: &Path) -> Result<()> {
+  let (svc, vn, archive, extract_to) = (unpack.clone(), version_name.to_owned(), archive.to_path_buf(), extract_to.to_path_buf());
+  let name = archive.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+  tokio::task::spawn_blocking(move || svc.extract_zip(&vn, &name, &archive, &extract_to))
+    .await?
+    .map_err(|e| anyhow!("Extract failed: {}", e))
+}
+
+/// Reads `bundle.json` straight from a container, so the unpack is reported
+/// under the real version name.
+fn read_container_info(bundle: &Path) -> Result<BundleInfo> {
+  let mut archive = ZipArchive::new(std::fs::File::open(bundle).with_context(|| format!("Cannot open {:?}", bundle))?)?;
+  let file = archive
+    .by_name(BUNDLE_INFO_NAME)
+    .with_context(|| format!("{:?} has no {}", bundle, BUNDLE_INFO_NAME))?;
+  serde_json::from_reader(BufReader::new(file)).with_context(|| format!("Cannot parse {} in {:?}", BUNDLE_INFO_NAME, bundle))
+}
+
+/// Undoes the patches of a failed import, newest first, from the backups
+/// `apply_patch` took. The unpacked release itself stays in place.
+fn roll_back_patches(install_path: &Path, tags: &[String]) -> Result<()> {
+  let installed = read_installed_patches(install_path);
+  for tag in tags.iter().rev() {
+    let backup = installed
+      .iter()
+      .find(|p| &p.name == tag)
+      .and_then(|p| p.backup.clone())
+      .ok_or_else(|| anyhow!("Patch '{}' has no backup to roll back", tag))?;
+    patch_backup::restore(install_path, tag, &backup)?;
+    remove_patch_marker(install_path, tag)?;
+  }
+  Ok(())
+}
+
+/// Installs a bundle (directory or `.zip` container) into
+/// `<install_root>/<release path>`, the same place an online install of the
+/// release goes, and returns the version name.
+pub async fn import_bundle(
+  app: &tauri::AppHandle,
+  config: &Arc<Mutex<AppConfig>>,
+  unpack: &Arc<ServiceUnpacker>,
+  bundle: &Path,
+  install_root: &Path,
+  log: impl Fn(String),
+) -> Result<String> {
+  std::fs::create_dir_all(install_root).with_context(|| format!("Cannot create {:?}", install_root))?;
+
+  // A container is unpacked next to the games and removed afterwards.
+  let root = if bundle.is_file() {
+    let mut plan = SpacePlan::default();
+    plan.add(install_root, std::fs::metadata(bundle)?.len())?;
+    plan.ensure().map_err(|e| anyhow!(e))?;
+
+    let info = read_container_info(bundle)?;
+    let root = staging_dir(&install_root.join(".bundle"));
+    log("Unpacking bundle ...".to_string());
+    unpack_archive(unpack, &info.release.name, bundle, &root).await?;
+    root
+  } else {
+    bundle.to_path_buf()
+  };
+
+  let result = install_from_dir(app, config, unpack, &root, install_root, &log).await;
+
+  if root != bundle {
+    if let Err(e) = std::fs::remove_dir_all(&root) {
+      log::warn!("Cannot remove bundle staging dir {:?}: {}", root, e);
+    }
+  }
+  result
+}
+
+async fn install_from_dir(
+  app: &tauri::AppHandle,
+  config: &Arc<Mutex<AppConfig>>,
+  unpack: &Arc<ServiceUnpacker>,
+  root: &Path,
+  install_root: &Path,
+  log: &impl Fn(String),
+) -> Result<String> {
+  let info: BundleInfo = read_json(&root.join(BUNDLE_INFO_NAME))?;
+  if info.schema != BUNDLE_SCHEMA_VERSION {
+    bail!("Bundle schema {} is not supported (expected {})", info.schema, BUNDLE_SCHEMA_VERSION);
+  }
+  let entry = &info.release;
+  let install_path = crate::utils::paths::safe_download_join(install_root, &entry.path).map_err(|e| anyhow!(e))?;
+  let install_path = install_path.as_path();
+
+  {
+    let cfg = config.lock().await;
+    if cfg.installed_versions.values().any(|v| v.name == entry.name) {
+      bail!("Version '{}' is already installed", entry.name);
+    }
+  }
+
+  // Release: every archive is checked before anything is unpacked.
+  let release_dir = root.join(RELEASE_DIR);
+  let manifest: ReleaseManifest = read_json(&release_dir.join(MANIFEST_NAME))?;
+  let mut archives: Vec<PathBuf> = vec![];
+  for asset in &entry.assets {
+    let path = crate::utils::paths::safe_download_join(&release_dir, &asset.name).map_err(|e| anyhow!(e))?;
+    let expected = manifest
+      .files
+      .iter()
+      .find(|f| f.name == asset.name)
+      .and_then(|f| f.sha256.as_deref())
+      .or(asset.sha256.as_deref());
+    check_archive(&path, expected).await?;
+    archives.push(path);
+  }
+
+  // The patches too, so a broken chain or patch archive fails the import before the release is unpacked.
+  let mut patches: Vec<(&BundlePatch, ReleaseManifest, Vec<PathBuf>)> = vec![];
+  let mut last_tag: Option<&str> = None;
+  for patch in &info.patches {
+    if patch.base_patch.as_deref().unwrap_or("") != last_tag.unwrap_or("") {
+      bail!(
+        "Patch chain mismatch: patch '{}' expects base '{:?}', the previous patch is '{:?}'",
+        patch.tag,
+        patch.base_patch,
+        last_tag
+      );
+    }
+    last_tag = Some(&patch.tag);
+
+    let patch_dir = root.join(PATCHES_DIR).join(&patch.tag);
+    let manifest: ReleaseManifest = read_json(&patch_dir.join(MANIFEST_NAME))?;
+
+    let mut patch_archives: Vec<PathBuf> = vec![];
+    for file in std::fs::read_dir(&patch_dir)?.flatten() {
+      let name = file.file_name().to_string_lossy().into_owned();
+      if name != MANIFEST_NAME && is_patch_archive(&name) {
+        let expected = manifest.files.iter().find(|f| f.name == name).and_then(|f| f.sha256.as_deref());
+        check_archive(&file.path(), expected).await?;
+        patch_archives.push(file.path());
+      }
+    }
+    patch_archives.sort_by_key(|path| archive_order(path));
+    patches.push((patch, manifest, patch_archives));
+  }
+
+  let sizes = |paths: &[PathBuf]| -> u64 { paths.iter().filter_map(|p| std::fs::metadata(p).ok()).map(|m| m.len()).sum() };
+  let patch_sizes: Vec<(u64, &ReleaseManifest)> = patches.iter().map(|(_, manifest, paths)| (sizes(paths), manifest)).collect();
+  bundle_plan(install_path, sizes(&archives), &manifest, &patch_sizes)?
+    .ensure()
+    .map_err(|e| anyhow!(e))?;
+
+  // Same transaction as an online install: nothing lands in the install dir unless every archive unpacked.
+  let staged = staging::staging_path(install_path);
+  for (i, archive) in archives.iter().enumerate() {
+    log(format!("Installing {} ({}/{})", archive.display(), i + 1, archives.len()));
+    if let Err(e) = unpack_archive(unpack, &entry.name, archive, &staged).await {
+      let _ = staging::discard(install_path);
+      return Err(e);
+    }
+  }
+  let committed = install_path.to_path_buf();
+  if let Err(e) = tokio::task::spawn_blocking(move || staging::commit(&committed)).await? {
+    let _ = staging::discard(install_path);
+    return Err(e);
+  }
+
+  let mut applied_tags: Vec<String> = vec![];
+  for (patch, patch_manifest, patch_archives) in &patches {
+    log(format!("Applying patch {}", &patch.tag));
+    let applied = apply_patch(
+      app,
+      unpack,
+      install_path,
+      &entry.name,
+      &patch.tag,
+      patch_archives,
+      patch_manifest,
+      InstalledPatch {
+        name: patch.tag.clone(),
+        provider_id: info.provider_id.clone(),
+        installed_at: Some(chrono::Local::now().to_rfc3339()),
+        notes: patch.notes.clone(),
+        backup: None,
+        supersedes: vec![],
+      },
+      None,
+      50.0..100.0,
+    )
+    .await;
+    // `apply_patch` already restored the failed patch; undo the earlier ones so the
+    // unregistered install is back to the plain release and the import can be retried.
+    if let Err(e) = applied {
+      let (path, tags) = (install_path.to_path_buf(), applied_tags.clone());
+      match tokio::task::spawn_blocking(move || roll_back_patches(&path, &tags)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(rb)) => log::error!("Cannot roll back the patches of {:?}: {:#}", install_path, rb),
+        Err(rb) => log::error!("Patch rollback task of {:?} failed: {}", install_path, rb),
+      }
+      return Err(e);
+    }
+    applied_tags.push(patch.tag.clone());
+  }
+
+  // Registered exactly like `add_installed_version_from_config` does after an online download.
+  {
+    let mut cfg = config.lock().await;
+    let id = cfg.versions.iter().find(|v| v.name == entry.name).map(|v| v.id).unwrap_or(0);
+    cfg.installed_versions.insert(
+      entry.path.clone(),
+      Version {
+        id,
+        name: entry.name.clone(),
+        path: entry.path.clone(),
+        exe_path: manifest.exe_path.clone().or(entry.exe_path.clone()),
+        // The per-file list is only needed by verify/repair, which fetch it again.
+        manifest: Some(ReleaseManifest { entries: vec![], ..manifest }),
+        installed_path: install_path.to_string_lossy().into_owned(),
+        download_path: String::new(),
+        engine_path: None,
+        fsgame_path: None,
+        userltx_path: None,
+        installed_updates: vec![],
+        is_local: false,
+      },
+    );
+    cfg.save()?;
+  }
+
+  log::info!("Bundle of '{}' installed to {:?}", entry.name, install_path);
+  Ok(entry.name.clone())
+}
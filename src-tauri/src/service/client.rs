@@ -1,7 +1,8 @@
 use std::time::Duration;
 
 use crate::{
-  consts::{REPO_LAUNCGER_ID, USER_CACHE_TTL_POSITIVE_SECS, USER_CACHE_TTL_NEGATIVE_SECS},
+  configs::DistributionProfile::profile,
+  consts::{USER_CACHE_TTL_POSITIVE_SECS, USER_CACHE_TTL_NEGATIVE_SECS},
   service::{dto::{UserData, UserDataCache}, main::Service},
 };
 use anyhow::Result;
@@ -39,7 +40,7 @@ impl ServiceClient for Service {
       }
     };
 
-    let issues = match api.find_user(&REPO_LAUNCGER_ID.to_string(), &uuid).await {
+    let issues = match api.find_user(&profile().gitlab.launcher_project_id.to_string(), &uuid).await {
       Ok(data) => data,
       Err(error) => {
         log::warn!("get_user: find_user failed, returning default UserData. Error: {:?}", error);
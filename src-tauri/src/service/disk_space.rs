@@ -0,0 +1,339 @@
+// Disk-space planner for downloads and installs.
+//
+// Computes the peak space a release download or a patch install still needs
+// on every volume it touches. Archives and the files extracted from them
+// coexist until the archive is removed after unpacking, so when the download
+// and install paths share a volume both amounts add up there.
+
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::{configs::AppConfig::FileProgress, consts::DISK_SPACE_RESERVE, handlers::dto::ReleaseManifest};
+
+/// Error prefix of a refused start; the JSON `SpacePlan` follows it.
+pub const NOT_ENOUGH_SPACE: &str = "NOT_ENOUGH_SPACE";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VolumeNeed {
+  /// Planned paths on this volume, in the order they were added.
+  pub paths: Vec<String>,
+  /// Bytes still to be written, reserve included.
+  pub needed: u64,
+  pub available: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SpacePlan {
+  pub volumes: Vec<VolumeNeed>,
+  #[serde(skip)]
+  keys: Vec<String>,
+}
+
+impl SpacePlan {
+  /// Adds `bytes` to the volume holding `path`.
+  pub fn add(&mut self, path: &Path, bytes: u64) -> Result<()> {
+    let existing = existing_ancestor(path)?;
+    let key = volume_key(&existing)?;
+
+    match self.keys.iter().position(|k| *k == key) {
+      Some(i) => {
+        self.volumes[i].needed += bytes;
+        self.volumes[i].paths.push(path.to_string_lossy().into_owned());
+      }
+      None => {
+        let available = fs4::available_space(&existing).with_context(|| format!("Cannot read free space of {:?}", existing))?;
+        self.keys.push(key);
+        self.volumes.push(VolumeNeed {
+          paths: vec![path.to_string_lossy().into_owned()],
+          needed: bytes + DISK_SPACE_RESERVE,
+          available,
+        });
+      }
+    }
+    Ok(())
+  }
+
+  pub fn is_enough(&self) -> bool {
+    self.volumes.iter().all(|v| v.available >= v.needed)
+  }
+
+  /// Err(`NOT_ENOUGH_SPACE:<plan json>`) when a volume is short.
+  pub fn ensure(&self) -> Result<(), String> {
+    if self.is_enough() {
+      return Ok(());
+    }
+    log::warn!("Not enough disk space: {:?}", &self.volumes);
+    Err(format!("{}:{}", NOT_ENOUGH_SPACE, serde_json::to_string(self).unwrap_or_default()))
+  }
+}
+
+/// The path itself or its nearest existing parent (install dirs are created lazily).
+fn existing_ancestor(path: &Path) -> Result<PathBuf> {
+  let mut current = Some(path);
+  while let Some(p) = current {
+    if p.exists() {
+      return Ok(p.canonicalize().unwrap_or_else(|_| p.to_path_buf()));
+    }
+    current = p.parent();
+  }
+  std::env::current_dir().context("Cannot resolve current dir")
+}
+
+#[cfg(unix)]
+fn volume_key(existing: &Path) -> Result<String> {
+  use std::os::unix::fs::MetadataExt;
+  Ok(std::fs::metadata(existing)?.dev().to_string())
+}
+
+#[cfg(not(unix))]
+fn volume_key(existing: &Path) -> Result<String> {
+  // The drive prefix (`C:`, `\\server\share`); paths are canonical here.
+  Ok(
+    existing
+      .components()
+      .next()
+      .map(|c| c.as_os_str().to_string_lossy().to_uppercase())
+      .unwrap_or_default(),
+  )
+}
+
+/// Space a release download still needs: the archive bytes not downloaded yet
+/// on the download volume, the files of the archives not unpacked yet on the
+/// install volume. Works for a fresh download and for a resume.
+pub fn release_plan(
+  download_path: &Path,
+  install_path: &Path,
+  manifest: Option<&ReleaseManifest>,
+  files: &HashMap<String, FileProgress>,
+) -> Result<SpacePlan> {
+  let compressed: u64 = files.values().map(|f| f.total_size).sum();
+  let to_download: u64 = files
+    .values()
+    .filter(|f| !f.is_downloaded)
+    .map(|f| f.total_size.saturating_sub(f.size))
+    .sum();
+  let to_unpack: u64 = files.values().filter(|f| !f.is_unpacked).map(|f| f.total_size).sum();
+
+  plan_release(
+    download_path,
+    install_path,
+    manifest.map(|m| m.total_size),
+    compressed,
+    to_download,
+    to_unpack,
+  )
+}
+
+/// `release_plan` for a download that has not been prepared yet: every
+/// archive of `manifest` is still to be downloaded and unpacked.
+pub fn fresh_release_plan(download_path: &Path, install_path: &Path, manifest: &ReleaseManifest) -> Result<SpacePlan> {
+  let compressed: u64 = manifest.files.iter().map(|f| f.size).sum();
+  plan_release(download_path, install_path, Some(manifest.total_size), compressed, compressed, compressed)
+}
+
+fn plan_release(
+  download_path: &Path,
+  install_path: &Path,
+  total_size: Option<u64>,
+  compressed: u64,
+  to_download: u64,
+  to_unpack: u64,
+) -> Result<SpacePlan> {
+  // Extracted size of the pending archives, pro rata; 1:1 when the manifest has no total.
+  let extracted = match total_size.filter(|t| *t > 0) {
+    Some(total) if compressed > 0 => (total as f64 * (to_unpack as f64 / compressed as f64)) as u64,
+    _ => to_unpack,
+  };
+
+  let mut plan = SpacePlan::default();
+  plan.add(download_path, to_download)?;
+  plan.add(install_path, extracted)?;
+  Ok(plan)
+}
+
+/// Space a patch install needs: archives staged in `<install>/.patches/` plus
+/// the files extracted from them, all on the install volume.
+pub fn patch_plan(installed_path: &Path, archives_size: u64, manifest: &ReleaseManifest) -> Result<SpacePlan> {
+  patch_chain_plan(installed_path, &[(archives_size, manifest)])
+}
+
+/// Space an offline bundle import needs: the release extracted plus the
+/// largest patch extracted on top of it, all on the install volume (the
+/// archives are already on disk).
+pub fn bundle_plan(install_path: &Path, release_archives: u64, release: &ReleaseManifest, patches: &[(u64, &ReleaseManifest)]) -> Result<SpacePlan> {
+  let extracted = |size: u64, manifest: &ReleaseManifest| if manifest.total_size > 0 { manifest.total_size } else { size };
+  let patch = patches.iter().map(|(size, manifest)| extracted(*size, manifest)).max().unwrap_or(0);
+
+  let mut plan = SpacePlan::default();
+  plan.add(install_path, extracted(release_archives, release) + patch)?;
+  Ok(plan)
+}
+
+/// Space a chain of patches needs: every archive is downloaded before the
+/// first one is applied, the patches are then extracted one at a time.
+pub fn patch_chain_plan(installed_path: &Path, patches: &[(u64, &ReleaseManifest)]) -> Result<SpacePlan> {
+  let archives: u64 = patches.iter().map(|(size, _)| size).sum();
+  let extracted = patches
+    .iter()
+    .map(|(size, manifest)| if manifest.total_size > 0 { manifest.total_size } else { *size })
+    .max()
+    .unwrap_or(0);
+
+  let mut plan = SpacePlan::default();
+  plan.add(&installed_path.join(".patches"), archives + extracted)?;
+  Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use uuid::Uuid;
+
+  struct TempDir(PathBuf);
+
+  impl TempDir {
+    fn new() -> Self {
+      let dir = std::env::temp_dir().join(format!("gw-space-test-{}", Uuid::new_v4()));
+      std::fs::create_dir_all(&dir).unwrap();
+      Self(dir)
+    }
+  }
+
+  impl Drop for TempDir {
+    fn drop(&mut self) {
+      let _ = std::fs::remove_dir_all(&self.0);
+    }
+  }
+
+  fn manifest(total_size: u64, archives: &[u64]) -> ReleaseManifest {
+    ReleaseManifest {
+      total_size,
+      files: archives
+        .iter()
+        .enumerate()
+        .map(|(i, size)| crate::handlers::dto::ReleaseManifestFile {
+          name: format!("data{}.zip", i),
+          size: *size,
+          sha256: None,
+        })
+        .collect(),
+      ..Default::default()
+    }
+  }
+
+  fn progress(name: &str, size: u64, total_size: u64, is_downloaded: bool, is_unpacked: bool) -> (String, FileProgress) {
+    let file = FileProgress {
+      id: name.to_owned(),
+      download_link: String::new(),
+      name: name.to_owned(),
+      is_downloaded,
+      is_unpacked,
+      size,
+      total_size,
+      sha256: None,
+    };
+    (name.to_owned(), file)
+  }
+
+  /// The only volume of a plan whose paths all live in one temp dir.
+  fn single_volume(plan: &SpacePlan) -> &VolumeNeed {
+    assert_eq!(plan.volumes.len(), 1, "{:?}", plan.volumes);
+    &plan.volumes[0]
+  }
+
+  #[test]
+  fn download_and_install_on_one_volume_add_up() {
+    let tmp = TempDir::new();
+    let plan = plan_release(&tmp.0.join("downloads"), &tmp.0.join("game"), Some(200), 100, 40, 50).unwrap();
+
+    let volume = single_volume(&plan);
+    // 40 bytes still to download + half of the archives unpacked to half of 200 bytes.
+    assert_eq!(volume.needed, 40 + 100 + DISK_SPACE_RESERVE);
+    assert_eq!(volume.paths.len(), 2);
+  }
+
+  #[test]
+  fn extracted_size_is_one_to_one_without_a_total() {
+    let tmp = TempDir::new();
+    for total in [None, Some(0)] {
+      let plan = plan_release(&tmp.0, &tmp.0, total, 100, 0, 30).unwrap();
+      assert_eq!(single_volume(&plan).needed, 30 + DISK_SPACE_RESERVE);
+    }
+  }
+
+  #[test]
+  fn release_plan_counts_only_what_is_left() {
+    let tmp = TempDir::new();
+    let files: HashMap<String, FileProgress> = [
+      progress("data0.zip", 100, 100, true, true),
+      progress("data1.zip", 100, 100, true, false),
+      progress("data2.zip", 30, 200, false, false),
+    ]
+    .into_iter()
+    .collect();
+
+    let plan = release_plan(&tmp.0, &tmp.0.join("game"), Some(&manifest(600, &[100, 100, 200])), &files).unwrap();
+    // 170 bytes of data2 to download, 3/4 of the archives (450 of 600 bytes) to unpack.
+    assert_eq!(single_volume(&plan).needed, 170 + 450 + DISK_SPACE_RESERVE);
+  }
+
+  #[test]
+  fn fresh_release_plan_needs_every_archive_and_the_total() {
+    let tmp = TempDir::new();
+    let plan = fresh_release_plan(&tmp.0, &tmp.0, &manifest(500, &[100, 150])).unwrap();
+    assert_eq!(single_volume(&plan).needed, 250 + 500 + DISK_SPACE_RESERVE);
+  }
+
+  #[test]
+  fn missing_dirs_are_planned_on_their_existing_parent() {
+    let tmp = TempDir::new();
+    let mut plan = SpacePlan::default();
+    plan.add(&tmp.0.join("not/created/yet"), 10).unwrap();
+    plan.add(&tmp.0, 5).unwrap();
+
+    assert_eq!(single_volume(&plan).needed, 15 + DISK_SPACE_RESERVE);
+  }
+
+  #[test]
+  fn patch_chain_downloads_all_archives_and_extracts_the_largest() {
+    let tmp = TempDir::new();
+    let (a, b) = (manifest(300, &[]), manifest(0, &[]));
+    let plan = patch_chain_plan(&tmp.0, &[(100, &a), (400, &b)]).unwrap();
+
+    // b has no total: its archive size stands in for the extracted size.
+    let volume = single_volume(&plan);
+    assert_eq!(volume.needed, 500 + 400 + DISK_SPACE_RESERVE);
+    assert!(volume.paths[0].ends_with(".patches"));
+  }
+
+  #[test]
+  fn bundle_plan_adds_the_largest_patch_to_the_release() {
+    let tmp = TempDir::new();
+    let (release, small, large) = (manifest(1000, &[]), manifest(50, &[]), manifest(0, &[]));
+    let plan = bundle_plan(&tmp.0, 700, &release, &[(20, &small), (80, &large)]).unwrap();
+    assert_eq!(single_volume(&plan).needed, 1000 + 80 + DISK_SPACE_RESERVE);
+  }
+
+  #[test]
+  fn ensure_reports_the_short_volume() {
+    let mut plan = SpacePlan::default();
+    plan.volumes.push(VolumeNeed {
+      paths: vec!["/games".to_owned()],
+      needed: 10,
+      available: 10,
+    });
+    assert!(plan.ensure().is_ok());
+
+    plan.volumes[0].needed = 11;
+    let err = plan.ensure().unwrap_err();
+    let json = err.strip_prefix(&format!("{}:", NOT_ENOUGH_SPACE)).unwrap();
+    let value: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(value["volumes"][0]["needed"], 11);
+    assert!(value.get("keys").is_none());
+  }
+}
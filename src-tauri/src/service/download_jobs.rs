@@ -0,0 +1,404 @@
+// Download job manager.
+//
+// Owns the download queue: full releases and patch installs, in priority
+// order, persisted as `AppConfig.download_jobs`. Releases and patches have
+// their own slots (`MAX_ACTIVE_DOWNLOAD_JOBS`, `MAX_ACTIVE_PATCH_JOBS`), two
+// jobs of the same version never run together. Jobs can be paused, resumed,
+// cancelled and reordered. A job that was queued or running when the launcher quit is
+// queued again on the next start. The download commands are thin wrappers:
+// they submit a job and wait for its result.
+
+use std::{
+  collections::{HashMap, HashSet},
+  sync::{
+    Arc, Mutex as StdMutex,
+    atomic::{AtomicBool, Ordering},
+  },
+  time::Duration,
+};
+
+use tauri::Emitter;
+use tokio::sync::{Mutex, Notify, broadcast, oneshot};
+
+use crate::{
+  configs::AppConfig::{AppConfig, DownloadJob, DownloadJobKind, DownloadJobState},
+  consts::{MAX_ACTIVE_DOWNLOAD_JOBS, MAX_ACTIVE_PATCH_JOBS},
+  service::{download_pipeline::run_release_download, files::ServiceFiles, main::Service, unpack::ServiceUnpacker},
+};
+
+/// Result a waiting command gets when its job is paused or cancelled.
+pub const USER_CANCELLED: &str = "USER_CANCELLED";
+
+/// Everything a job needs to run outside of a Tauri command.
+#[derive(Clone)]
+pub struct DownloadContext {
+  pub app: tauri::AppHandle,
+  pub config: Arc<Mutex<AppConfig>>,
+  pub service: Arc<Mutex<Service>>,
+  pub files: Arc<ServiceFiles>,
+  pub unpack: Arc<ServiceUnpacker>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StopReason {
+  Pause,
+  Cancel,
+  /// Launcher exit: the job stays queued and is restored on the next start.
+  Shutdown,
+}
+
+struct RunningJob {
+  cancel_tx: broadcast::Sender<()>,
+  stop: Option<StopReason>,
+}
+
+impl DownloadJob {
+  pub fn release(version_name: &str) -> Self {
+    Self {
+      id: version_name.to_owned(),
+      version_name: version_name.to_owned(),
+      kind: DownloadJobKind::Release,
+      state: DownloadJobState::Queued,
+      error: None,
+    }
+  }
+
+  pub fn patch(version_name: &str, patch_name: &str) -> Self {
+    Self {
+      id: format!("patch:{}:{}", version_name, patch_name),
+      version_name: version_name.to_owned(),
+      kind: DownloadJobKind::Patch {
+        patch_name: patch_name.to_owned(),
+      },
+      state: DownloadJobState::Queued,
+      error: None,
+    }
+  }
+
+  pub fn patch_chain(version_name: &str, patches: &[String]) -> Self {
+    Self {
+      id: format!("patch-chain:{}", version_name),
+      version_name: version_name.to_owned(),
+      kind: DownloadJobKind::PatchChain { patches: patches.to_vec() },
+      state: DownloadJobState::Queued,
+      error: None,
+    }
+  }
+}
+
+pub struct DownloadJobs {
+  ctx: DownloadContext,
+  jobs: StdMutex<Vec<DownloadJob>>,
+  running: StdMutex<HashMap<String, RunningJob>>,
+  waiters: StdMutex<HashMap<String, Vec<oneshot::Sender<Result<(), String>>>>>,
+  wake: Notify,
+  started: AtomicBool,
+}
+
+impl DownloadJobs {
+  /// `saved` is the persisted queue; jobs interrupted by the last exit are queued again.
+  pub fn new(ctx: DownloadContext, saved: Vec<DownloadJob>) -> Self {
+    let jobs = saved
+      .into_iter()
+      .map(|mut job| {
+        if job.state == DownloadJobState::Running {
+          job.state = DownloadJobState::Queued;
+        }
+        job
+      })
+      .collect();
+
+    Self {
+      ctx,
+      jobs: StdMutex::new(jobs),
+      running: StdMutex::new(HashMap::new()),
+      waiters: StdMutex::new(HashMap::new()),
+      wake: Notify::new(),
+      started: AtomicBool::new(false),
+    }
+  }
+
+  pub fn context(&self) -> &DownloadContext {
+    &self.ctx
+  }
+
+  /// Starts the scheduler (once). Call after the providers are registered.
+  pub fn start(self: &Arc<Self>) {
+    if self.started.swap(true, Ordering::SeqCst) {
+      return;
+    }
+    let this = self.clone();
+    tauri::async_runtime::spawn(async move {
+      loop {
+        this.schedule().await;
+        this.wake.notified().await;
+      }
+    });
+  }
+
+  pub fn list(&self) -> Vec<DownloadJob> {
+    crate::utils::locks::lock(&self.jobs).clone()
+  }
+
+  /// True while the job is queued or running.
+  pub fn is_active(&self, id: &str) -> bool {
+    crate::utils::locks::lock(&self.jobs)
+      .iter()
+      .any(|j| j.id == id && matches!(j.state, DownloadJobState::Queued | DownloadJobState::Running))
+  }
+
+  /// Queues the job and waits until it finishes: Ok when done, `USER_CANCELLED`
+  /// when paused or cancelled, the error otherwise.
+  pub async fn run(&self, job: DownloadJob) -> Result<(), String> {
+    let (tx, rx) = oneshot::channel();
+    self.enqueue(job, tx)?;
+    self.changed().await;
+
+    rx.await.unwrap_or_else(|_| Err(USER_CANCELLED.to_string()))
+  }
+
+  /// Adds a job to the end of the queue; a paused or failed job with the same
+  /// id is queued again instead. `waiter` is registered under the queue lock,
+  /// so no pause, cancel or finish of the job can slip in before it.
+  fn enqueue(&self, job: DownloadJob, waiter: oneshot::Sender<Result<(), String>>) -> Result<(), String> {
+    let id = job.id.clone();
+    let mut jobs = crate::utils::locks::lock(&self.jobs);
+    let queued = match jobs.iter_mut().find(|j| j.id == id) {
+      Some(existing) if matches!(existing.state, DownloadJobState::Queued | DownloadJobState::Running) => {
+        Err("DOWNLOAD_ALREADY_RUNNING".to_string())
+      }
+      Some(existing) => {
+        existing.state = DownloadJobState::Queued;
+        existing.error = None;
+        Ok(())
+      }
+      None => {
+        jobs.push(job);
+        Ok(())
+      }
+    };
+    if queued.is_ok() {
+      crate::utils::locks::lock(&self.waiters).entry(id).or_default().push(waiter);
+    }
+    queued
+  }
+
+  pub async fn pause(&self, id: &str) -> Result<(), String> {
+    if self.stop_running(id, StopReason::Pause) {
+      return Ok(());
+    }
+    self.set_state(id, DownloadJobState::Paused)?;
+    self.notify_waiters(id, Err(USER_CANCELLED.to_string()));
+    self.changed().await;
+    Ok(())
+  }
+
+  pub async fn resume(&self, id: &str) -> Result<(), String> {
+    let state = self.state(id).ok_or_else(|| format!("Download job '{}' not found", id))?;
+    if matches!(state, DownloadJobState::Paused | DownloadJobState::Failed) {
+      self.set_state(id, DownloadJobState::Queued)?;
+      self.changed().await;
+    }
+    Ok(())
+  }
+
+  /// Removes the job from the queue, stopping it first when it runs. Files
+  /// already downloaded stay on disk.
+  pub async fn cancel(&self, id: &str) -> Result<(), String> {
+    if self.stop_running(id, StopReason::Cancel) {
+      return Ok(());
+    }
+    crate::utils::locks::lock(&self.jobs).retain(|j| j.id != id);
+    self.notify_waiters(id, Err(USER_CANCELLED.to_string()));
+    self.changed().await;
+    Ok(())
+  }
+
+  /// Moves a job to `position` in the queue (0 = next to run). Running jobs are not preempted.
+  pub async fn reorder(&self, id: &str, position: usize) -> Result<(), String> {
+    {
+      let mut jobs = crate::utils::locks::lock(&self.jobs);
+      let from = jobs.iter().position(|j| j.id == id).ok_or_else(|| format!("Download job '{}' not found", id))?;
+      let job = jobs.remove(from);
+      let to = position.min(jobs.len());
+      jobs.insert(to, job);
+    }
+
+    self.changed().await;
+    Ok(())
+  }
+
+  /// Stops every running job without dequeuing it, so it is restored on the
+  /// next start. Waits (bounded) for the workers to persist their progress.
+  pub async fn shutdown(&self) {
+    let ids: Vec<String> = crate::utils::locks::lock(&self.running).keys().cloned().collect();
+    for id in &ids {
+      self.stop_running(id, StopReason::Shutdown);
+    }
+
+    for _ in 0..20 {
+      if crate::utils::locks::lock(&self.running).is_empty() {
+        break;
+      }
+      tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+  }
+
+  fn state(&self, id: &str) -> Option<DownloadJobState> {
+    crate::utils::locks::lock(&self.jobs).iter().find(|j| j.id == id).map(|j| j.state)
+  }
+
+  fn set_state(&self, id: &str, state: DownloadJobState) -> Result<(), String> {
+    let mut jobs = crate::utils::locks::lock(&self.jobs);
+    let job = jobs.iter_mut().find(|j| j.id == id).ok_or_else(|| format!("Download job '{}' not found", id))?;
+    job.state = state;
+    Ok(())
+  }
+
+  /// Signals a running job to stop; false when it is not running.
+  fn stop_running(&self, id: &str, reason: StopReason) -> bool {
+    let mut running = crate::utils::locks::lock(&self.running);
+    let Some(job) = running.get_mut(id) else {
+      return false;
+    };
+    job.stop = Some(reason);
+    let _ = job.cancel_tx.send(());
+    true
+  }
+
+  fn notify_waiters(&self, id: &str, result: Result<(), String>) {
+    let waiters = crate::utils::locks::lock(&self.waiters).remove(id).unwrap_or_default();
+    for tx in waiters {
+      let _ = tx.send(result.clone());
+    }
+  }
+
+  /// Persists the queue, tells the UI and wakes the scheduler.
+  async fn changed(&self) {
+    let jobs = self.list();
+    {
+      let mut config_guard = self.ctx.config.lock().await;
+      config_guard.download_jobs = jobs.clone();
+      if let Err(e) = config_guard.save() {
+        log::error!("Cannot save download jobs: {}", e);
+      }
+    }
+
+    let _ = self.ctx.app.emit("download-jobs", &jobs);
+    self.wake.notify_one();
+  }
+
+  /// Starts queued jobs in queue order while their kind has a free slot.
+  async fn schedule(self: &Arc<Self>) {
+    let next: Vec<DownloadJob> = {
+      let jobs = crate::utils::locks::lock(&self.jobs);
+      let running = crate::utils::locks::lock(&self.running);
+      let is_patch = |j: &DownloadJob| !matches!(j.kind, DownloadJobKind::Release);
+
+      let active: Vec<&DownloadJob> = jobs.iter().filter(|j| running.contains_key(&j.id)).collect();
+      let mut busy_versions: HashSet<&str> = active.iter().map(|j| j.version_name.as_str()).collect();
+      let running_patches = active.iter().filter(|j| is_patch(j)).count();
+      let mut free_patches = MAX_ACTIVE_PATCH_JOBS.saturating_sub(running_patches);
+      let mut free_releases = MAX_ACTIVE_DOWNLOAD_JOBS.saturating_sub(active.len() - running_patches);
+
+      let mut next = vec![];
+      let queued = jobs.iter().filter(|j| j.state == DownloadJobState::Queued && !running.contains_key(&j.id));
+      for job in queued {
+        let free = if is_patch(job) { &mut free_patches } else { &mut free_releases };
+        if *free == 0 || !busy_versions.insert(job.version_name.as_str()) {
+          continue;
+        }
+        *free -= 1;
+        next.push(job.clone());
+      }
+      next
+    };
+
+    for job in next {
+      self.launch(job).await;
+    }
+  }
+
+  async fn launch(self: &Arc<Self>, job: DownloadJob) {
+    let (cancel_tx, _) = broadcast::channel::<()>(1);
+    crate::utils::locks::lock(&self.running).insert(
+      job.id.clone(),
+      RunningJob {
+        cancel_tx: cancel_tx.clone(),
+        stop: None,
+      },
+    );
+    let _ = self.set_state(&job.id, DownloadJobState::Running);
+    self.changed().await;
+
+    log::info!("Download job '{}' started", &job.id);
+    let this = self.clone();
+    tauri::async_runtime::spawn(async move {
+      let scope_tx = cancel_tx.clone();
+      let run = async {
+        match &job.kind {
+          DownloadJobKind::Release => run_release_download(&this.ctx, &job.version_name, cancel_tx).await,
+          DownloadJobKind::Patch { patch_name } => {
+            crate::handlers::patch_install::install_patch(&this.ctx, &cancel_tx, &job.version_name, patch_name)
+              .await
+              .map_err(|e| {
+                crate::utils::errors::log_full_error(&e);
+                e.to_string()
+              })
+          }
+          DownloadJobKind::PatchChain { patches } => {
+            crate::handlers::patch_install::install_patch_chain(&this.ctx, &cancel_tx, &job.version_name, patches)
+              .await
+              .map_err(|e| {
+                crate::utils::errors::log_full_error(&e);
+                e.to_string()
+              })
+          }
+        }
+      };
+      let result = crate::utils::background::scope(&scope_tx, run).await;
+      this.finish(&job.id, result).await;
+    });
+  }
+
+  async fn finish(&self, id: &str, result: Result<(), String>) {
+    let stop = crate::utils::locks::lock(&self.running).remove(id).and_then(|r| r.stop);
+    log::info!("Download job '{}' finished: {:?} (stop: {:?})", id, &result, stop);
+
+    {
+      let mut jobs = crate::utils::locks::lock(&self.jobs);
+      match (stop, &result) {
+        // Finished before the stop signal landed: nothing left to resume.
+        (_, Ok(_)) | (Some(StopReason::Cancel), _) => jobs.retain(|j| j.id != id),
+        (Some(StopReason::Shutdown), _) => {
+          if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+            job.state = DownloadJobState::Queued;
+          }
+        }
+        (Some(StopReason::Pause), _) => {
+          if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+            job.state = DownloadJobState::Paused;
+          }
+        }
+        (None, Err(e)) => {
+          if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+            // Gave up on a file: keep the progress, the user resumes it later.
+            if e.contains(USER_CANCELLED) {
+              job.state = DownloadJobState::Paused;
+            } else {
+              job.state = DownloadJobState::Failed;
+              job.error = Some(e.clone());
+            }
+          }
+        }
+      }
+    }
+
+    let result = match (stop, result) {
+      (Some(_), Err(_)) => Err(USER_CANCELLED.to_string()),
+      (_, result) => result,
+    };
+    self.notify_waiters(id, result);
+    self.changed().await;
+  }
+}
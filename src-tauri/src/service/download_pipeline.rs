@@ -0,0 +1,658 @@
+// Release download pipeline run by the download job manager.
+//
+// A job only needs the `progress_download` entry of its version: a fresh
+// download is prepared once (`prepare_release_download`), after which every
+// run — first start, resume after a pause, or a job restored after a restart —
+// goes through `run_release_download`, which downloads whatever is still
+// missing and unpacks archives as they complete.
+
+use std::{
+  cmp::Reverse,
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  sync::{
+    Arc,
+    atomic::{AtomicU32, Ordering},
+  },
+  time::Duration,
+};
+
+use anyhow::Context;
+use tauri::Emitter;
+use tokio::{
+  sync::{Mutex, Semaphore, broadcast, mpsc},
+  task::JoinSet,
+};
+
+use crate::{
+  configs::AppConfig::{AppConfig, FileProgress, VersionProgress},
+  handlers::dto::{DownlaodFileStat, DownloadFileSource, DownloadProgress, DownloadStatus, UnzipTask},
+  providers::ApiClient::ApiClient::ApiClient,
+  service::{
+    disk_space::release_plan,
+    download_jobs::DownloadContext,
+    failover::{DownloadSource, next_source},
+    files::DownloadOutcome,
+    get_release::{main_release, release_manifest},
+    part_file,
+    retry::RetryCounter,
+    staging,
+  },
+  utils::errors::log_full_error,
+};
+
+/// Creates the `progress_download` entry of a fresh download: the release
+/// assets with their expected hashes, nothing downloaded yet. Returns the
+/// version name, which is also the job id.
+pub async fn prepare_release_download(
+  ctx: &DownloadContext,
+  download_path: &str,
+  install_path: &str,
+  version_name: &str,
+  version_id: Option<u32>,
+) -> Result<String, String> {
+  let app = &ctx.app;
+  let cfg = ctx.config.lock().await.clone();
+
+  let selected_version = cfg
+    .versions
+    .iter()
+    .find(|v| v.name == version_name || version_id.is_some_and(|id| v.id == id))
+    .ok_or_else(|| anyhow::anyhow!("Version not found, versionName: {:?} versionId: {:?}", version_name, version_id))
+    .map_err(|e| {
+      log_full_error(&e);
+      e.to_string()
+    })?;
+
+  log::info!("prepare_release_download, selected_version: {:?}", &selected_version);
+
+  let mut version = VersionProgress {
+    id: selected_version.id,
+    name: selected_version.name.clone(),
+    path: selected_version.path.clone(),
+    installed_path: install_path.to_owned(),
+    download_path: download_path.to_owned(),
+    is_downloaded: false,
+    files: HashMap::new(),
+    downloaded_files_cnt: 0,
+    total_file_count: selected_version.manifest.as_ref().map(|m| m.files.len() as u32).unwrap_or(0),
+    manifest: selected_version.manifest.clone(),
+  };
+
+  let _ = app.emit(
+    "download-version",
+    DownloadProgress {
+      version_name: version.name.clone(),
+      status: DownloadStatus::Init,
+      file: "".to_owned(),
+      progress: 0.0,
+      downloaded_files_cnt: 0,
+      total_file_count: version.total_file_count,
+    },
+  );
+
+  let download_dir = Path::new(&version.download_path);
+  std::fs::create_dir_all(download_dir)
+    .with_context(|| format!("Failed to create output download directory: {:?}", download_dir))
+    .map_err(|e| {
+      log_full_error(&e);
+      e.to_string()
+    })?;
+
+  // Fetched on a clone of the client so the Service lock is not held across the requests.
+  let api_client = { ctx.service.lock().await.api_client.clone() };
+  let release = main_release(&api_client, &selected_version.name)
+    .await
+    .context("Failed to get main release files")
+    .map_err(|e| {
+      log_full_error(&e);
+      e.to_string()
+    })?;
+
+  // Expected archive hashes; from the index when it has them (no extra request).
+  let hashes: HashMap<String, String> = match release_manifest(&api_client, &selected_version.name).await {
+    Ok(manifest) => manifest.files.into_iter().filter_map(|f| f.sha256.map(|h| (f.name, h))).collect(),
+    Err(e) => {
+      log::warn!("No manifest for '{}', archives will not be verified: {}", &selected_version.name, e);
+      HashMap::new()
+    }
+  };
+
+  version.total_file_count = release.assets.len() as u32;
+  for file in &release.assets {
+    version.files.insert(
+      file.name.clone(),
+      FileProgress {
+        id: file.name.clone(),
+        download_link: file.download_link.clone(),
+        name: file.name.clone(),
+        is_downloaded: false,
+        is_unpacked: false,
+        size: 0,
+        total_size: file.size,
+        sha256: hashes.get(&file.name).cloned(),
+      },
+    );
+  }
+
+  // Refuse before anything is recorded, so a start that does not fit leaves no paused download behind.
+  release_plan(Path::new(download_path), Path::new(install_path), version.manifest.as_ref(), &version.files)
+    .map_err(|e| e.to_string())?
+    .ensure()?;
+
+  let mut config_guard = ctx.config.lock().await;
+  config_guard.progress_download.insert(version.name.clone(), version.clone());
+  config_guard.save().map_err(|e| {
+    log_full_error(&e);
+    e.to_string()
+  })?;
+
+  Ok(version.name)
+}
+
+/// Downloads the missing files of a prepared version and unpacks them.
+/// Ok = every file is in place (`download-unpack-version` is emitted);
+/// `USER_CANCELLED` = stopped through `cancel_tx` or gave up on a file, the
+/// progress is kept for the next run.
+pub async fn run_release_download(ctx: &DownloadContext, version_name: &str, cancel_tx: broadcast::Sender<()>) -> Result<(), String> {
+  let app = &ctx.app;
+  let app_config = &ctx.config;
+  let service = &ctx.service;
+  let service_files = &ctx.files;
+  let service_unpack = &ctx.unpack;
+  let version_name = version_name.to_owned();
+
+  // 2. Сбор статистики и подготовка данных
+  let mut file_sizes: Vec<DownlaodFileStat> = vec![];
+  let (version, mut files_to_download, files_to_unpack, workers) = {
+    let mut cfg_guard = app_config.lock().await;
+
+    // Ensure the download dir exists on resume: a job restored after a restart
+    // could otherwise finish "successfully" with no real files on disk, then
+    // fail downstream when removing an already-absent download dir (os error 3).
+    {
+      let version_data = cfg_guard
+        .progress_download
+        .get(&version_name)
+        .ok_or_else(|| "Version not found".to_string())?;
+      let download_dir = Path::new(&version_data.download_path);
+      if !download_dir.exists() {
+        std::fs::create_dir_all(download_dir).map_err(|e| e.to_string())?;
+      }
+    }
+
+    let mut to_download = Vec::new();
+    let mut to_unpack = Vec::new();
+    let version_data = {
+      let version_data = cfg_guard
+        .progress_download
+        .get_mut(&version_name)
+        .ok_or_else(|| "Version not found".to_string())?;
+
+      let mut files_dwn_cnt: u32 = 0;
+
+      for (_, file_progress) in version_data.files.iter_mut() {
+        let file_path = Path::new(&version_data.download_path).join(&file_progress.name);
+        let file_part_path = Path::new(&version_data.download_path).join(format!("{}.part", &file_progress.name));
+
+        let current_size = if file_part_path.exists() {
+          part_file::downloaded_bytes(&file_part_path).unwrap_or(0)
+        } else if file_path.exists() && file_progress.is_downloaded {
+          file_progress.total_size
+        } else if !file_path.exists() && file_progress.is_unpacked {
+          file_progress.total_size
+        } else {
+          0
+        };
+
+        file_progress.size = current_size;
+
+        if current_size >= file_progress.total_size && file_progress.total_size > 0 {
+          file_progress.is_downloaded = true;
+          files_dwn_cnt += 1;
+        } else {
+          file_progress.is_downloaded = false;
+          to_download.push(file_progress.clone());
+        }
+
+        if file_progress.is_downloaded && !file_progress.is_unpacked {
+          to_unpack.push(file_progress.clone());
+        }
+
+        file_sizes.push(DownlaodFileStat {
+          name: file_progress.name.clone(),
+          unpacked: file_progress.is_unpacked,
+          size: Some(current_size),
+        });
+      }
+
+      version_data.downloaded_files_cnt = files_dwn_cnt;
+      version_data.clone()
+    };
+
+    cfg_guard.save().map_err(|e| e.to_string())?;
+
+    (version_data.clone(), to_download, to_unpack, cfg_guard.download_workers())
+  };
+
+  // Other data may have filled the drives since the download was started.
+  release_plan(Path::new(&version.download_path), Path::new(&version.installed_path), version.manifest.as_ref(), &version.files)
+    .map_err(|e| e.to_string())?
+    .ensure()?;
+
+  // Сортировка для UI (по номеру чанка в расширении)
+  file_sizes.sort_by_key(|file| Reverse(file.size));
+  files_to_download.sort_by_key(|file| Reverse(file.size));
+
+  let _ = app.emit("download-version-files", (&version_name, &file_sizes));
+  // Bug E fix: guard against division by zero.
+  let progress = if version.total_file_count > 0 {
+    (version.downloaded_files_cnt as f32 / version.total_file_count as f32) * 100.0
+  } else {
+    0.0
+  };
+  // Отправляем ивент на фронт с сохраненными данными о прогресса после паузы. Это нужна для начальной инициализации UI
+  let _ = app.emit(
+    "download-version",
+    DownloadProgress {
+      version_name: version.name.clone(),
+      status: DownloadStatus::DownloadFiles,
+      file: "".to_string(),
+      progress,
+      downloaded_files_cnt: version.downloaded_files_cnt,
+      total_file_count: version.total_file_count,
+    },
+  );
+
+  // 3. Создание очереди задач
+  let total_file_count = version.total_file_count;
+  let downloaded_cnt = Arc::new(AtomicU32::new(version.downloaded_files_cnt));
+  let (tx_queue, rx_queue) = mpsc::channel(total_file_count as usize + 100);
+  // Workers pull from the shared queue in parallel (no more workers than files).
+  let workers = (workers as usize).min(files_to_download.len().max(1));
+
+  // Archives still to be unpacked, downloaded ones included.
+  let archives_left = files_to_download.len() + files_to_unpack.len();
+  for file in files_to_download {
+    log::debug!("tx_queue.send, file: {:?}", &file);
+    let _ = tx_queue.send(file).await;
+  }
+
+  let (tx_unzip, mut rx_unzip) = mpsc::channel::<UnzipTask>(total_file_count as usize);
+  // Archives are unpacked next to the install dir and moved in once all of them succeeded.
+  // A download paused before staging existed keeps unpacking in place, where its first archives are.
+  let staging_dir = staging::staging_path(Path::new(&version.installed_path));
+  let in_place = !staging_dir.exists() && version.files.values().any(|f| f.is_unpacked);
+  let staging_dir = if in_place { PathBuf::from(&version.installed_path) } else { staging_dir };
+
+  // Отдельный поток-менеджер распаковки
+  let app_unzip = app.clone();
+  let version_name_unzip = version_name.clone();
+  let service_unpack_arc = service_unpack.clone();
+  let app_config_arc = app_config.clone();
+  let unzip_manager_handle = tokio::spawn(async move {
+    // Several archives are unpacked at once; the unpacker's thread budget splits the cores between them.
+    let threads = service_unpack_arc.threads();
+    let slots = Arc::new(Semaphore::new(threads));
+    let mut unpacking = JoinSet::new();
+    let mut archives_left = archives_left;
+
+    while let Some(data) = rx_unzip.recv().await {
+      log::debug!("Worker got msg to unpack file, data: {:?}", &data);
+
+      let Ok(slot) = slots.clone().acquire_owned().await else {
+        break;
+      };
+      // The first archives must leave threads for the ones right behind them.
+      let side_by_side = archives_left.clamp(1, threads);
+      archives_left = archives_left.saturating_sub(1);
+
+      let app_inner = app_unzip.clone();
+      let v_name = version_name_unzip.clone();
+      let service_unpack_for_thread = service_unpack_arc.clone();
+      let app_config_arc_for_thread = app_config_arc.clone();
+      let archive_path = data.archive_path.clone();
+      let file_name = data.file_name.clone();
+      let v_name_for_thread = v_name.clone();
+
+      unpacking.spawn(async move {
+        let _slot = slot;
+        // Unpacking is CPU-intensive → run it in spawn_blocking, returning whether it
+        // succeeded so the config update happens in the async context (no block_on
+        // inside a blocking thread, which previously risked starving the pool).
+        let unpack_ok: bool = tokio::task::spawn_blocking(move || {
+          let res = service_unpack_for_thread.extract_zip_shared(
+            &v_name_for_thread,
+            &data.file_name,
+            &data.archive_path,
+            &data.destination_path,
+            side_by_side,
+          );
+          if let Err(e) = &res {
+            log::error!("Unpack of '{}' failed: {}", &data.file_name, e);
+          }
+          let _ = app_inner.emit("file-unzipped", (&v_name_for_thread, data.archive_path.to_str()));
+          res.is_ok()
+        })
+        .await
+        .unwrap_or(false);
+
+        // Config update + archive removal back in the async context.
+        if unpack_ok {
+          let mut config_guard = app_config_arc_for_thread.lock().await;
+          if let Some(ver) = config_guard.progress_download.get_mut(&v_name) {
+            if let Some(file_progress) = ver.files.get_mut(&file_name) {
+              file_progress.is_unpacked = true;
+            }
+          }
+          let _ = config_guard.save();
+          drop(config_guard);
+          let _ = fs::remove_file(&archive_path);
+        }
+      });
+    }
+
+    while unpacking.join_next().await.is_some() {}
+    log::info!("Unzip queue finished");
+  });
+
+  let rx_queue_arc = Arc::new(Mutex::new(rx_queue));
+  let cancel_tx_arc = Arc::new(cancel_tx);
+  let tx_unzip_arc = Arc::new(tx_unzip);
+
+  for file in files_to_unpack {
+    let download_dir_c = Path::new(&version.download_path).to_path_buf();
+    let file_path = crate::utils::paths::safe_download_join(&download_dir_c, &file.name)?;
+    let _ = tx_unzip_arc
+      .send(UnzipTask {
+        file_name: file.name.clone(),
+        archive_path: file_path,
+        destination_path: staging_dir.clone(),
+      })
+      .await;
+  }
+
+  let api_client = service.lock().await.api_client.clone();
+  let mut join_handles = Vec::new();
+
+  // 4. Run download workers
+  log::info!("Downloading '{}' with {} workers", &version_name, workers);
+  for _ in 0..workers {
+    let app_c = app.clone();
+    let app_config_c = app_config.clone();
+    let service_files_c = service_files.clone();
+    let api_client_c = api_client.clone();
+    let version_name_c = version_name.clone();
+    let staging_dir_c = staging_dir.clone();
+    let download_dir_c = Path::new(&version.download_path).to_path_buf();
+    let downloaded_cnt_c = downloaded_cnt.clone();
+
+    let tx_unzip_c = tx_unzip_arc.clone();
+    let rx_queue_c = rx_queue_arc.clone();
+    let cancel_tx_arc_c = cancel_tx_arc.clone();
+    let mut stop_rx = cancel_tx_arc.subscribe();
+
+    let handle = tokio::spawn(async move {
+      // Per-file retry counter so a persistently failing file does not loop forever.
+      let mut retries = RetryCounter::default();
+      let mut current_task: Option<_> = None;
+      // Source of the current file and the providers already used for it (failover).
+      let mut source = DownloadSource::current("");
+      let mut tried: Vec<String> = vec![];
+
+      loop {
+        // Take next task either from the previous failed attempt or from the queue.
+        let file_task = if let Some(t) = current_task.take() {
+          t
+        } else {
+          let mut rx_lock = rx_queue_c.lock().await;
+          let task = tokio::select! {
+              _ = stop_rx.recv() => break,
+              task = rx_lock.recv() => match task {
+                  Some(t) => t,
+                  None => break,
+              }
+          };
+
+          // New file: start from the current provider again.
+          source = DownloadSource::current(&task.download_link);
+          tried = api_client_c.current_provider().map(|p| vec![p.id().to_owned()]).unwrap_or_default();
+          emit_file_source(&app_c, &api_client_c, &version_name_c, &task.name, &source);
+          task
+        };
+
+        let file_path = match crate::utils::paths::safe_download_join(&download_dir_c, &file_task.name) {
+          Ok(p) => p,
+          Err(e) => {
+            log::error!("safe_download_join failed: {}", e);
+            continue;
+          }
+        };
+        let part_path = format!("{}.part", file_path.to_str().unwrap_or(""));
+
+        // Actual seek before each attempt
+        let seek_pos = part_file::downloaded_bytes(&part_path);
+
+        let mut local_cancel = cancel_tx_arc_c.subscribe();
+        let res = service_files_c
+          .download_blob_to_file(
+            &api_client_c,
+            source.provider_id.as_deref(),
+            &version_name_c,
+            &source.url,
+            &file_task.total_size,
+            &file_path,
+            &seek_pos,
+            local_cancel,
+          )
+          .await;
+
+        // A corrupted archive must not reach the unpacker: a mismatch goes down the retry path from zero.
+        let res = match res {
+          Ok(DownloadOutcome::Completed) => service_files_c
+            .verify_download(&file_path, file_task.sha256.as_deref())
+            .await
+            .map(|_| DownloadOutcome::Completed),
+          other => other,
+        };
+
+        match res {
+          Ok(DownloadOutcome::Completed) => {
+            retries.reset();
+            let current = downloaded_cnt_c.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let _ = tx_unzip_c
+              .send(UnzipTask {
+                file_name: file_task.name.clone(),
+                archive_path: file_path.clone(),
+                destination_path: staging_dir_c.clone(),
+              })
+              .await;
+
+            // Update config
+            {
+              let mut config_guard = app_config_c.lock().await;
+              if let Some(ver) = config_guard.progress_download.get_mut(&version_name_c) {
+                if let Some(fp) = ver.files.get_mut(&file_task.id) {
+                  fp.is_downloaded = true;
+                }
+                ver.downloaded_files_cnt = current;
+              }
+              let _ = config_guard.save();
+            }
+
+            // Emit progress
+            // Bug E fix: guard against division by zero.
+            let progress = if total_file_count > 0 {
+              (current as f32 / total_file_count as f32) * 100.0
+            } else {
+              0.0
+            };
+            let _ = app_c.emit(
+              "download-version",
+              DownloadProgress {
+                version_name: version_name_c.clone(),
+                status: DownloadStatus::DownloadFiles,
+                file: file_task.name,
+                progress,
+                downloaded_files_cnt: current,
+                total_file_count,
+              },
+            );
+
+            if current >= total_file_count {
+              let _ = cancel_tx_arc_c.send(());
+              break;
+            }
+          }
+          Ok(DownloadOutcome::Interrupted) => {
+            // User pause / shutdown: persist partial progress to config and stop without
+            // counting this file as completed.
+            log::info!("Download of '{}' interrupted by cancel signal, saving progress", file_task.name);
+            persist_file_size(&app_config_c, &version_name_c, &file_task.name, &part_path).await;
+            break;
+          }
+          Err(e) => {
+            let failure = retries.fail(&e);
+            // Same asset on another provider: resume the .part there instead of hammering a failing host.
+            if failure.should_fail_over() {
+              if let Some(next) = next_source(&api_client_c, &version_name_c, &file_task.name, file_task.total_size, &mut tried).await {
+                log::warn!(
+                  "Download of '{}' failed {} times on '{}': {}. Switching to provider '{}'",
+                  file_task.name,
+                  failure.total,
+                  source.provider_name(&api_client_c),
+                  e,
+                  next.provider_name(&api_client_c),
+                );
+                source = next;
+                retries.reset();
+                emit_file_source(&app_c, &api_client_c, &version_name_c, &file_task.name, &source);
+                persist_file_size(&app_config_c, &version_name_c, &file_task.name, &part_path).await;
+                current_task = Some(file_task);
+                continue;
+              }
+            }
+            let Some(delay) = failure.delay else {
+              log::error!(
+                "Download of '{}' failed after {} attempts ({:?}): {}",
+                file_task.name,
+                failure.total,
+                failure.class,
+                e
+              );
+              persist_file_size(&app_config_c, &version_name_c, &file_task.name, &part_path).await;
+              break;
+            };
+            log::warn!(
+              "Error downloading '{}' (attempt {}/{}, {:?}): {}. Retrying in {:.1}s",
+              file_task.name,
+              failure.attempt,
+              failure.class.max_retries() + 1,
+              failure.class,
+              e,
+              delay.as_secs_f64()
+            );
+            persist_file_size(&app_config_c, &version_name_c, &file_task.name, &part_path).await;
+            current_task = Some(file_task);
+            // A pause or shutdown must not wait out a long backoff.
+            tokio::select! {
+              _ = tokio::time::sleep(delay) => {}
+              _ = stop_rx.recv() => break,
+            }
+          }
+        }
+      }
+    });
+    join_handles.push(handle);
+  }
+
+  // 5. Wait for completion
+  drop(tx_queue); // Lets rx_lock.recv() return None once workers finish their retries
+
+  for h in join_handles {
+    let _ = h.await;
+  }
+
+  // Determine whether the download completed fully. We only emit the completion
+  // event when every file finished; otherwise the frontend would start unpacking
+  // a partial download and wipe the saved progress.
+  let downloaded_total = downloaded_cnt.load(Ordering::SeqCst);
+  let fully_downloaded = downloaded_total >= total_file_count;
+
+  // ВАЖНО: Закрываем передатчик очереди распаковки.
+  // После этого rx_unzip.recv() вернет None, когда обработает ВСЕ задачи в очереди.
+  drop(tx_unzip_arc);
+
+  // Ждем, пока менеджер распаковки закончит последний файл
+  let _ = unzip_manager_handle.await;
+
+  if fully_downloaded {
+    // Bug B fix: mark the version as fully downloaded in config.
+    {
+      let mut config_guard = app_config.lock().await;
+      if let Some(ver) = config_guard.progress_download.get_mut(&version_name) {
+        ver.is_downloaded = true;
+      }
+      let _ = config_guard.save();
+    }
+
+    let all_unpacked = {
+      let config_guard = app_config.lock().await;
+      config_guard
+        .progress_download
+        .get(&version_name)
+        .is_some_and(|ver| ver.files.values().all(|f| f.is_unpacked))
+    };
+    if !all_unpacked {
+      // Nothing was moved into the install dir; a resume unpacks the failed archives again.
+      return Err(format!("Unpacking of '{}' failed, the installed files were not changed", &version_name));
+    }
+
+    if !in_place {
+      let installed_path = PathBuf::from(&version.installed_path);
+      tokio::task::spawn_blocking(move || staging::commit(&installed_path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    }
+
+    let _ = app.emit("download-unpack-version", &version_name);
+    Ok(())
+  } else {
+    log::info!(
+      "Download of '{}' did not complete (downloaded {}/{}); keeping progress, no unpack event",
+      &version_name,
+      downloaded_total,
+      total_file_count
+    );
+    Err("USER_CANCELLED".to_string())
+  }
+}
+
+/// Reads the `.part` sidecar and persists its byte count into `FileProgress.size`,
+/// so the resume point survives an abrupt process kill. Called after interruptions/retries.
+async fn persist_file_size(config: &Arc<Mutex<AppConfig>>, version_name: &str, file_name: &str, part_path: &str) {
+  let size = part_file::downloaded_bytes(part_path).unwrap_or(0);
+
+  let mut config_guard = config.lock().await;
+  if let Some(ver) = config_guard.progress_download.get_mut(version_name) {
+    if let Some(fp) = ver.files.get_mut(file_name) {
+      fp.size = size;
+    }
+  }
+  let _ = config_guard.save();
+}
+
+/// Tells the UI which provider serves `file_name` (on file start and after a failover).
+pub(crate) fn emit_file_source(app: &tauri::AppHandle, api_client: &ApiClient, version_name: &str, file_name: &str, source: &DownloadSource) {
+  let _ = app.emit(
+    "download-file-source",
+    DownloadFileSource {
+      version_name: version_name.to_string(),
+      file_name: file_name.to_string(),
+      provider_id: source.provider_name(api_client),
+    },
+  );
+}
@@ -0,0 +1,203 @@
+// Cross-provider failover for download workers.
+//
+// Every provider publishes the same releases in its own `index.json`, so when
+// one host keeps failing on an asset (403 / 5xx mid-download) the same file can
+// be looked up by release path + file name in another provider's index and
+// resumed from there: the `.part` offset stays valid because the bytes are
+// identical (the size is checked to be sure).
+
+use crate::{providers::ApiClient::ApiClient::ApiClient, service::index::IndexAsset};
+
+/// Where a file is downloaded from. `provider_id` = None means the current provider.
+#[derive(Debug, Clone)]
+pub struct DownloadSource {
+  pub provider_id: Option<String>,
+  pub url: String,
+}
+
+impl DownloadSource {
+  pub fn current(url: &str) -> Self {
+    Self {
+      provider_id: None,
+      url: url.to_owned(),
+    }
+  }
+
+  /// Provider id for the UI ("github", "gitlab", ...).
+  pub fn provider_name(&self, api_client: &ApiClient) -> String {
+    match &self.provider_id {
+      Some(id) => id.clone(),
+      None => api_client.current_provider().map(|p| p.id().to_owned()).unwrap_or_default(),
+    }
+  }
+}
+
+fn find_asset(assets: Vec<IndexAsset>, file_name: &str, size: u64) -> Option<IndexAsset> {
+  assets
+    .into_iter()
+    .find(|a| a.name == file_name && (size == 0 || a.size == 0 || a.size == size))
+}
+
+/// Finds `file_name` of `release_name` (name or path) in the index of the fastest
+/// available provider not listed in `tried`. The chosen provider is appended to `tried`.
+pub async fn next_source(api_client: &ApiClient, release_name: &str, file_name: &str, size: u64, tried: &mut Vec<String>) -> Option<DownloadSource> {
+  for (pid, _) in api_client.fastest_available() {
+    if tried.iter().any(|t| t == pid) {
+      continue;
+    }
+    tried.push(pid.to_owned());
+
+    let index = match api_client.get_provider(pid) {
+      Ok(api) => api.release_index().await,
+      Err(e) => Err(e),
+    };
+    let index = match index {
+      Ok(index) => index,
+      Err(e) => {
+        log::warn!("Failover: no release index for provider '{}': {}", pid, e);
+        continue;
+      }
+    };
+
+    let Some(entry) = index.releases.into_iter().find(|r| r.name == release_name || r.path == release_name) else {
+      continue;
+    };
+
+    let patch_assets = entry.patches.into_iter().flat_map(|p| p.assets);
+    if let Some(asset) = find_asset(entry.assets.into_iter().chain(patch_assets).collect(), file_name, size) {
+      log::info!("Failover: '{}' of '{}' found on provider '{}'", file_name, release_name, pid);
+      return Some(DownloadSource {
+        provider_id: Some(pid.to_owned()),
+        url: asset.url,
+      });
+    }
+  }
+
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use super::*;
+  use crate::{
+    providers::fake::Fake,
+    service::index::{IndexPatch, LauncherIndex, ReleaseIndex, ReleaseIndexEntry},
+  };
+
+  fn asset(name: &str, size: u64, url: &str) -> IndexAsset {
+    IndexAsset {
+      name: name.to_owned(),
+      size,
+      url: url.to_owned(),
+      sha256: None,
+    }
+  }
+
+  /// Index with release `GW 1` (path `gw-1`): `data.zip` and a patch archive, served from `host`.
+  fn index(host: &str) -> ReleaseIndex {
+    ReleaseIndex {
+      schema: 1,
+      generated_at: "".to_owned(),
+      launcher: LauncherIndex {
+        version: "".to_owned(),
+        assets: vec![],
+        bg_etag: None,
+      },
+      releases: vec![ReleaseIndexEntry {
+        name: "GW 1".to_owned(),
+        path: "gw-1".to_owned(),
+        tag: "gw-1".to_owned(),
+        exe_path: None,
+        manifest: format!("{}/manifest.json", host),
+        assets: vec![asset("data.zip", 100, &format!("{}/data.zip", host))],
+        patches: vec![IndexPatch {
+          tag: "p1".to_owned(),
+          base_patch: None,
+          notes: None,
+          manifest: None,
+          assets: vec![asset("patch.zip", 10, &format!("{}/patch.zip", host))],
+          supersedes: vec![],
+        }],
+        total_files_count: 0,
+        total_size: 0,
+        compressed_size: 0,
+      }],
+    }
+  }
+
+  fn fake(id: &'static str, latency_ms: Option<u64>, index: Option<ReleaseIndex>) -> Fake {
+    Fake {
+      index,
+      ..Fake::new(id, latency_ms)
+    }
+  }
+
+  fn client(providers: Vec<Fake>) -> ApiClient {
+    let mut client = ApiClient::new(Arc::new(|_: &str| {}));
+    for provider in providers {
+      client.register_provider(provider);
+    }
+    client
+  }
+
+  #[test]
+  fn find_asset_matches_the_name_and_a_known_size() {
+    let assets = || vec![asset("a.zip", 5, "a"), asset("b.zip", 7, "b1"), asset("b.zip", 0, "b2")];
+
+    assert_eq!(find_asset(assets(), "b.zip", 7).unwrap().url, "b1");
+    // Unknown size on either side matches by name only.
+    assert_eq!(find_asset(assets(), "b.zip", 0).unwrap().url, "b1");
+    assert_eq!(find_asset(assets(), "b.zip", 9).unwrap().url, "b2");
+    assert!(find_asset(vec![asset("a.zip", 5, "a")], "a.zip", 6).is_none());
+    assert!(find_asset(assets(), "c.zip", 0).is_none());
+  }
+
+  #[tokio::test]
+  async fn next_source_goes_from_the_fastest_provider_to_the_slowest() {
+    let client = client(vec![
+      fake("slow", Some(300), Some(index("https://slow"))),
+      fake("fast", Some(20), Some(index("https://fast"))),
+      fake("mid", Some(100), Some(index("https://mid"))),
+    ]);
+    let mut tried = vec![];
+
+    let mut urls = vec![];
+    while let Some(source) = next_source(&client, "GW 1", "data.zip", 100, &mut tried).await {
+      assert_eq!(source.provider_id.as_deref(), tried.last().map(String::as_str));
+      urls.push(source.url);
+    }
+
+    assert_eq!(urls, vec!["https://fast/data.zip", "https://mid/data.zip", "https://slow/data.zip"]);
+    assert_eq!(tried, vec!["fast", "mid", "slow"]);
+  }
+
+  #[tokio::test]
+  async fn next_source_skips_tried_down_and_indexless_providers() {
+    let client = client(vec![
+      fake("current", Some(10), Some(index("https://current"))),
+      fake("down", None, Some(index("https://down"))),
+      fake("no-index", Some(20), None),
+      fake("mirror", Some(50), Some(index("https://mirror"))),
+    ]);
+    let mut tried = vec!["current".to_owned()];
+
+    // Patch archives are found by release path too.
+    let source = next_source(&client, "gw-1", "patch.zip", 10, &mut tried).await.unwrap();
+    assert_eq!(source.provider_id.as_deref(), Some("mirror"));
+    assert_eq!(source.url, "https://mirror/patch.zip");
+    assert_eq!(tried, vec!["current", "no-index", "mirror"]);
+
+    assert!(next_source(&client, "gw-1", "patch.zip", 10, &mut tried).await.is_none());
+  }
+
+  #[tokio::test]
+  async fn next_source_rejects_a_copy_of_another_size() {
+    let client = client(vec![fake("mirror", Some(50), Some(index("https://mirror")))]);
+    let mut tried = vec![];
+
+    assert!(next_source(&client, "GW 1", "data.zip", 101, &mut tried).await.is_none());
+    assert_eq!(tried, vec!["mirror"]);
+  }
+}
@@ -1,8 +1,16 @@
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time::{Duration, Instant};
 
+use crate::configs::AppConfig::BandwidthRule;
+use crate::consts::{DOWNLOAD_SEGMENTS, DOWNLOAD_STALL_TIMEOUT_SECS, SEGMENT_MIN_SIZE};
 use crate::providers::ApiClient::ApiClient::ApiClient;
+use crate::providers::ApiProvider::ApiProvider;
+use crate::service::bandwidth::{BandwidthPolicy, ThrottleStatus, TokenBucket};
+use crate::service::index::IndexAsset;
+use crate::service::part_file::{self, PartFile, SegmentPlan};
+use crate::utils::errors::StallError;
 use crate::utils::paths::get_file_name;
 use anyhow::{Context, Result};
 use futures_util::stream::StreamExt;
@@ -10,9 +18,27 @@ use std::io::SeekFrom;
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncSeekExt;
 use tokio::io::AsyncWriteExt;
-use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::{self, Receiver};
 
-pub type NetSpeedCallback = Box<dyn Fn(&str, &str, u64, u64, f64) + Send + Sync>;
+pub type NetSpeedCallback = Box<dyn Fn(&str, &str, u64, u64, f64, ThrottleStatus) + Send + Sync>;
+
+// Persist the .part resume point often enough to survive abrupt kills…
+const PART_SAVE_INTERVAL: Duration = Duration::from_millis(250);
+// …but emit progress at ~2 Hz: the frontend rebuilds per-file progress
+// state on every event, so 10 events/s per active file froze the UI on
+// manifests with thousands of files.
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(500);
+// A stream silent for this long is dropped and resumed by the worker retry.
+const STALL_TIMEOUT: Duration = Duration::from_secs(DOWNLOAD_STALL_TIMEOUT_SECS);
+
+fn is_cancelled(rx: &mut Receiver<()>) -> bool {
+  match rx.try_recv() {
+    Ok(_) => true,
+    Err(tokio::sync::broadcast::error::TryRecvError::Empty) => false,
+    Err(tokio::sync::broadcast::error::TryRecvError::Closed) => false, // sender gone — keep going, not a cancel
+    Err(tokio::sync::broadcast::error::TryRecvError::Lagged(_)) => true,
+  }
+}
 
 /// Result of a single file download attempt.
 /// `Completed`  — file fully downloaded, `.part` removed.
@@ -25,27 +51,54 @@ pub enum DownloadOutcome {
 
 pub struct ServiceFiles {
   callback: Arc<NetSpeedCallback>,
+  /// Shared by all download workers: the cap applies to their total speed.
+  limiter: Arc<TokenBucket>,
+  policy: StdMutex<BandwidthPolicy>,
 }
 
 impl ServiceFiles {
   pub fn new<F>(callback: F) -> Self
   where
-    F: Fn(&str, &str, u64, u64, f64) + Send + Sync + 'static,
+    F: Fn(&str, &str, u64, u64, f64, ThrottleStatus) + Send + Sync + 'static,
   {
     Self {
       callback: Arc::new(Box::new(callback)),
+      limiter: Arc::new(TokenBucket::new(0)),
+      policy: StdMutex::new(BandwidthPolicy::default()),
     }
   }
 
+  /// Base speed cap in bytes per second (0 = unlimited) and the time-of-day
+  /// rules overriding it. Applies to running downloads too.
+  pub fn set_bandwidth(&self, base: u64, schedule: &[BandwidthRule]) -> Result<()> {
+    let policy = BandwidthPolicy::new(base, schedule)?;
+    *crate::utils::locks::lock(&self.policy) = policy;
+    self.throttle();
+
+    Ok(())
+  }
+
+  /// Cap for the current local time; moves the shared bucket to it, so a
+  /// schedule boundary is picked up by downloads already running.
+  pub fn throttle(&self) -> ThrottleStatus {
+    let status = crate::utils::locks::lock(&self.policy).status_at(chrono::Local::now().time());
+    self.limiter.set_rate(status.limit);
+    status
+  }
+
   pub async fn get_launcher_bg(&self, api_client: &ApiClient) -> Result<Vec<u8>> {
     let api = api_client.current_provider()?;
 
     api.get_launcher_bg().await
   }
 
+  /// `provider_id` overrides the current provider (failover to another mirror).
+  /// Files of `SEGMENT_MIN_SIZE` and up are fetched over `DOWNLOAD_SEGMENTS`
+  /// connections; `seek` only applies to a single-stream `.part`.
   pub async fn download_blob_to_file(
     &self,
     api_client: &ApiClient,
+    provider_id: Option<&str>,
     release_name: &str,
     direct_url: &str,
     total_bytes: &u64,
@@ -53,8 +106,10 @@ impl ServiceFiles {
     seek: &Option<u64>,
     mut rx: Receiver<()>,
   ) -> Result<DownloadOutcome> {
-    let api = api_client.current_provider()?;
-    let (mut stream, stream_start) = api.get_blob_by_url_stream(direct_url, seek).await?;
+    let api = match provider_id {
+      Some(id) => api_client.get_provider(id)?,
+      None => api_client.current_provider()?,
+    };
 
     let file_name = get_file_name(&output_path).ok_or_else(|| anyhow::anyhow!("download path has no file name"))?;
     let part_file_path = format!(
@@ -65,6 +120,44 @@ impl ServiceFiles {
         .ok_or_else(|| anyhow::anyhow!("download path is not valid UTF-8"))?
     );
 
+    // A single-stream resume point keeps going as one stream; a segment plan
+    // for another size is stale and starts over.
+    let part = part_file::read(&part_file_path);
+    let plan = match &part {
+      Some(PartFile::Segmented(plan)) if plan.total == *total_bytes => Some(plan.clone()),
+      Some(PartFile::Offset(offset)) if *offset > 0 => None,
+      _ if *total_bytes >= SEGMENT_MIN_SIZE => Some(SegmentPlan::split(*total_bytes, DOWNLOAD_SEGMENTS)),
+      _ => None,
+    };
+
+    // `seek` of a stale segment plan is a byte count, not an offset.
+    let mut seek = match part {
+      Some(PartFile::Segmented(_)) => None,
+      _ => *seek,
+    };
+    if let Some(plan) = plan {
+      let outcome = self
+        .download_segmented(
+          api,
+          release_name,
+          direct_url,
+          output_path.as_ref(),
+          &file_name,
+          &part_file_path,
+          plan,
+          &mut rx,
+        )
+        .await?;
+      if let Some(outcome) = outcome {
+        return Ok(outcome);
+      }
+      log::warn!("Server ignored the Range request for {}, downloading it as a single stream", file_name);
+      part_file::save_offset(&part_file_path, 0).await?;
+      seek = None;
+    }
+
+    let (mut stream, stream_start) = api.get_blob_by_url_stream(direct_url, &seek).await?;
+
     // Open the target file for writing. When the server ignored the Range
     // header (stream_start == 0 despite a requested resume offset) the body
     // starts from byte 0, so any stale partial bytes must be truncated —
@@ -90,40 +183,44 @@ impl ServiceFiles {
     if restart_from_zero {
       // Drop the stale resume point right away: a crash mid-restart must not
       // resurrect the old (now invalid) offset from the .part sidecar file.
-      Self::save_part_file(&part_file_path, 0).await?;
+      part_file::save_offset(&part_file_path, 0).await?;
     }
 
     let start_time = Instant::now();
-    // Persist the .part resume point often enough to survive abrupt kills…
-    const PART_SAVE_INTERVAL: Duration = Duration::from_millis(250);
-    // …but emit progress at ~2 Hz: the frontend rebuilds per-file progress
-    // state on every event, so 10 events/s per active file froze the UI on
-    // manifests with thousands of files.
-    const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(500);
     let mut last_part_save = Instant::now();
     let mut last_emit = Instant::now();
     let mut was_interrupted = false;
+    let mut throttle = self.throttle();
 
-    while let Some(chunk) = stream.next().await {
-      // Check for cancellation signal
-      let cancelled = match rx.try_recv() {
-        Ok(_) => true,
-        Err(tokio::sync::broadcast::error::TryRecvError::Empty) => false,
-        Err(tokio::sync::broadcast::error::TryRecvError::Closed) => false, // sender gone — keep going, not a cancel
-        Err(tokio::sync::broadcast::error::TryRecvError::Lagged(_)) => true,
+    loop {
+      let chunk = match tokio::time::timeout(STALL_TIMEOUT, stream.next()).await {
+        Ok(Some(chunk)) => chunk,
+        Ok(None) => break,
+        Err(_) => {
+          let _ = file.flush().await;
+          part_file::save_offset(&part_file_path, downloaded).await?;
+          return Err(
+            StallError {
+              file_name: file_name.to_string(),
+              downloaded,
+            }
+            .into(),
+          );
+        }
       };
 
-      if cancelled {
+      if is_cancelled(&mut rx) {
         log::info!("Download interrupted for file: {}", file_name);
         was_interrupted = true;
         // Flush whatever we have so far, then persist `.part` and the partial file.
         let _ = file.flush().await;
-        Self::save_part_file(&part_file_path, downloaded).await?;
+        part_file::save_offset(&part_file_path, downloaded).await?;
         break;
       }
 
       let chunk = chunk.context("Error reading chunk from response stream")?;
       let chunk_len = chunk.len() as u64;
+      self.limiter.acquire(chunk_len).await;
 
       file.write_all(&chunk).await.context("Failed to write chunk to file")?;
       downloaded += chunk_len;
@@ -131,7 +228,7 @@ impl ServiceFiles {
       let now = Instant::now();
       if now.duration_since(last_part_save) >= PART_SAVE_INTERVAL {
         // Persist progress periodically so an abrupt process kill keeps the resume point.
-        Self::save_part_file(&part_file_path, downloaded).await?;
+        part_file::save_offset(&part_file_path, downloaded).await?;
         last_part_save = now;
       }
 
@@ -143,14 +240,15 @@ impl ServiceFiles {
           0.0
         };
 
-        (self.callback)(release_name, &file_name, downloaded, total_bytes.clone(), speed);
+        throttle = self.throttle();
+        (self.callback)(release_name, &file_name, downloaded, total_bytes.clone(), speed, throttle);
         last_emit = now;
       }
     }
 
     if was_interrupted {
       // Final progress callback so the UI reflects the persisted partial size.
-      (self.callback)(release_name, &file_name, downloaded, total_bytes.clone(), 0.0);
+      (self.callback)(release_name, &file_name, downloaded, total_bytes.clone(), 0.0, throttle);
       return Ok(DownloadOutcome::Interrupted);
     }
 
@@ -163,8 +261,8 @@ impl ServiceFiles {
         total_bytes
       );
       let _ = file.flush().await;
-      Self::save_part_file(&part_file_path, downloaded).await?;
-      (self.callback)(release_name, &file_name, downloaded, total_bytes.clone(), 0.0);
+      part_file::save_offset(&part_file_path, downloaded).await?;
+      (self.callback)(release_name, &file_name, downloaded, total_bytes.clone(), 0.0, throttle);
       return Ok(DownloadOutcome::Interrupted);
     }
 
@@ -173,14 +271,209 @@ impl ServiceFiles {
     // Download finished successfully — remove `.part`.
     let _ = tokio::fs::remove_file(&part_file_path).await;
 
-    (self.callback)(release_name, &file_name, downloaded, total_bytes.clone(), 0.0);
+    (self.callback)(release_name, &file_name, downloaded, total_bytes.clone(), 0.0, throttle);
     Ok(DownloadOutcome::Completed)
   }
 
-  // Persist the current downloaded byte count into a `.part` sidecar file.
-  async fn save_part_file(path: &str, downloaded: u64) -> Result<()> {
-    // Write number as string — robust and easy to debug.
-    tokio::fs::write(path, downloaded.to_string().as_bytes()).await?;
-    Ok(())
+  /// Fetches the unfinished ranges of `plan` concurrently, one blob stream
+  /// each, writing at their own offsets. Returns None when the server ignored
+  /// Range: the caller falls back to a single stream (which rewrites the file
+  /// from byte 0, so bytes a segment already wrote do no harm).
+  async fn download_segmented(
+    &self,
+    api: &(dyn ApiProvider + Send + Sync),
+    release_name: &str,
+    direct_url: &str,
+    output_path: &Path,
+    file_name: &str,
+    part_file_path: &str,
+    plan: SegmentPlan,
+    rx: &mut Receiver<()>,
+  ) -> Result<Option<DownloadOutcome>> {
+    let total_bytes = plan.total;
+    let pending: Vec<usize> = plan
+      .segments
+      .iter()
+      .enumerate()
+      .filter(|(_, segment)| segment.remaining() > 0)
+      .map(|(idx, _)| idx)
+      .collect();
+
+    // Pre-size the file so each segment can write into its own range.
+    {
+      let file = OpenOptions::new().write(true).create(true).open(output_path).await?;
+      if file.metadata().await?.len() != total_bytes {
+        file.set_len(total_bytes).await?;
+      }
+    }
+
+    let start_bytes = plan.downloaded();
+    let plan = StdMutex::new(plan);
+    let stop = AtomicBool::new(false);
+    let (plan_ref, stop_ref) = (&plan, &stop);
+
+    // Each segment opens its response only when it starts, and checks that
+    // the stream really begins at its offset (206, not 200) before writing.
+    let fetch = |idx: usize| async move {
+      let (offset, mut remaining) = {
+        let plan = crate::utils::locks::lock(plan_ref);
+        (plan.segments[idx].offset(), plan.segments[idx].remaining())
+      };
+      let (mut stream, stream_start) = api.get_blob_by_url_stream(direct_url, &Some(offset).filter(|o| *o > 0)).await?;
+      if stream_start != offset {
+        stop_ref.store(true, Ordering::Relaxed);
+        return Ok(false);
+      }
+
+      let mut file = OpenOptions::new().write(true).open(output_path).await?;
+      file.seek(SeekFrom::Start(offset)).await?;
+
+      while remaining > 0 && !stop_ref.load(Ordering::Relaxed) {
+        let next = tokio::time::timeout(STALL_TIMEOUT, stream.next()).await.map_err(|_| StallError {
+          file_name: file_name.to_string(),
+          downloaded: crate::utils::locks::lock(plan_ref).downloaded(),
+        })?;
+        let Some(chunk) = next else {
+          anyhow::bail!("Segment {} of {} ended {} bytes early", idx, file_name, remaining);
+        };
+        let chunk = chunk.context("Error reading chunk from response stream")?;
+        // The stream runs to the end of the file: keep only this segment's bytes.
+        let len = (chunk.len() as u64).min(remaining);
+        self.limiter.acquire(len).await;
+
+        file.write_all(&chunk[..len as usize]).await.context("Failed to write chunk to file")?;
+        remaining -= len;
+        crate::utils::locks::lock(plan_ref).segments[idx].done += len;
+      }
+
+      file.flush().await?;
+      Ok::<bool, anyhow::Error>(true)
+    };
+
+    let mut segments = Box::pin(futures_util::future::try_join_all(pending.into_iter().map(fetch)));
+    let mut ticker = tokio::time::interval(PART_SAVE_INTERVAL);
+    let start_time = Instant::now();
+    let mut last_emit = Instant::now();
+    let mut was_interrupted = false;
+    let mut throttle = self.throttle();
+
+    let result = loop {
+      tokio::select! {
+        res = &mut segments => break res,
+        _ = ticker.tick() => {
+          if !was_interrupted && is_cancelled(rx) {
+            log::info!("Download interrupted for file: {}", file_name);
+            was_interrupted = true;
+            stop.store(true, Ordering::Relaxed);
+          }
+
+          let snapshot = crate::utils::locks::lock(&plan).clone();
+          part_file::save_plan(part_file_path, &snapshot).await?;
+
+          let now = Instant::now();
+          if now.duration_since(last_emit) >= PROGRESS_EMIT_INTERVAL {
+            let downloaded = snapshot.downloaded();
+            let elapsed = now.duration_since(start_time).as_secs_f64();
+            let speed = if elapsed > 0.0 {
+              (downloaded - start_bytes) as f64 / elapsed
+            } else {
+              0.0
+            };
+
+            throttle = self.throttle();
+            (self.callback)(release_name, file_name, downloaded, total_bytes, speed, throttle);
+            last_emit = now;
+          }
+        }
+      }
+    };
+    drop(segments);
+
+    if let Ok(ranged) = &result
+      && ranged.contains(&false)
+    {
+      return Ok(None);
+    }
+
+    // Persist the offsets before reporting an error, so the retry resumes every segment.
+    let plan = crate::utils::locks::lock(&plan).clone();
+    part_file::save_plan(part_file_path, &plan).await?;
+    result?;
+
+    let downloaded = plan.downloaded();
+    if was_interrupted || downloaded < total_bytes {
+      (self.callback)(release_name, file_name, downloaded, total_bytes, 0.0, throttle);
+      return Ok(Some(DownloadOutcome::Interrupted));
+    }
+
+    let _ = tokio::fs::remove_file(part_file_path).await;
+
+    (self.callback)(release_name, file_name, downloaded, total_bytes, 0.0, throttle);
+    Ok(Some(DownloadOutcome::Completed))
+  }
+
+  /// Checks a completed download against the manifest SHA-256 (None = nothing
+  /// to check). On mismatch the file and its `.part` are removed so the retry
+  /// starts from zero instead of resuming into bad data.
+  pub async fn verify_download(&self, output_path: &Path, expected_sha256: Option<&str>) -> Result<()> {
+    let Some(expected) = expected_sha256.filter(|s| !s.is_empty()) else {
+      return Ok(());
+    };
+
+    let path = output_path.to_path_buf();
+    let actual = tokio::task::spawn_blocking(move || crate::utils::hash::sha256_file(&path)).await??;
+    if actual.eq_ignore_ascii_case(expected) {
+      return Ok(());
+    }
+
+    let _ = tokio::fs::remove_file(output_path).await;
+    let _ = tokio::fs::remove_file(format!("{}.part", output_path.to_string_lossy())).await;
+    anyhow::bail!(
+      "SHA-256 mismatch for {:?}: expected {}, got {}; file removed, downloading again",
+      output_path,
+      expected,
+      actual
+    )
+  }
+}
+
+/// Downloads one asset into `dir`, resuming a `.part` left by an earlier export.
+/// An already complete file with a matching hash is kept.
+pub async fn fetch_asset(
+  api_client: &ApiClient,
+  files: &ServiceFiles,
+  provider_id: &str,
+  version_name: &str,
+  asset: &IndexAsset,
+  dir: &Path,
+  cancel_tx: &broadcast::Sender<()>,
+) -> Result<()> {
+  let path = crate::utils::paths::safe_download_join(dir, &asset.name).map_err(|e| anyhow::anyhow!(e))?;
+  let part_path = format!("{}.part", path.display());
+
+  if path.exists() && !Path::new(&part_path).exists() {
+    match &asset.sha256 {
+      Some(expected) if crate::utils::hash::sha256_file(&path)?.eq_ignore_ascii_case(expected) => return Ok(()),
+      None if std::fs::metadata(&path)?.len() == asset.size => return Ok(()),
+      _ => std::fs::remove_file(&path)?,
+    }
+  }
+
+  let seek = part_file::downloaded_bytes(&part_path);
+  let outcome = files
+    .download_blob_to_file(
+      api_client,
+      Some(provider_id),
+      version_name,
+      &asset.url,
+      &asset.size,
+      &path,
+      &seek,
+      cancel_tx.subscribe(),
+    )
+    .await?;
+  match outcome {
+    DownloadOutcome::Completed => files.verify_download(&path, asset.sha256.as_deref()).await,
+    DownloadOutcome::Interrupted => anyhow::bail!("USER_CANCELLED"),
   }
 }
@@ -4,7 +4,10 @@ use crate::{
   configs::AppConfig::Version,
   consts::*,
   handlers::dto::ReleaseManifest,
-  providers::dto::{Release, ReleaseAssetGit, ReleaseGit, ReleasePlatform, TreeItem},
+  providers::{
+    ApiClient::ApiClient::ApiClient,
+    dto::{Release, ReleaseAssetGit, ReleaseGit, ReleasePlatform, TreeItem},
+  },
   service::{index::ReleaseIndexEntry, main::Service},
   utils::{encoding::read_cp1251_file, patch_markers::read_installed_patches, resources::game_exe},
 };
@@ -29,6 +32,13 @@ fn get_platform_from_name(name: &str) -> ReleasePlatform {
   }
 }
 
+/// Drops the per-file list of a full manifest: the result is kept in the
+/// config (`versions`, `progress_download`) and saved on every progress
+/// update, while only verify/repair need the list, and they fetch it again.
+fn without_entries(manifest: ReleaseManifest) -> ReleaseManifest {
+  ReleaseManifest { entries: vec![], ..manifest }
+}
+
 /// Build a lightweight ReleaseManifest from index entry fields (no network).
 /// Always returns `Some` — even when size fields are zero (old index format).
 /// This prevents an infinite spinner in the UI when the fallback manifest
@@ -40,11 +50,15 @@ fn get_platform_from_name(name: &str) -> ReleasePlatform {
 /// The per-file list comes from the index assets: without it the manifest is
 /// a stub (aggregate sizes only) and the download queue rows show
 /// "0 B / 0 B" for every file that is not currently being downloaded.
-fn manifest_from_index_entry(entry: &ReleaseIndexEntry) -> Option<ReleaseManifest> {
+pub(crate) fn manifest_from_index_entry(entry: &ReleaseIndexEntry) -> Option<ReleaseManifest> {
   let files: Vec<crate::handlers::dto::ReleaseManifestFile> = entry
     .assets
     .iter()
-    .map(|a| crate::handlers::dto::ReleaseManifestFile { name: a.name.clone(), size: a.size })
+    .map(|a| crate::handlers::dto::ReleaseManifestFile {
+      name: a.name.clone(),
+      size: a.size,
+      sha256: a.sha256.clone(),
+    })
     .collect();
 
   Some(ReleaseManifest {
@@ -57,6 +71,107 @@ fn manifest_from_index_entry(entry: &ReleaseIndexEntry) -> Option<ReleaseManifes
   })
 }
 
+/// `ServiceGetRelease::get_release_manifest` on a clone of the client, so
+/// callers can release the Service lock before the request.
+pub(crate) async fn release_manifest(api_client: &ApiClient, release_name: &str) -> Result<ReleaseManifest> {
+  let api = api_client.current_provider()?;
+
+  // Try the static index first.
+  if let Ok(index) = api.release_index().await {
+    if let Some(entry) = index.releases.iter().find(|r| r.path == release_name || r.name == release_name) {
+      // Fast path: sizes already embedded in the index (no network needed).
+      if let Some(m) = manifest_from_index_entry(entry) {
+        log::info!("get_release_manifest '{}': serving from index fields (0 requests)", release_name);
+        return Ok(m);
+      }
+      // Slow path: fetch the manifest via its raw URL.
+      log::info!("get_release_manifest '{}': fetching from index manifest URL", release_name);
+      let cached = crate::utils::http_cache::fetch(
+          &crate::utils::http_cache::shared_client()?,
+          &entry.manifest,
+          std::time::Duration::from_secs(crate::consts::CACHE_TTL_RAW_FILE_SECS),
+      )
+      .await?;
+      let manifest: ReleaseManifest = serde_json::from_slice(&cached.bytes)?;
+      return Ok(without_entries(manifest));
+    }
+  }
+
+  // Fallback: original API path.
+  let repos = api.get_release_repos_by_name(release_name.clone()).await?;
+
+  let project = repos
+    .iter()
+    .find(|r| is_main_repo(&r.name))
+    .ok_or_else(|| anyhow!("Repo main_1 not found for release: {}", &release_name))?;
+
+  let project_id = if api.is_suppot_subgroups() {
+    project.id.to_string()
+  } else {
+    project.name.clone()
+  };
+  let bytes = api.get_file_raw(&project_id, MANIFEST_NAME).await?;
+  let manifest: ReleaseManifest = serde_json::from_slice(&bytes)?;
+
+  Ok(without_entries(manifest))
+}
+
+/// `ServiceGetRelease::get_main_release` on a clone of the client.
+pub(crate) async fn main_release(api_client: &ApiClient, release_name: &str) -> Result<ReleaseGit> {
+  let api = api_client.current_provider()?;
+
+  // Try the static release index first.
+  if let Ok(index) = api.release_index().await {
+    if let Some(entry) = index.releases.iter().find(|r| r.path == release_name || r.name == release_name) {
+      log::info!("get_main_release '{}': loaded from static index", release_name);
+      let assets: Vec<ReleaseAssetGit> = entry
+        .assets
+        .iter()
+        .map(|a| ReleaseAssetGit {
+          name: a.name.clone(),
+          platform: get_platform_from_name(&a.name),
+          size: a.size,
+          download_link: a.url.clone(),
+        })
+        .collect();
+      return Ok(ReleaseGit {
+        name: entry.name.clone(),
+        version: entry.tag.clone(),
+        assets,
+      });
+    }
+  }
+
+  // Fallback: original API path.
+
+  let repos = api.get_release_repos_by_name(release_name).await?;
+
+  if repos.is_empty() {
+    bail!("No 'main_' repos found for release {}", release_name);
+  }
+
+  let main_repo = repos
+    .iter()
+    .find(|r| is_main_repo(&r.name))
+    .ok_or_else(|| {
+      let names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
+      log::error!(
+        "main_1 repo not found for release '{}'. Available repos: {:?}",
+        &release_name,
+        names
+      );
+      anyhow!("Repo main_1 not found for release: {}", &release_name)
+    })?;
+
+  let project_id = if api.is_suppot_subgroups() {
+    main_repo.id.to_string()
+  } else {
+    main_repo.name.clone()
+  };
+
+  api.get_launcher_latest_release(&crate::configs::DistributionProfile::profile().github.org, &project_id).await
+}
+
 pub trait ServiceGetRelease {
   async fn get_releases(&mut self, cashed: bool) -> Result<Vec<Version>>;
   async fn get_release_manifest(&self, release_name: &str) -> Result<ReleaseManifest>;
@@ -96,7 +211,7 @@ impl ServiceGetRelease for Service {
     // 2. Static release index (0 API calls) — works for both cashed=false
     //    (fresh fetch) and cashed=true with empty in-memory cache (e.g.
     //    right after switching providers in the UI).
-    match crate::service::index::load_index(provider_id).await {
+    match api.release_index().await {
       Ok(index) => {
         log::info!("get_releases: loaded from static index ({} releases)", index.releases.len());
         let versions: Vec<Version> = index
@@ -164,101 +279,11 @@ impl ServiceGetRelease for Service {
   }
 
   async fn get_release_manifest(&self, release_name: &str) -> Result<ReleaseManifest> {
-    let api = self.api_client.current_provider()?;
-
-    // Try the static index first.
-    if let Ok(index) = crate::service::index::load_index(api.id()).await {
-      if let Some(entry) = index.releases.iter().find(|r| r.path == release_name || r.name == release_name) {
-        // Fast path: sizes already embedded in the index (no network needed).
-        if let Some(m) = manifest_from_index_entry(entry) {
-          log::info!("get_release_manifest '{}': serving from index fields (0 requests)", release_name);
-          return Ok(m);
-        }
-        // Slow path: fetch the manifest via its raw URL.
-        log::info!("get_release_manifest '{}': fetching from index manifest URL", release_name);
-        let cached = crate::utils::http_cache::fetch(
-            &crate::utils::http_cache::SHARED_CLIENT,
-            &entry.manifest,
-            std::time::Duration::from_secs(crate::consts::CACHE_TTL_RAW_FILE_SECS),
-        )
-        .await?;
-        let manifest: ReleaseManifest = serde_json::from_slice(&cached.bytes)?;
-        return Ok(manifest);
-      }
-    }
-
-    // Fallback: original API path.
-    let repos = api.get_release_repos_by_name(release_name.clone()).await?;
-
-    let project = repos
-      .iter()
-      .find(|r| is_main_repo(&r.name))
-      .ok_or_else(|| anyhow!("Repo main_1 not found for release: {}", &release_name))?;
-
-    let project_id = if api.is_suppot_subgroups() {
-      project.id.to_string()
-    } else {
-      project.name.clone()
-    };
-    let bytes = api.get_file_raw(&project_id, MANIFEST_NAME).await?;
-    let manifest: ReleaseManifest = serde_json::from_slice(&bytes)?;
-
-    Ok(manifest)
+    release_manifest(&self.api_client, release_name).await
   }
 
   async fn get_main_release(&self, release_name: &str) -> Result<ReleaseGit> {
-    let api = self.api_client.current_provider()?;
-
-    // Try the static release index first.
-    if let Ok(index) = crate::service::index::load_index(api.id()).await {
-      if let Some(entry) = index.releases.iter().find(|r| r.path == release_name || r.name == release_name) {
-        log::info!("get_main_release '{}': loaded from static index", release_name);
-        let assets: Vec<ReleaseAssetGit> = entry
-          .assets
-          .iter()
-          .map(|a| ReleaseAssetGit {
-            name: a.name.clone(),
-            platform: get_platform_from_name(&a.name),
-            size: a.size,
-            download_link: a.url.clone(),
-          })
-          .collect();
-        return Ok(ReleaseGit {
-          name: entry.name.clone(),
-          version: entry.tag.clone(),
-          assets,
-        });
-      }
-    }
-
-    // Fallback: original API path.
-
-    let repos = api.get_release_repos_by_name(release_name).await?;
-
-    if repos.is_empty() {
-      bail!("No 'main_' repos found for release {}", release_name);
-    }
-
-    let main_repo = repos
-      .iter()
-      .find(|r| is_main_repo(&r.name))
-      .ok_or_else(|| {
-        let names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
-        log::error!(
-          "main_1 repo not found for release '{}'. Available repos: {:?}",
-          &release_name,
-          names
-        );
-        anyhow!("Repo main_1 not found for release: {}", &release_name)
-      })?;
-
-    let project_id = if api.is_suppot_subgroups() {
-      main_repo.id.to_string()
-    } else {
-      main_repo.name.clone()
-    };
-
-    api.get_launcher_latest_release(GITHUB_ORG, &project_id).await
+    main_release(&self.api_client, release_name).await
   }
 
   async fn get_main_release_files(&self, release_name: &str) -> Result<Vec<TreeItem>> {
@@ -0,0 +1,318 @@
+// Background provider health monitor.
+//
+// The provider is picked once at startup from a single `ping_all`. This task
+// re-pings every provider periodically, keeps rolling latency / error stats
+// and moves `ApiClient`'s current provider away from one that went down (and
+// back to the user's choice once it has recovered). The switch is
+// runtime-only: `selected_provider_id` in the config is never touched.
+
+use std::{
+  collections::{HashMap, VecDeque},
+  sync::{Arc, Mutex as StdMutex},
+  time::Duration,
+};
+
+use tauri::Emitter;
+use tokio::sync::Mutex;
+
+use crate::{
+  configs::AppConfig::AppConfig,
+  consts::{HEALTH_FAILS_BEFORE_SWITCH, HEALTH_OKS_BEFORE_RESTORE, HEALTH_WINDOW},
+  handlers::dto::ProviderSwitch,
+  providers::dto::{ProviderHealth, ProviderStatus},
+  service::main::Service,
+};
+
+pub type HealthMap = Arc<StdMutex<HashMap<&'static str, HealthSamples>>>;
+
+/// Last `HEALTH_WINDOW` checks of one provider (latency, None = failed).
+#[derive(Debug, Default)]
+pub struct HealthSamples {
+  samples: VecDeque<Option<u64>>,
+  consecutive_failures: u32,
+  consecutive_successes: u32,
+  last_checked_at: Option<u64>,
+}
+
+impl HealthSamples {
+  fn push(&mut self, status: &ProviderStatus) {
+    if self.samples.len() >= HEALTH_WINDOW {
+      self.samples.pop_front();
+    }
+
+    if status.available {
+      self.samples.push_back(Some(status.latency_ms.unwrap_or(0)));
+      self.consecutive_failures = 0;
+      self.consecutive_successes += 1;
+    } else {
+      self.samples.push_back(None);
+      self.consecutive_failures += 1;
+      self.consecutive_successes = 0;
+    }
+    self.last_checked_at = Some(chrono::Utc::now().timestamp() as u64);
+  }
+
+  pub fn summary(&self) -> ProviderHealth {
+    let latencies: Vec<u64> = self.samples.iter().flatten().copied().collect();
+    let failed = self.samples.len() - latencies.len();
+
+    ProviderHealth {
+      avg_latency_ms: if latencies.is_empty() {
+        None
+      } else {
+        Some(latencies.iter().sum::<u64>() / latencies.len() as u64)
+      },
+      error_rate: if self.samples.is_empty() {
+        0.0
+      } else {
+        failed as f64 / self.samples.len() as f64
+      },
+      consecutive_failures: self.consecutive_failures,
+      consecutive_successes: self.consecutive_successes,
+      checks: self.samples.len() as u32,
+      last_checked_at: self.last_checked_at,
+    }
+  }
+}
+
+/// Provider to switch to, with the reason for the `provider-switched` event.
+/// `stats` is sorted by latency (fastest first), as returned by `ping_all`.
+fn choose_switch(
+  current: Option<&str>,
+  saved: Option<&str>,
+  health: &HashMap<&'static str, HealthSamples>,
+  stats: &[(&'static str, ProviderStatus)],
+) -> Option<(&'static str, &'static str)> {
+  let is_up = |id: &str| stats.iter().any(|(pid, s)| *pid == id && s.available);
+  let fails = |id: &str| health.get(id).map(|h| h.consecutive_failures).unwrap_or(0);
+  let oks = |id: &str| health.get(id).map(|h| h.consecutive_successes).unwrap_or(0);
+
+  let current_down = match current {
+    Some(id) => fails(id) >= HEALTH_FAILS_BEFORE_SWITCH,
+    // Startup found nothing available: take the first provider that came up.
+    None => true,
+  };
+
+  if current_down {
+    // Prefer the user's choice, then the fastest available provider.
+    return saved
+      .and_then(|s| stats.iter().find(|(id, _)| *id == s && Some(*id) != current && is_up(id)))
+      .or_else(|| stats.iter().find(|(id, s)| Some(*id) != current && s.available))
+      .map(|(id, _)| (*id, "unavailable"));
+  }
+
+  // Back to the saved provider once it is stable again.
+  match saved {
+    Some(s) if Some(s) != current && oks(s) >= HEALTH_OKS_BEFORE_RESTORE => stats.iter().find(|(id, _)| *id == s).map(|(id, _)| (*id, "restored")),
+    _ => None,
+  }
+}
+
+#[derive(Clone)]
+pub struct HealthMonitor {
+  app: tauri::AppHandle,
+  service: Arc<Mutex<Service>>,
+  config: Arc<Mutex<AppConfig>>,
+  health: HealthMap,
+}
+
+impl HealthMonitor {
+  pub fn new(app: tauri::AppHandle, service: Arc<Mutex<Service>>, config: Arc<Mutex<AppConfig>>, health: HealthMap) -> Self {
+    Self {
+      app,
+      service,
+      config,
+      health,
+    }
+  }
+
+  /// Runs the checks every `interval` in a background task.
+  pub fn start(self, interval: Duration) {
+    tauri::async_runtime::spawn(async move {
+      loop {
+        tokio::time::sleep(interval).await;
+        self.check().await;
+      }
+    });
+  }
+
+  async fn check(&self) {
+    // Ping on a clone (provider statuses are shared) so UI commands are not
+    // blocked behind the Service lock for the whole round.
+    let api_client = { self.service.lock().await.api_client.clone() };
+    if api_client.get_provider_ids().is_empty() {
+      // Providers are not registered yet.
+      return;
+    }
+
+    let stats = api_client.ping_all().await;
+
+    {
+      let mut health = crate::utils::locks::lock(&self.health);
+      for (id, status) in &stats {
+        health.entry(*id).or_default().push(status);
+      }
+    }
+
+    let saved = { self.config.lock().await.selected_provider_id.clone() };
+
+    let mut service = self.service.lock().await;
+    service.stats = stats.clone();
+
+    let current = service.api_client.current_provider().ok().map(|p| p.id());
+    let switch = {
+      let health = crate::utils::locks::lock(&self.health);
+      choose_switch(current, saved.as_deref(), &health, &stats)
+    };
+    let Some((to, reason)) = switch else {
+      return;
+    };
+
+    if let Err(e) = service.api_client.set_current_provider(to) {
+      log::error!("Health monitor: cannot switch to provider '{}': {}", to, e);
+      return;
+    }
+    log::warn!("Health monitor: current provider {:?} -> '{}' ({})", current, to, reason);
+
+    // The manifest is shared by the provider clones: load it without holding
+    // the Service lock across the request.
+    let api_client = service.api_client.clone();
+    drop(service);
+
+    // Same rule as at startup: the manifest is needed for uploads only and
+    // costs a Search API call on GitHub without a token.
+    if let Ok(api) = api_client.current_provider()
+      && (api.is_suppot_subgroups() || !api.get_token().is_empty())
+      && let Err(e) = api.load_manifest().await
+    {
+      log::warn!("Health monitor: load_manifest for '{}' failed: {}", to, e);
+    }
+
+    let _ = self.app.emit(
+      "provider-switched",
+      ProviderSwitch {
+        from: current.map(|id| id.to_owned()),
+        to: to.to_owned(),
+        reason: reason.to_owned(),
+      },
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  struct Case {
+    name: &'static str,
+    current: Option<&'static str>,
+    saved: Option<&'static str>,
+    /// (provider, consecutive failures, consecutive successes)
+    health: Vec<(&'static str, u32, u32)>,
+    /// (provider, available), fastest first.
+    stats: Vec<(&'static str, bool)>,
+    expected: Option<(&'static str, &'static str)>,
+  }
+
+  fn run(case: &Case) -> Option<(&'static str, &'static str)> {
+    let health = case
+      .health
+      .iter()
+      .map(|&(id, fails, oks)| {
+        let samples = HealthSamples {
+          consecutive_failures: fails,
+          consecutive_successes: oks,
+          ..Default::default()
+        };
+        (id, samples)
+      })
+      .collect();
+    let stats: Vec<_> = case
+      .stats
+      .iter()
+      .map(|&(id, available)| {
+        let status = ProviderStatus {
+          available,
+          latency_ms: available.then_some(100),
+          rate_limit: None,
+        };
+        (id, status)
+      })
+      .collect();
+    choose_switch(case.current, case.saved, &health, &stats)
+  }
+
+  #[test]
+  fn choose_switch_table() {
+    let cases = [
+      Case {
+        name: "healthy current provider stays",
+        current: Some("github"),
+        saved: Some("github"),
+        health: vec![("github", 0, 5), ("gitea", 0, 5)],
+        stats: vec![("gitea", true), ("github", true)],
+        expected: None,
+      },
+      Case {
+        name: "a single failure is not enough to switch",
+        current: Some("github"),
+        saved: Some("github"),
+        health: vec![("github", HEALTH_FAILS_BEFORE_SWITCH - 1, 0)],
+        stats: vec![("gitea", true), ("github", false)],
+        expected: None,
+      },
+      Case {
+        name: "degraded current moves to the fastest healthy provider",
+        current: Some("github"),
+        saved: Some("github"),
+        health: vec![("github", HEALTH_FAILS_BEFORE_SWITCH, 0), ("gitea", 0, 5), ("mirror", 0, 5)],
+        stats: vec![("gitea", true), ("mirror", true), ("github", false)],
+        expected: Some(("gitea", "unavailable")),
+      },
+      Case {
+        name: "degraded current prefers the saved provider over a faster one",
+        current: Some("gitea"),
+        saved: Some("github"),
+        health: vec![("gitea", HEALTH_FAILS_BEFORE_SWITCH, 0), ("github", 0, 1)],
+        stats: vec![("mirror", true), ("github", true), ("gitea", false)],
+        expected: Some(("github", "unavailable")),
+      },
+      Case {
+        name: "all providers down",
+        current: Some("github"),
+        saved: Some("github"),
+        health: vec![("github", 5, 0), ("gitea", 5, 0)],
+        stats: vec![("github", false), ("gitea", false)],
+        expected: None,
+      },
+      Case {
+        name: "nothing picked at startup takes the first provider that came up",
+        current: None,
+        saved: Some("github"),
+        health: vec![],
+        stats: vec![("gitea", true), ("github", false)],
+        expected: Some(("gitea", "unavailable")),
+      },
+      Case {
+        name: "saved provider is restored once stable",
+        current: Some("gitea"),
+        saved: Some("github"),
+        health: vec![("gitea", 0, 5), ("github", 0, HEALTH_OKS_BEFORE_RESTORE)],
+        stats: vec![("gitea", true), ("github", true)],
+        expected: Some(("github", "restored")),
+      },
+      Case {
+        name: "saved provider is not restored too early",
+        current: Some("gitea"),
+        saved: Some("github"),
+        health: vec![("gitea", 0, 5), ("github", 0, HEALTH_OKS_BEFORE_RESTORE - 1)],
+        stats: vec![("gitea", true), ("github", true)],
+        expected: None,
+      },
+    ];
+
+    for case in &cases {
+      assert_eq!(run(case), case.expected, "{}", case.name);
+    }
+  }
+}
@@ -2,16 +2,17 @@
 //
 // Per-provider: each provider has its own index with provider-specific URLs.
 // The index is fetched with ETag-cached conditional GETs (304 does NOT count
-// against the GitHub API rate limit).
+// against the GitHub API rate limit).  The local / LAN provider reads it
+// straight from its directory (`providers::Local`).
 
 use std::time::Duration;
 
 use anyhow::{Result, bail};
 use serde::{Deserialize, Serialize};
 
+use crate::configs::DistributionProfile::profile;
 use crate::consts::{
-  GITHUB_INDEX_RAW_URL, GITHUB_PID, GITLAB_API_HOST, GITLAB_INDEX_PROJECT_ID,
-  GITLAB_PID, INDEX_CACHE_TTL_SECS, INDEX_SCHEMA_VERSION,
+  DEFAULT_BRANCH, GITEA_PID, GITHUB_PID, GITLAB_PID, INDEX_CACHE_TTL_SECS, INDEX_SCHEMA_VERSION, LOCAL_PID, MIRROR_PID,
 };
 
 // ---------------------------------------------------------------------------
@@ -23,6 +24,9 @@ pub struct IndexAsset {
     pub name: String,
     pub size: u64,
     pub url: String,
+    /// Copied from the release manifest; None for old releases / non-archive assets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +62,9 @@ pub struct IndexPatch {
     pub manifest: Option<String>,
     #[serde(default)]
     pub assets: Vec<IndexAsset>,
+    /// Cumulative patch: the patch tags it replaces (from its manifest).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supersedes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,18 +104,34 @@ pub struct ReleaseIndex {
 // ---------------------------------------------------------------------------
 
 /// Resolve the raw URL for the static release index of the given provider.
+/// GitLab / Gitea read `DEFAULT_BRANCH` of the index repo (no API call for a
+/// branch lookup); the GitHub URL is configured in full in the distribution profile.
 fn index_raw_url(provider_id: &str) -> Result<String> {
+    let profile = profile();
     match provider_id {
-        GITHUB_PID => Ok(GITHUB_INDEX_RAW_URL.to_string()),
+        GITHUB_PID => Ok(profile.github.index_raw_url.clone()),
         GITLAB_PID => {
-            if GITLAB_INDEX_PROJECT_ID == 0 {
-                bail!("GitLab release index is not configured (GITLAB_INDEX_PROJECT_ID = 0)");
+            if profile.gitlab.index_project_id == 0 {
+                bail!("GitLab release index is not configured (gitlab.index_project_id = 0)");
             }
             Ok(format!(
-                "{}/projects/{}/repository/files/index.json/raw?ref=master",
-                GITLAB_API_HOST, GITLAB_INDEX_PROJECT_ID,
+                "{}/projects/{}/repository/files/index.json/raw?ref={}",
+                profile.gitlab.api_host,
+                profile.gitlab.index_project_id,
+                DEFAULT_BRANCH,
             ))
         }
+        GITEA_PID => Ok(format!(
+            "{}/{}/{}/raw/branch/{}/index.json",
+            profile.gitea.host.trim_end_matches('/'), profile.gitea.org, profile.index_repo_name, DEFAULT_BRANCH,
+        )),
+        MIRROR_PID => {
+            if profile.mirror.base_url.is_empty() {
+                bail!("Static mirror is not configured (mirror.base_url is empty)");
+            }
+            Ok(format!("{}/index.json", profile.mirror.base_url.trim_end_matches('/')))
+        }
+        LOCAL_PID => bail!("The local release index is read through the Local provider"),
         _ => bail!("Unknown provider '{}': no release index", provider_id),
     }
 }
@@ -119,17 +142,24 @@ fn index_raw_url(provider_id: &str) -> Result<String> {
 /// API rate limit.  Returns `Err` if the index is not configured for this
 /// provider, the network is down (and no stale cache exists), or the schema
 /// version is incompatible (forces launcher self-update).
+///
+/// The local provider's index is read by the provider itself
+/// (`ApiProvider::release_index`), which owns its root directory.
 pub async fn load_index(provider_id: &str) -> Result<ReleaseIndex> {
     let url = index_raw_url(provider_id)?;
-
     let cached = crate::utils::http_cache::fetch(
-        &crate::utils::http_cache::SHARED_CLIENT,
+        &crate::utils::http_cache::shared_client()?,
         &url,
         Duration::from_secs(INDEX_CACHE_TTL_SECS),
     )
     .await?;
 
-    let index: ReleaseIndex = serde_json::from_slice(&cached.bytes)?;
+    parse_index(&cached.bytes, provider_id, &format!("{:?}", cached.source))
+}
+
+/// Parses `index.json` of `provider_id` and checks its schema version.
+pub fn parse_index(bytes: &[u8], provider_id: &str, source: &str) -> Result<ReleaseIndex> {
+    let index: ReleaseIndex = serde_json::from_slice(bytes)?;
 
     if index.schema != INDEX_SCHEMA_VERSION {
         bail!(
@@ -141,11 +171,11 @@ pub async fn load_index(provider_id: &str) -> Result<ReleaseIndex> {
     }
 
     log::info!(
-        "Release index loaded for provider '{}' (schema={}, {} releases, cache={:?})",
+        "Release index loaded for provider '{}' (schema={}, {} releases, cache={})",
         provider_id,
         index.schema,
         index.releases.len(),
-        cached.source,
+        source,
     );
 
     Ok(index)
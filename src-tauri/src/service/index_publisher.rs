@@ -16,6 +16,7 @@ use anyhow::{Context, Result, bail};
 use serde_json;
 
 use crate::{
+    configs::DistributionProfile::profile,
     consts::*,
     handlers::dto::ReleaseManifest,
     providers::{dto::Project, ApiProvider::ApiProvider},
@@ -33,17 +34,21 @@ pub async fn collect_index(api: &(dyn ApiProvider + Send + Sync)) -> Result<Stri
 pub async fn commit_index_json(api: &(dyn ApiProvider + Send + Sync), json: &str) -> Result<()> {
     let is_gitlab = api.is_suppot_subgroups();
     if is_gitlab {
-        if GITLAB_INDEX_PROJECT_ID == 0 {
-            bail!("GITLAB_INDEX_PROJECT_ID = 0, GitLab index not configured");
+        let index_project_id = profile().gitlab.index_project_id;
+        if index_project_id == 0 {
+            bail!("gitlab.index_project_id = 0, GitLab index not configured");
         }
+        let repo_id = index_project_id.to_string();
+        let branch = api.repo_branch(&repo_id).await;
         api.add_file_to_repo(
-            &GITLAB_INDEX_PROJECT_ID.to_string(), "index.json", json,
-            "Update release index", DEFAULT_BRANCH,
+            &repo_id, "index.json", json,
+            "Update release index", &branch,
         ).await.context("index: add_file_to_repo (GitLab)")?;
     } else {
+        let branch = api.repo_branch(&profile().index_repo_name).await;
         api.add_file_to_repo(
-            INDEX_REPO_NAME, "index.json", json,
-            "Update release index", DEFAULT_BRANCH,
+            &profile().index_repo_name, "index.json", json,
+            "Update release index", &branch,
         ).await.context("index: add_file_to_repo (GitHub)")?;
     }
     Ok(())
@@ -55,18 +60,18 @@ async fn collect_release_index(api: &(dyn ApiProvider + Send + Sync)) -> Result<
 
     // ---- Launcher (self-update) ----
     let launcher_project_id = if is_gitlab {
-        REPO_LAUNCGER_ID_2.to_string()
+        profile().gitlab.launcher_release_project_id.to_string()
     } else {
-        GITHUB_LAUNCHER_REPO_NAME.to_string()
+        profile().github.launcher_repo.clone()
     };
-    let launcher_owner = if is_gitlab { "" } else { MAIN_DEVELOPER_NAME };
+    let launcher_owner = if is_gitlab { "" } else { profile().github.launcher_owner.as_str() };
 
     let launcher_release = api
         .get_launcher_latest_release(launcher_owner, &launcher_project_id)
         .await
         .context("index: get_launcher_latest_release")?;
 
-    let bg_etag = fetch_bg_etag(&api.launcher_bg_url()).await;
+    let bg_etag = fetch_bg_etag(&api.launcher_bg_url().await).await;
 
     let launcher_index = LauncherIndex {
         version: launcher_release.version.clone(),
@@ -116,7 +121,7 @@ async fn collect_release_index(api: &(dyn ApiProvider + Send + Sync)) -> Result<
 
         let latest = match api
             .get_launcher_latest_release(
-                if is_gitlab { "" } else { GITHUB_ORG },
+                if is_gitlab { "" } else { profile().github.org.as_str() },
                 &project_id,
             )
             .await
@@ -128,7 +133,8 @@ async fn collect_release_index(api: &(dyn ApiProvider + Send + Sync)) -> Result<
             }
         };
 
-        let manifest_url = manifest_url_for(api, main);
+        let branch = api.repo_branch(&project_id).await;
+        let manifest_url = manifest_url_for(api, main, &branch);
 
         // Extract size fields + exe_path from the release manifest (CDN, not rate-limited).
         let manifest_info = extract_manifest_info(&manifest_url).await;
@@ -143,6 +149,7 @@ async fn collect_release_index(api: &(dyn ApiProvider + Send + Sync)) -> Result<
                 name: a.name.clone(),
                 size: a.size,
                 url: a.download_link.clone(),
+                sha256: manifest_sha256(manifest_info.as_ref(), &a.name),
             })
             .collect();
 
@@ -175,31 +182,38 @@ async fn collect_release_index(api: &(dyn ApiProvider + Send + Sync)) -> Result<
             };
 
             for rr in repo_releases {
-                let mut manifest_asset_url: Option<String> = None;
+                let manifest_asset_url = rr
+                    .assets
+                    .iter()
+                    .find(|a| a.name == MANIFEST_NAME)
+                    .map(|a| a.download_link.clone());
+
+                // base_patch + archive hashes from the patch manifest (CDN, not rate-limited).
+                let patch_manifest = match manifest_asset_url.as_deref() {
+                    Some(url) => extract_manifest_info(url).await,
+                    None => None,
+                };
+                let base_patch = patch_manifest.as_ref().and_then(|m| m.base_patch.clone());
+                let supersedes = patch_manifest.as_ref().map(|m| m.supersedes.clone()).unwrap_or_default();
+
                 let patch_assets: Vec<IndexAsset> = rr
                     .assets
                     .iter()
-                    .map(|a| {
-                        if a.name == MANIFEST_NAME {
-                            manifest_asset_url = Some(a.download_link.clone());
-                        }
-                        IndexAsset {
-                            name: a.name.clone(),
-                            size: a.size.unwrap_or(0),
-                            url: a.download_link.clone(),
-                        }
+                    .map(|a| IndexAsset {
+                        name: a.name.clone(),
+                        size: a.size.unwrap_or(0),
+                        url: a.download_link.clone(),
+                        sha256: manifest_sha256(patch_manifest.as_ref(), &a.name),
                     })
                     .collect();
 
-                // Extract base_patch from the patch manifest (CDN, not rate-limited).
-                let base_patch = extract_base_patch(manifest_asset_url.as_deref()).await;
-
                 patches.push(IndexPatch {
                     tag: rr.tag_name,
                     base_patch,
                     notes: rr.body,
                     manifest: manifest_asset_url,
                     assets: patch_assets,
+                    supersedes,
                 });
             }
         }
@@ -207,8 +221,12 @@ async fn collect_release_index(api: &(dyn ApiProvider + Send + Sync)) -> Result<
         // Order patches by chain (base -> newest) so that the player UI can
         // mark the first uninstalled patch as "next" in install order. The
         // provider APIs return releases newest-first, which would otherwise
-        // invert the chain and mislead the user.
-        let patches = order_patches_by_chain(patches);
+        // invert the chain and mislead the user. Cumulative patches are not
+        // links of the chain (their base is the full release): they follow it.
+        let (cumulative, regular): (Vec<IndexPatch>, Vec<IndexPatch>) =
+            patches.into_iter().partition(|p| !p.supersedes.is_empty());
+        let mut patches = order_patches_by_chain(regular);
+        patches.extend(cumulative);
 
         release_entries.push(ReleaseIndexEntry {
             name: release.name.clone(),
@@ -244,32 +262,36 @@ pub async fn publish_index(api: &(dyn ApiProvider + Send + Sync)) -> Result<()>
     Ok(())
 }
 
-/// Build the manifest URL for a main repo, provider-specific.
-fn manifest_url_for(api: &(dyn ApiProvider + Send + Sync), main: &Project) -> String {
+/// Build the manifest URL for a main repo on `branch`, provider-specific.
+fn manifest_url_for(api: &(dyn ApiProvider + Send + Sync), main: &Project, branch: &str) -> String {
     if api.is_suppot_subgroups() {
         // GitLab: raw file endpoint.
         format!(
-            "{}/projects/{}/repository/files/{}/raw?ref=master",
-            GITLAB_API_HOST, main.id, MANIFEST_NAME,
+            "{}/projects/{}/repository/files/{}/raw?ref={}",
+            profile().gitlab.api_host, main.id, MANIFEST_NAME, urlencoding::encode(branch),
+        )
+    } else if api.id() == GITEA_PID {
+        // Gitea/Forgejo: web raw endpoint of the mirror.
+        format!(
+            "{}/{}/{}/raw/branch/{}/{}",
+            profile().gitea.host.trim_end_matches('/'), profile().gitea.org, main.name, branch, MANIFEST_NAME,
         )
     } else {
         // GitHub: raw.githubusercontent via github.com/raw redirect.
         format!(
-            "{}/{}/{}/raw/master/{}",
-            GITHUB_HOST, GITHUB_ORG, main.name, MANIFEST_NAME,
+            "{}/{}/{}/raw/{}/{}",
+            profile().github.web_host, profile().github.org, main.name, branch, MANIFEST_NAME,
         )
     }
 }
 
-/// Download a patch manifest (CDN URL) and extract the `base_patch` field.
-/// Returns `None` on any error (non-fatal — the writer should not abort).
-async fn extract_base_patch(manifest_url: Option<&str>) -> Option<String> {
-    let url = manifest_url?;
-    let cached = crate::utils::http_cache::fetch(&crate::utils::http_cache::SHARED_CLIENT, url, Duration::from_secs(crate::consts::CACHE_TTL_RAW_FILE_SECS))
-        .await
-        .ok()?;
-    let manifest: ReleaseManifest = serde_json::from_slice(&cached.bytes).ok()?;
-    manifest.base_patch
+/// SHA-256 recorded in the manifest for an archive, if any.
+fn manifest_sha256(manifest: Option<&ReleaseManifest>, file_name: &str) -> Option<String> {
+    manifest?
+        .files
+        .iter()
+        .find(|f| f.name == file_name)
+        .and_then(|f| f.sha256.clone())
 }
 
 /// Choose the correct `project_id` argument for provider API calls.
@@ -285,7 +307,7 @@ fn project_id_for_api(api: &(dyn ApiProvider + Send + Sync), project: &Project)
 /// Download a release manifest (raw URL) and extract size fields + exe_path.
 /// Returns `None` on any error (non-fatal — the index will just have 0 sizes).
 async fn extract_manifest_info(manifest_url: &str) -> Option<ReleaseManifest> {
-    let cached = crate::utils::http_cache::fetch(&crate::utils::http_cache::SHARED_CLIENT, manifest_url, Duration::from_secs(crate::consts::CACHE_TTL_RAW_FILE_SECS))
+    let cached = crate::utils::http_cache::fetch(&crate::utils::http_cache::shared_client().ok()?, manifest_url, Duration::from_secs(crate::consts::CACHE_TTL_RAW_FILE_SECS))
         .await
         .ok()?;
     serde_json::from_slice(&cached.bytes).ok()
@@ -293,7 +315,7 @@ async fn extract_manifest_info(manifest_url: &str) -> Option<ReleaseManifest> {
 
 /// HEAD the launcher bg URL to capture its current ETag for the index.
 async fn fetch_bg_etag(bg_url: &str) -> Option<String> {
-    let resp = crate::utils::http_cache::SHARED_CLIENT.head(bg_url).send().await.ok()?;
+    let resp = crate::utils::http_cache::shared_client().ok()?.head(bg_url).send().await.ok()?;
     resp.headers()
         .get(reqwest::header::ETAG)
         .and_then(|v| v.to_str().ok())
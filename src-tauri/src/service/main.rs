@@ -1,13 +1,19 @@
 use std::{collections::HashMap, sync::Arc};
 
 use crate::{
-  configs::AppConfig::{AppConfig, Version},
-  consts::{GITHUB_API_HOST, GITLAB_API_HOST},
+  configs::{
+    AppConfig::{AppConfig, Version},
+    DistributionProfile::profile,
+  },
+  consts::{GITEA_PID, GITHUB_PID, GITLAB_PID, LOCAL_PID, MIRROR_PID},
   providers::{
     ApiClient::ApiClient::ApiClient,
     ApiProvider::ApiProvider,
+    Gitea::Gitea::Gitea,
     Github::Github::Github,
     Gitlab::Gitlab::Gitlab,
+    Local::Local::Local,
+    Mirror::Mirror::Mirror,
     dto::{ProviderStatus, Release},
   },
 };
@@ -37,8 +43,20 @@ impl Service {
   }
 
   pub async fn register_all_providers(&mut self) -> Result<()> {
-    self.register_github();
-    self.register_gitlab();
+    let local_path = { self.config.lock().await.local_provider_path.clone() };
+    // One broken provider (e.g. its client cannot be built) must not hide the others.
+    let registered = [
+      (GITHUB_PID, self.register_github()),
+      (GITLAB_PID, self.register_gitlab()),
+      (GITEA_PID, self.register_gitea()),
+      (MIRROR_PID, self.register_mirror()),
+      (LOCAL_PID, self.register_local(local_path.as_deref())),
+    ];
+    for (id, res) in registered {
+      if let Err(e) = res {
+        log::error!("Cannot register provider '{}': {:#}", id, e);
+      }
+    }
 
     self.stats = self.api_client.ping_all().await;
 
@@ -103,7 +121,7 @@ impl Service {
   }
 
   fn register_github(&mut self) -> Result<()> {
-    let github = Github::new(GITHUB_API_HOST, false, self.logger.clone())?;
+    let github = Github::new(&profile().github, false, self.logger.clone())?;
     let github_id = github.id();
 
     log::info!("Register provider: {}", github_id);
@@ -113,13 +131,61 @@ impl Service {
     Ok(())
   }
   fn register_gitlab(&mut self) -> Result<()> {
-    let gitlab = Gitlab::new(GITLAB_API_HOST, true, self.logger.clone())?;
+    let gitlab = Gitlab::new(&profile().gitlab, true, self.logger.clone())?;
     let gitlab_id = gitlab.id();
 
     log::info!("Register provider: {}", gitlab_id);
 
     self.api_client.register_provider(gitlab);
 
+    Ok(())
+  }
+  fn register_gitea(&mut self) -> Result<()> {
+    if profile().gitea.host.is_empty() {
+      log::info!("Gitea host is not configured, skip provider registration");
+      return Ok(());
+    }
+
+    let gitea = Gitea::new(&profile().gitea, false, self.logger.clone())?;
+    let gitea_id = gitea.id();
+
+    log::info!("Register provider: {}", gitea_id);
+
+    self.api_client.register_provider(gitea);
+
+    Ok(())
+  }
+  fn register_mirror(&mut self) -> Result<()> {
+    if profile().mirror.base_url.is_empty() {
+      log::info!("Static mirror is not configured, skip provider registration");
+      return Ok(());
+    }
+
+    let mirror = Mirror::new(&profile().mirror.base_url, self.logger.clone())?;
+    let mirror_id = mirror.id();
+
+    log::info!("Register provider: {}", mirror_id);
+
+    self.api_client.register_provider(mirror);
+
+    Ok(())
+  }
+  /// (Re)registers the local / LAN provider for `path`; `None` removes it.
+  /// Also called at runtime when the user changes the directory.
+  pub fn register_local(&mut self, path: Option<&str>) -> Result<()> {
+    self.api_client.unregister_provider(LOCAL_PID);
+
+    let Some(path) = path.filter(|p| !p.is_empty()) else {
+      return Ok(());
+    };
+
+    let local = Local::new(std::path::Path::new(path), self.logger.clone())?;
+    let local_id = local.id();
+
+    log::info!("Register provider: {}, root: {}", local_id, path);
+
+    self.api_client.register_provider(local);
+
     Ok(())
   }
 }
@@ -1,12 +1,25 @@
+pub mod bandwidth;
+pub mod bundle;
 pub mod client;
 pub mod create_release;
+pub mod disk_space;
+pub mod download_jobs;
+pub mod download_pipeline;
 pub mod dto;
+pub mod failover;
 pub mod files;
 pub mod get_release;
+pub mod health_monitor;
 pub mod index;
 pub mod index_publisher;
 pub mod keybind_manager;
 pub mod main;
+pub mod part_file;
+pub mod patch_backup;
+pub mod patch_delta;
+pub mod retry;
+pub mod staging;
 pub mod unpack;
 pub mod updater;
+pub mod verify;
 pub mod wake_detector;
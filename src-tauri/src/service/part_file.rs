@@ -0,0 +1,168 @@
+// `.part` sidecar of an unfinished download.
+//
+// A single-stream download stores the byte offset as a plain number (the
+// original format, still written for small files and servers without Range
+// support). A segmented download stores JSON with one resume offset per byte
+// range, so every connection picks up where it stopped after a kill.
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// One byte range `[start, end)` of the file; `done` bytes from `start` are on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+  pub start: u64,
+  pub end: u64,
+  pub done: u64,
+}
+
+impl Segment {
+  pub fn offset(&self) -> u64 {
+    self.start + self.done
+  }
+
+  pub fn remaining(&self) -> u64 {
+    self.end - self.offset()
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentPlan {
+  /// File size the plan was made for; a different size invalidates it.
+  pub total: u64,
+  pub segments: Vec<Segment>,
+}
+
+impl SegmentPlan {
+  /// Splits `total` bytes into `count` nearly equal ranges.
+  pub fn split(total: u64, count: usize) -> Self {
+    let count = (count.max(1) as u64).min(total.max(1));
+    let step = total / count;
+    let segments = (0..count)
+      .map(|i| Segment {
+        start: i * step,
+        end: if i + 1 == count { total } else { (i + 1) * step },
+        done: 0,
+      })
+      .collect();
+
+    Self { total, segments }
+  }
+
+  pub fn downloaded(&self) -> u64 {
+    self.segments.iter().map(|s| s.done).sum()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub enum PartFile {
+  Offset(u64),
+  Segmented(SegmentPlan),
+}
+
+impl PartFile {
+  pub fn parse(content: &str) -> Option<Self> {
+    let content = content.trim();
+    if let Ok(offset) = content.parse::<u64>() {
+      return Some(Self::Offset(offset));
+    }
+    serde_json::from_str(content).ok().map(Self::Segmented)
+  }
+
+  pub fn downloaded(&self) -> u64 {
+    match self {
+      Self::Offset(offset) => *offset,
+      Self::Segmented(plan) => plan.downloaded(),
+    }
+  }
+}
+
+pub fn read(path: impl AsRef<Path>) -> Option<PartFile> {
+  std::fs::read_to_string(path).ok().and_then(|s| PartFile::parse(&s))
+}
+
+/// Bytes already on disk according to the sidecar, None when there is none.
+pub fn downloaded_bytes(path: impl AsRef<Path>) -> Option<u64> {
+  read(path).map(|p| p.downloaded())
+}
+
+pub async fn save_offset(path: &str, downloaded: u64) -> Result<()> {
+  // Write number as string — robust and easy to debug.
+  tokio::fs::write(path, downloaded.to_string().as_bytes()).await?;
+  Ok(())
+}
+
+pub async fn save_plan(path: &str, plan: &SegmentPlan) -> Result<()> {
+  tokio::fs::write(path, serde_json::to_vec(plan)?).await?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ranges(plan: &SegmentPlan) -> Vec<(u64, u64)> {
+    plan.segments.iter().map(|s| (s.start, s.end)).collect()
+  }
+
+  #[test]
+  fn split_gives_the_remainder_to_the_last_segment() {
+    let plan = SegmentPlan::split(10, 3);
+    assert_eq!(ranges(&plan), vec![(0, 3), (3, 6), (6, 10)]);
+    assert_eq!(plan.total, 10);
+    assert_eq!(plan.downloaded(), 0);
+  }
+
+  #[test]
+  fn split_never_makes_empty_segments() {
+    assert_eq!(ranges(&SegmentPlan::split(2, 4)), vec![(0, 1), (1, 2)]);
+    assert_eq!(ranges(&SegmentPlan::split(100, 0)), vec![(0, 100)]);
+    assert_eq!(ranges(&SegmentPlan::split(0, 4)), vec![(0, 0)]);
+  }
+
+  #[test]
+  fn parse_plain_offset() {
+    assert!(matches!(PartFile::parse("1234\n"), Some(PartFile::Offset(1234))));
+    assert_eq!(PartFile::parse(" 0 ").unwrap().downloaded(), 0);
+  }
+
+  #[test]
+  fn parse_resumes_a_partly_written_plan() {
+    let mut plan = SegmentPlan::split(1000, 4);
+    plan.segments[0].done = 250;
+    plan.segments[2].done = 100;
+    let content = serde_json::to_string(&plan).unwrap();
+
+    let Some(PartFile::Segmented(resumed)) = PartFile::parse(&content) else {
+      panic!("expected a segment plan");
+    };
+    assert_eq!(resumed.total, 1000);
+    assert_eq!(resumed.downloaded(), 350);
+    let remaining: Vec<(u64, u64)> = resumed.segments.iter().map(|s| (s.offset(), s.remaining())).collect();
+    assert_eq!(remaining, vec![(250, 0), (250, 250), (600, 150), (750, 250)]);
+  }
+
+  #[test]
+  fn parse_rejects_a_corrupt_part_file() {
+    assert!(PartFile::parse("").is_none());
+    assert!(PartFile::parse("-5").is_none());
+    assert!(PartFile::parse(r#"{"total":1000,"segments":[{"start":0,"end":500"#).is_none());
+    assert!(PartFile::parse(r#"{"total":1000}"#).is_none());
+  }
+
+  #[test]
+  fn read_missing_or_corrupt_file_is_none() {
+    let path = std::env::temp_dir().join(format!("gw-part-test-{}.part", uuid::Uuid::new_v4()));
+    assert!(read(&path).is_none());
+
+    std::fs::write(&path, "not a plan").unwrap();
+    assert!(read(&path).is_none());
+    assert!(downloaded_bytes(&path).is_none());
+
+    std::fs::write(&path, "4096").unwrap();
+    assert_eq!(downloaded_bytes(&path), Some(4096));
+    let _ = std::fs::remove_file(&path);
+  }
+}
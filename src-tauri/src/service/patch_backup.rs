@@ -0,0 +1,248 @@
+// Backups for patch uninstall.
+//
+// Before a patch is applied, every game file it is going to overwrite (a file
+// of its archives) or delete (`deleted_files`) is copied to
+// `appdata/patches/<patch>/`, and the files it is going to create are listed.
+// The lists go into the patch marker, so the last installed patch can be taken
+// off again: the added files are removed and the saved copies moved back.
+
+use std::{
+  collections::BTreeSet,
+  fs,
+  path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::{
+  service::{disk_space::SpacePlan, unpack::archive_file_names},
+  utils::{
+    delta::DELTA_DIR,
+    patch_markers::{PatchBackup, patch_backup_dir},
+  },
+};
+
+fn normalize(path: &str) -> Option<String> {
+  let path = path.replace('\\', "/").trim_start_matches('/').to_owned();
+  (!path.is_empty() && !path.contains("..")).then_some(path)
+}
+
+/// Saves the files of `installed` that the patch archives and `deleted` will
+/// replace or remove (delta targets are passed in `deleted`, the unpacked
+/// deltas themselves are not game files). Blocking.
+pub fn create(installed: &Path, patch_name: &str, archives: &[PathBuf], deleted: &[String]) -> Result<PatchBackup> {
+  let dir = patch_backup_dir(installed, patch_name)?;
+  // Leftover of an install that failed before its marker was written.
+  discard(installed, patch_name)?;
+
+  let delta_prefix = format!("{}/", DELTA_DIR);
+  let mut unpacked: BTreeSet<String> = BTreeSet::new();
+  for archive in archives {
+    let names = archive_file_names(archive).map_err(|e| anyhow!("Cannot read {:?}: {}", archive, e))?;
+    unpacked.extend(names.iter().filter_map(|n| normalize(n)).filter(|n| !n.starts_with(&delta_prefix)));
+  }
+  let removed: BTreeSet<String> = deleted.iter().filter_map(|p| normalize(p)).collect();
+
+  let mut backup = PatchBackup::default();
+  let mut size = 0u64;
+  for rel in unpacked.union(&removed) {
+    match fs::metadata(installed.join(rel)) {
+      Ok(meta) if meta.is_file() => {
+        size += meta.len();
+        backup.backed_up.push(rel.clone());
+      }
+      Ok(_) => {}
+      Err(_) if unpacked.contains(rel) => backup.added.push(rel.clone()),
+      Err(_) => {}
+    }
+  }
+
+  let mut space = SpacePlan::default();
+  space.add(&dir, size)?;
+  space.ensure().map_err(|e| anyhow!(e))?;
+
+  let copied = backup.backed_up.iter().try_for_each(|rel| -> Result<()> {
+    let saved = dir.join(rel);
+    if let Some(parent) = saved.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::copy(installed.join(rel), &saved).with_context(|| format!("Cannot back up '{}'", rel))?;
+    Ok(())
+  });
+  if let Err(e) = copied {
+    let _ = discard(installed, patch_name);
+    return Err(e);
+  }
+
+  log::info!(
+    "Backed up {} files ({} bytes) for patch '{}', {} files will be added",
+    backup.backed_up.len(),
+    size,
+    patch_name,
+    backup.added.len()
+  );
+  Ok(backup)
+}
+
+/// Puts `installed` back into the state before the patch: removes the added
+/// files, moves the saved copies back and drops the backup store. Can be run
+/// again after an interruption. Blocking.
+pub fn restore(installed: &Path, patch_name: &str, backup: &PatchBackup) -> Result<()> {
+  let dir = patch_backup_dir(installed, patch_name)?;
+
+  for rel in &backup.added {
+    match fs::remove_file(installed.join(rel)) {
+      Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e).with_context(|| format!("Cannot remove '{}'", rel)),
+      _ => {}
+    }
+  }
+
+  for rel in &backup.backed_up {
+    let saved = dir.join(rel);
+    if !saved.exists() {
+      log::warn!("Backup of '{}' for patch '{}' is missing, skipped", rel, patch_name);
+      continue;
+    }
+    let target = installed.join(rel);
+    if let Some(parent) = target.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::rename(&saved, &target).with_context(|| format!("Cannot restore '{}'", rel))?;
+  }
+
+  discard(installed, patch_name)?;
+  log::info!(
+    "Restored {} files and removed {} files of patch '{}'",
+    backup.backed_up.len(),
+    backup.added.len(),
+    patch_name
+  );
+  Ok(())
+}
+
+/// Drops the backup store of a patch.
+pub fn discard(installed: &Path, patch_name: &str) -> Result<()> {
+  let dir = patch_backup_dir(installed, patch_name)?;
+  match fs::remove_dir_all(&dir) {
+    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+    _ => Ok(()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::Write;
+
+  use uuid::Uuid;
+  use zip::{ZipWriter, write::FileOptions};
+
+  use super::*;
+
+  struct TempDir(PathBuf);
+
+  impl TempDir {
+    fn new() -> Self {
+      let dir = std::env::temp_dir().join(format!("gw-backup-test-{}", Uuid::new_v4()));
+      fs::create_dir_all(&dir).unwrap();
+      Self(dir)
+    }
+
+    fn write(&self, rel: &str, content: &str) {
+      let path = self.0.join(rel);
+      fs::create_dir_all(path.parent().unwrap()).unwrap();
+      fs::write(path, content).unwrap();
+    }
+
+    fn read(&self, rel: &str) -> Option<String> {
+      fs::read_to_string(self.0.join(rel)).ok()
+    }
+  }
+
+  impl Drop for TempDir {
+    fn drop(&mut self) {
+      let _ = fs::remove_dir_all(&self.0);
+    }
+  }
+
+  fn archive(path: &Path, names: &[&str]) {
+    let mut zip = ZipWriter::new(fs::File::create(path).unwrap());
+    for name in names {
+      zip.start_file(*name, FileOptions::<'_, ()>::default()).unwrap();
+      zip.write_all(b"new").unwrap();
+    }
+    zip.finish().unwrap();
+  }
+
+  #[test]
+  fn normalize_rejects_escapes() {
+    assert_eq!(normalize("\\bin\\game.exe").as_deref(), Some("bin/game.exe"));
+    assert_eq!(normalize("/data/a.txt").as_deref(), Some("data/a.txt"));
+    assert_eq!(normalize("../outside.txt"), None);
+    assert_eq!(normalize("/"), None);
+  }
+
+  #[test]
+  fn create_then_restore_puts_the_install_back() {
+    let game = TempDir::new();
+    let work = TempDir::new();
+    game.write("bin/game.exe", "old exe");
+    game.write("data/keep.txt", "untouched");
+    game.write("data/deleted.txt", "deleted by the patch");
+
+    let zip = work.0.join("data0.zip");
+    archive(&zip, &["bin/game.exe", "data/new.txt", ".deltas/data/keep.txt.delta"]);
+    let deleted = vec!["data/deleted.txt".to_owned(), "missing.txt".to_owned(), "../evil.txt".to_owned()];
+
+    let backup = create(&game.0, "p1", &[zip], &deleted).unwrap();
+    assert_eq!(backup.backed_up, vec!["bin/game.exe", "data/deleted.txt"]);
+    assert_eq!(backup.added, vec!["data/new.txt"]);
+    let store = patch_backup_dir(&game.0, "p1").unwrap();
+    assert_eq!(fs::read_to_string(store.join("bin/game.exe")).unwrap(), "old exe");
+
+    // What applying the patch does.
+    game.write("bin/game.exe", "new");
+    game.write("data/new.txt", "new");
+    fs::remove_file(game.0.join("data/deleted.txt")).unwrap();
+
+    restore(&game.0, "p1", &backup).unwrap();
+    assert_eq!(game.read("bin/game.exe").as_deref(), Some("old exe"));
+    assert_eq!(game.read("data/deleted.txt").as_deref(), Some("deleted by the patch"));
+    assert_eq!(game.read("data/keep.txt").as_deref(), Some("untouched"));
+    assert_eq!(game.read("data/new.txt"), None);
+    assert!(!store.exists());
+  }
+
+  #[test]
+  fn restore_can_run_again_after_an_interruption() {
+    let game = TempDir::new();
+    let work = TempDir::new();
+    game.write("a.txt", "old");
+    let zip = work.0.join("data0.zip");
+    archive(&zip, &["a.txt", "b.txt"]);
+
+    let backup = create(&game.0, "p1", &[zip], &[]).unwrap();
+    game.write("a.txt", "new");
+    game.write("b.txt", "new");
+
+    restore(&game.0, "p1", &backup).unwrap();
+    restore(&game.0, "p1", &backup).unwrap();
+    assert_eq!(game.read("a.txt").as_deref(), Some("old"));
+    assert_eq!(game.read("b.txt"), None);
+  }
+
+  #[test]
+  fn create_drops_a_leftover_store() {
+    let game = TempDir::new();
+    let work = TempDir::new();
+    let store = patch_backup_dir(&game.0, "p1").unwrap();
+    fs::create_dir_all(&store).unwrap();
+    fs::write(store.join("stale.txt"), "from a failed install").unwrap();
+
+    let zip = work.0.join("data0.zip");
+    archive(&zip, &["b.txt"]);
+    let backup = create(&game.0, "p1", &[zip], &[]).unwrap();
+
+    assert!(backup.backed_up.is_empty());
+    assert!(!store.join("stale.txt").exists());
+  }
+}
@@ -0,0 +1,119 @@
+// Install side of binary delta patches.
+//
+// The data archives of a delta patch unpack the deltas into
+// `<install>/.deltas/`. Each one is applied only when the local file is the
+// exact base it was made against, and the result must match the patched
+// hash; otherwise the whole file is taken from the `fullN.zip` archive named
+// in the manifest, which is downloaded only for such installs (up front when
+// the base differs, while applying when a result turns out wrong).
+
+use std::{
+  collections::{BTreeMap, HashSet},
+  fs,
+  path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow};
+
+use crate::{
+  handlers::dto::PatchDelta,
+  service::unpack::ServiceUnpacker,
+  utils::{
+    delta::{self, DELTA_DIR},
+    hash::sha256_file,
+  },
+};
+
+fn base_matches(file: &Path, d: &PatchDelta) -> bool {
+  match fs::metadata(file) {
+    Ok(meta) if meta.is_file() && meta.len() == d.base_size => sha256_file(file).is_ok_and(|h| h.eq_ignore_ascii_case(&d.base_sha256)),
+    _ => false,
+  }
+}
+
+/// Deltas whose local base differs, so their full copy is needed. `shipped`
+/// are paths an earlier patch of the same run rewrites; their base is only
+/// known once that patch is applied and is checked then. Blocking.
+pub fn stale<'a>(installed: &Path, deltas: &'a [PatchDelta], shipped: &HashSet<String>) -> Vec<&'a PatchDelta> {
+  deltas
+    .iter()
+    .filter(|d| !shipped.contains(&d.path) && !base_matches(&installed.join(&d.path), d))
+    .collect()
+}
+
+/// Applies the deltas unpacked into `<installed>/.deltas/` and returns the
+/// ones that cannot be applied: the local base differs, or the result does
+/// not match the patched hash, so the whole file is needed. Blocking.
+pub fn apply(installed: &Path, deltas: &[PatchDelta]) -> Vec<PatchDelta> {
+  let delta_dir = installed.join(DELTA_DIR);
+  let mut from_full = vec![];
+
+  for d in deltas {
+    let target = installed.join(&d.path);
+    if !base_matches(&target, d) {
+      from_full.push(d.clone());
+      continue;
+    }
+
+    let name = target.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let patched = target.with_file_name(format!(".{}.patched", name));
+    let result = delta::decode(&target, &delta_dir.join(&d.path), &patched)
+      .and_then(|_| sha256_file(&patched))
+      .and_then(|hash| match hash.eq_ignore_ascii_case(&d.sha256) {
+        true => Ok(()),
+        false => Err(anyhow!("hash mismatch after applying the delta")),
+      })
+      .and_then(|()| fs::rename(&patched, &target).map_err(Into::into));
+    if let Err(e) = result {
+      log::warn!("Delta of '{}' failed, using the full file: {}", &d.path, e);
+      let _ = fs::remove_file(&patched);
+      from_full.push(d.clone());
+    }
+  }
+  from_full
+}
+
+/// The `fullN.zip` archives `from_full` needs that are not in `full_archives`.
+pub fn missing_archives(from_full: &[PatchDelta], full_archives: &[PathBuf]) -> Vec<String> {
+  let mut missing: Vec<String> = from_full
+    .iter()
+    .map(|d| d.archive.clone())
+    .filter(|archive| !archive.is_empty() && !full_archives.iter().any(|p| p.file_name().is_some_and(|n| n == archive.as_str())))
+    .collect();
+  missing.sort();
+  missing.dedup();
+  missing
+}
+
+/// Extracts the files of `from_full` whole from `full_archives` and removes
+/// the unpacked deltas. Blocking.
+pub fn apply_full(unpack: &ServiceUnpacker, version_name: &str, installed: &Path, from_full: &[PatchDelta], full_archives: &[PathBuf]) -> Result<()> {
+  let mut by_archive: BTreeMap<&str, Vec<&PatchDelta>> = BTreeMap::new();
+  for d in from_full {
+    by_archive.entry(d.archive.as_str()).or_default().push(d);
+  }
+
+  for (archive, files) in &by_archive {
+    let path = full_archives
+      .iter()
+      .find(|p| !archive.is_empty() && p.file_name().is_some_and(|n| n == *archive))
+      .ok_or_else(|| anyhow!("'{}' differs from the patch base and its full copy was not downloaded", files[0].path))?;
+    let names: Vec<String> = files.iter().map(|d| d.path.clone()).collect();
+    unpack
+      .extract_zip_entries(version_name, archive, path, installed, &names)
+      .map_err(|e| anyhow!("Extract failed: {}", e))?;
+    for d in files {
+      let hash = sha256_file(&installed.join(&d.path))?;
+      if !hash.eq_ignore_ascii_case(&d.sha256) {
+        return Err(anyhow!("'{}' from {} does not match the patch manifest", d.path, archive));
+      }
+    }
+  }
+
+  let delta_dir = installed.join(DELTA_DIR);
+  if let Err(e) = fs::remove_dir_all(&delta_dir) {
+    log::warn!("Cannot remove {:?}: {}", delta_dir, e);
+  }
+  log::info!("Deltas applied to {:?}, {} files taken in full", installed, from_full.len());
+  Ok(())
+}
@@ -0,0 +1,340 @@
+// Retry policy of download workers (release files and patch archives).
+//
+// A failed attempt is classified by its error chain: a 404 will not heal by
+// itself, a 503 or a dropped connection usually does. Each class has its own
+// attempt limit; the pause between attempts grows exponentially with random
+// jitter (so the workers of one job do not reconnect in lockstep) unless the
+// server sent `Retry-After`.
+
+use std::{
+  collections::HashMap,
+  sync::{
+    LazyLock,
+    atomic::{AtomicU64, Ordering},
+  },
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::consts::FAILOVER_AFTER_RETRIES;
+use crate::utils::errors::{HttpStatusError, StallError};
+
+/// Upper bound of a computed backoff; `Retry-After` may ask for longer.
+const MAX_BACKOFF: Duration = Duration::from_secs(120);
+/// Longest `Retry-After` honoured; a longer one is cut to this.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+  /// HTTP 4xx (except 408/429), disk full: retrying the same host is pointless.
+  Permanent,
+  /// HTTP 429.
+  RateLimited,
+  /// HTTP 5xx, 408.
+  Server,
+  Timeout,
+  /// Stream delivered no bytes for `DOWNLOAD_STALL_TIMEOUT_SECS`.
+  Stalled,
+  /// Connection refused / reset, body cut off.
+  Connection,
+  Dns,
+  Tls,
+  /// Anything else (hash mismatch, local I/O).
+  Other,
+}
+
+impl ErrorClass {
+  /// Attempts after the first one before the file is given up.
+  pub fn max_retries(self) -> u32 {
+    match self {
+      ErrorClass::Permanent => 0,
+      ErrorClass::Tls => 1,
+      ErrorClass::Other => 3,
+      ErrorClass::Server | ErrorClass::Dns => 5,
+      ErrorClass::RateLimited => 6,
+      ErrorClass::Timeout | ErrorClass::Stalled | ErrorClass::Connection => 10,
+    }
+  }
+
+  fn base_delay(self) -> Duration {
+    match self {
+      ErrorClass::Stalled => Duration::from_millis(500),
+      ErrorClass::Timeout | ErrorClass::Connection | ErrorClass::Other => Duration::from_secs(1),
+      ErrorClass::Server | ErrorClass::Tls => Duration::from_secs(2),
+      ErrorClass::Dns | ErrorClass::RateLimited => Duration::from_secs(5),
+      ErrorClass::Permanent => Duration::ZERO,
+    }
+  }
+}
+
+/// Class of a failed attempt and the server's `Retry-After`, if any.
+pub fn classify(e: &anyhow::Error) -> (ErrorClass, Option<Duration>) {
+  for cause in e.chain() {
+    if let Some(http) = cause.downcast_ref::<HttpStatusError>() {
+      let status = http.status.as_u16();
+      let class = match status {
+        429 => ErrorClass::RateLimited,
+        408 => ErrorClass::Server,
+        500..=599 => ErrorClass::Server,
+        _ => ErrorClass::Permanent,
+      };
+      return (class, http.retry_after);
+    }
+    if cause.downcast_ref::<StallError>().is_some() {
+      return (ErrorClass::Stalled, None);
+    }
+    if let Some(req) = cause.downcast_ref::<reqwest::Error>() {
+      return (classify_reqwest(req), None);
+    }
+    if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+      use std::io::ErrorKind::*;
+      let class = match io.kind() {
+        TimedOut => ErrorClass::Timeout,
+        ConnectionReset | ConnectionAborted | ConnectionRefused | BrokenPipe | UnexpectedEof => ErrorClass::Connection,
+        StorageFull | PermissionDenied | ReadOnlyFilesystem => ErrorClass::Permanent,
+        _ => continue,
+      };
+      return (class, None);
+    }
+  }
+  (ErrorClass::Other, None)
+}
+
+fn classify_reqwest(e: &reqwest::Error) -> ErrorClass {
+  if e.is_timeout() {
+    return ErrorClass::Timeout;
+  }
+  if let Some(status) = e.status() {
+    return if status.is_server_error() {
+      ErrorClass::Server
+    } else {
+      ErrorClass::Permanent
+    };
+  }
+
+  // reqwest folds resolver and handshake failures into "connect" errors; the
+  // underlying cause is only visible in the message of the source chain.
+  let mut sources = String::new();
+  let mut source = std::error::Error::source(e);
+  while let Some(s) = source {
+    sources.push_str(&s.to_string().to_lowercase());
+    sources.push(' ');
+    source = s.source();
+  }
+  if ["dns", "resolve", "lookup", "name or service not known", "no such host"]
+    .iter()
+    .any(|m| sources.contains(m))
+  {
+    ErrorClass::Dns
+  } else if ["certificate", "tls", "ssl", "handshake"].iter().any(|m| sources.contains(m)) {
+    ErrorClass::Tls
+  } else {
+    ErrorClass::Connection
+  }
+}
+
+/// Failed attempts of one file: all of them (when to fail over to another
+/// provider) and per class (attempt limit and backoff of that class).
+#[derive(Debug, Default)]
+pub struct RetryCounter {
+  total: u32,
+  per_class: HashMap<ErrorClass, u32>,
+}
+
+/// A counted failed attempt.
+#[derive(Debug)]
+pub struct Failure {
+  pub class: ErrorClass,
+  /// Failed attempts of this class so far.
+  pub attempt: u32,
+  /// Failed attempts of any class so far.
+  pub total: u32,
+  /// Pause before the next attempt; `None` once the class is out of attempts.
+  pub delay: Option<Duration>,
+}
+
+impl Failure {
+  /// Another provider should take over instead of the same host again.
+  pub fn should_fail_over(&self) -> bool {
+    self.total >= FAILOVER_AFTER_RETRIES || self.class == ErrorClass::Permanent
+  }
+}
+
+impl RetryCounter {
+  pub fn fail(&mut self, e: &anyhow::Error) -> Failure {
+    let (class, retry_after) = classify(e);
+    self.total += 1;
+    let attempt = self.per_class.entry(class).or_insert(0);
+    *attempt += 1;
+    let attempt = *attempt;
+    Failure {
+      class,
+      attempt,
+      total: self.total,
+      delay: (attempt <= class.max_retries()).then(|| backoff(class, attempt, retry_after)),
+    }
+  }
+
+  /// After a success or a switch to another provider.
+  pub fn reset(&mut self) {
+    self.total = 0;
+    self.per_class.clear();
+  }
+}
+
+/// Pause before attempt `attempt` (1-based): `Retry-After` when the server
+/// sent one, otherwise `base * 2^(attempt-1)` capped, with "equal jitter"
+/// (half fixed, half random).
+pub fn backoff(class: ErrorClass, attempt: u32, retry_after: Option<Duration>) -> Duration {
+  if let Some(wait) = retry_after {
+    return wait.min(MAX_RETRY_AFTER);
+  }
+  let exp = class
+    .base_delay()
+    .saturating_mul(1u32 << attempt.saturating_sub(1).min(16))
+    .min(MAX_BACKOFF);
+  let half = exp / 2;
+  half + half.mul_f64(random_unit())
+}
+
+/// Jitter source shared by all workers, seeded once from the clock (never 0,
+/// xorshift would stay at 0): workers failing together still get different delays.
+static JITTER_STATE: LazyLock<AtomicU64> = LazyLock::new(|| {
+  let nanos = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_nanos() as u64)
+    .unwrap_or_default();
+  AtomicU64::new(nanos | 1)
+});
+
+fn xorshift(mut x: u64) -> u64 {
+  x ^= x << 13;
+  x ^= x >> 7;
+  x ^= x << 17;
+  x
+}
+
+/// Random number in [0, 1) without an RNG dependency (xorshift64).
+fn random_unit() -> f64 {
+  let prev = JITTER_STATE
+    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(xorshift(x)))
+    .unwrap_or_else(|x| x);
+  (xorshift(prev) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::Context;
+
+  use super::*;
+
+  fn http(status: u16, retry_after: Option<u64>) -> anyhow::Error {
+    anyhow::Error::new(HttpStatusError {
+      context: "test".to_owned(),
+      status: reqwest::StatusCode::from_u16(status).unwrap(),
+      retry_after: retry_after.map(Duration::from_secs),
+      body: String::new(),
+    })
+  }
+
+  fn io(kind: std::io::ErrorKind) -> anyhow::Error {
+    anyhow::Error::new(std::io::Error::from(kind))
+  }
+
+  #[test]
+  fn classify_http_statuses() {
+    assert_eq!(classify(&http(429, Some(30))), (ErrorClass::RateLimited, Some(Duration::from_secs(30))));
+    assert_eq!(classify(&http(408, None)), (ErrorClass::Server, None));
+    assert_eq!(classify(&http(500, None)), (ErrorClass::Server, None));
+    assert_eq!(classify(&http(503, Some(5))), (ErrorClass::Server, Some(Duration::from_secs(5))));
+    assert_eq!(classify(&http(403, None)), (ErrorClass::Permanent, None));
+    assert_eq!(classify(&http(404, None)), (ErrorClass::Permanent, None));
+  }
+
+  #[test]
+  fn classify_io_and_stall_errors() {
+    use std::io::ErrorKind::*;
+
+    assert_eq!(classify(&io(TimedOut)).0, ErrorClass::Timeout);
+    for kind in [ConnectionReset, ConnectionAborted, ConnectionRefused, BrokenPipe, UnexpectedEof] {
+      assert_eq!(classify(&io(kind)).0, ErrorClass::Connection, "{:?}", kind);
+    }
+    for kind in [StorageFull, PermissionDenied, ReadOnlyFilesystem] {
+      assert_eq!(classify(&io(kind)).0, ErrorClass::Permanent, "{:?}", kind);
+    }
+    assert_eq!(classify(&io(NotFound)).0, ErrorClass::Other);
+
+    let stall = anyhow::Error::new(StallError {
+      file_name: "data.zip".to_owned(),
+      downloaded: 10,
+    });
+    assert_eq!(classify(&stall), (ErrorClass::Stalled, None));
+    assert_eq!(classify(&anyhow::anyhow!("sha256 mismatch")), (ErrorClass::Other, None));
+  }
+
+  #[test]
+  fn classify_looks_through_the_context_chain() {
+    let e = Err::<(), _>(http(502, None)).context("download data.zip").unwrap_err();
+    assert_eq!(classify(&e).0, ErrorClass::Server);
+
+    let e = Err::<(), _>(std::io::Error::from(std::io::ErrorKind::ConnectionReset))
+      .context("read chunk")
+      .unwrap_err();
+    assert_eq!(classify(&e).0, ErrorClass::Connection);
+  }
+
+  #[test]
+  fn backoff_stays_within_the_jitter_bounds() {
+    for class in [ErrorClass::Stalled, ErrorClass::Connection, ErrorClass::Server, ErrorClass::RateLimited] {
+      for attempt in 1..=20 {
+        let exp = class.base_delay().saturating_mul(1u32 << (attempt - 1).min(16)).min(MAX_BACKOFF);
+        for _ in 0..50 {
+          let delay = backoff(class, attempt, None);
+          assert!(
+            delay >= exp / 2 && delay <= exp,
+            "{:?} attempt {}: {:?} not in [{:?}, {:?}]",
+            class,
+            attempt,
+            delay,
+            exp / 2,
+            exp
+          );
+        }
+      }
+    }
+    assert_eq!(backoff(ErrorClass::Permanent, 1, None), Duration::ZERO);
+  }
+
+  #[test]
+  fn backoff_honours_a_capped_retry_after() {
+    assert_eq!(
+      backoff(ErrorClass::RateLimited, 1, Some(Duration::from_secs(42))),
+      Duration::from_secs(42)
+    );
+    assert_eq!(backoff(ErrorClass::Server, 3, Some(Duration::from_secs(3600))), MAX_RETRY_AFTER);
+  }
+
+  #[test]
+  fn random_unit_is_in_the_unit_interval_and_varies() {
+    let values: Vec<f64> = (0..1000).map(|_| random_unit()).collect();
+    assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+    assert!(values.windows(2).any(|w| w[0] != w[1]));
+  }
+
+  #[test]
+  fn counter_gives_up_after_the_class_limit_and_fails_over() {
+    let mut counter = RetryCounter::default();
+    let e = http(404, None);
+    let failure = counter.fail(&e);
+    assert_eq!(failure.delay, None);
+    assert!(failure.should_fail_over());
+
+    counter.reset();
+    let e = io(std::io::ErrorKind::TimedOut);
+    for attempt in 1..=ErrorClass::Timeout.max_retries() {
+      let failure = counter.fail(&e);
+      assert!(failure.delay.is_some());
+      assert_eq!(failure.should_fail_over(), attempt >= FAILOVER_AFTER_RETRIES);
+    }
+    assert_eq!(counter.fail(&e).delay, None);
+  }
+}
@@ -0,0 +1,286 @@
+// Transactional release install.
+//
+// Archives are extracted into `.<name>.staging` next to the install dir and
+// moved in only after every archive succeeded. The commit moves the top-level
+// entries one by one, parking entries they replace in `.<name>.previous`, so
+// an install dir that holds more than the game (a user-picked folder) is never
+// swapped out as a whole. A journal listing the entries is written first:
+// while it exists the commit is incomplete and can be rolled back, also after
+// a crash (see `recover`).
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::configs::AppConfig::AppConfig;
+
+const STAGING_SUFFIX: &str = ".staging";
+const PREVIOUS_SUFFIX: &str = ".previous";
+const JOURNAL_SUFFIX: &str = ".commit.json";
+
+fn sibling(installed: &Path, suffix: &str) -> PathBuf {
+  let name = installed.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+  installed.with_file_name(format!(".{}{}", name, suffix))
+}
+
+/// Where the archives of `installed` are extracted until the commit.
+pub fn staging_path(installed: &Path) -> PathBuf {
+  sibling(installed, STAGING_SUFFIX)
+}
+
+/// Moves the staged files into `installed`. On failure everything moved so far
+/// is put back and the staging dir is kept, so the commit can be retried.
+pub fn commit(installed: &Path) -> Result<()> {
+  let staging = staging_path(installed);
+  let previous = sibling(installed, PREVIOUS_SUFFIX);
+  let journal = sibling(installed, JOURNAL_SUFFIX);
+
+  let entries: Vec<String> = fs::read_dir(&staging)
+    .with_context(|| format!("Staging dir {:?} is missing", staging))?
+    .flatten()
+    .map(|e| e.file_name().to_string_lossy().into_owned())
+    .collect();
+
+  fs::create_dir_all(installed)?;
+  if previous.exists() {
+    fs::remove_dir_all(&previous)?;
+  }
+  fs::create_dir_all(&previous)?;
+  fs::write(&journal, serde_json::to_vec(&entries)?)?;
+
+  let moved = entries.iter().try_for_each(|name| -> Result<()> {
+    let target = installed.join(name);
+    if target.symlink_metadata().is_ok() {
+      fs::rename(&target, previous.join(name)).with_context(|| format!("Cannot move aside {:?}", target))?;
+    }
+    fs::rename(staging.join(name), &target).with_context(|| format!("Cannot move {:?} into place", target))
+  });
+
+  if let Err(e) = moved {
+    log::error!("Install commit of {:?} failed, rolling back: {}", installed, e);
+    rollback(installed, &entries).map_err(|re| anyhow!("{}; rollback failed too: {}", e, re))?;
+    return Err(e);
+  }
+
+  // The journal is the commit point: without it leftovers are just garbage.
+  fs::remove_file(&journal)?;
+  for dir in [&previous, &staging] {
+    if let Err(e) = fs::remove_dir_all(dir) {
+      log::warn!("Cannot remove {:?} after install: {}", dir, e);
+    }
+  }
+  log::info!("Install of {:?} committed ({} entries)", installed, entries.len());
+  Ok(())
+}
+
+/// Undoes a partial commit of `entries`: new entries go back to staging, the
+/// parked ones back into `installed`.
+fn rollback(installed: &Path, entries: &[String]) -> Result<()> {
+  let staging = staging_path(installed);
+  let previous = sibling(installed, PREVIOUS_SUFFIX);
+
+  for name in entries.iter().rev() {
+    let target = installed.join(name);
+    let staged = staging.join(name);
+    if staged.symlink_metadata().is_err() && target.symlink_metadata().is_ok() {
+      fs::rename(&target, &staged)?;
+    }
+    let parked = previous.join(name);
+    if parked.symlink_metadata().is_ok() {
+      fs::rename(&parked, &target)?;
+    }
+  }
+
+  fs::remove_file(sibling(installed, JOURNAL_SUFFIX))?;
+  let _ = fs::remove_dir_all(&previous);
+  Ok(())
+}
+
+/// Drops the staged files of a cancelled install.
+pub fn discard(installed: &Path) -> Result<()> {
+  let staging = staging_path(installed);
+  match fs::remove_dir_all(&staging) {
+    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+    _ => Ok(()),
+  }
+}
+
+/// Startup cleanup of every known install dir: rolls back a commit cut off by
+/// a crash, removes the parked previous contents and a staging dir no download
+/// will resume into. Only the exact sibling paths of those installs are
+/// touched, never other entries of the parent dir.
+pub fn recover(config: &AppConfig) {
+  let resumable: Vec<PathBuf> = config.progress_download.values().map(|v| PathBuf::from(&v.installed_path)).collect();
+  let installs: Vec<PathBuf> = config
+    .installed_versions
+    .values()
+    .map(|v| &v.installed_path)
+    .chain(config.progress_download.values().map(|v| &v.installed_path))
+    .filter(|p| !p.is_empty())
+    .map(PathBuf::from)
+    .collect();
+
+  recover_installs(&installs, &resumable);
+}
+
+fn recover_installs(installs: &[PathBuf], resumable: &[PathBuf]) {
+  let mut installs = installs.to_vec();
+  installs.sort();
+  installs.dedup();
+
+  for installed in &installs {
+    let journal = sibling(installed, JOURNAL_SUFFIX);
+    if journal.exists() {
+      let entries: Option<Vec<String>> = fs::read(&journal).ok().and_then(|bytes| serde_json::from_slice(&bytes).ok());
+      // Without the entry list the parked contents cannot be put back: keep them for the user.
+      let Some(entries) = entries else {
+        log::error!("Unreadable install journal {:?}, leaving {:?} as it is", journal, installed);
+        continue;
+      };
+      match rollback(installed, &entries) {
+        Ok(()) => log::warn!("Rolled back an interrupted install of {:?}", installed),
+        Err(e) => {
+          log::error!("Cannot roll back the interrupted install of {:?}: {}", installed, e);
+          continue;
+        }
+      }
+    }
+
+    let mut leftovers = vec![sibling(installed, PREVIOUS_SUFFIX)];
+    if !resumable.contains(installed) {
+      leftovers.push(staging_path(installed));
+    }
+    for dir in leftovers.iter().filter(|d| d.is_dir()) {
+      log::info!("Removing leftover install dir {:?}", dir);
+      if let Err(e) = fs::remove_dir_all(dir) {
+        log::warn!("Cannot remove {:?}: {}", dir, e);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use uuid::Uuid;
+
+  use super::*;
+
+  struct TempDir(PathBuf);
+
+  impl TempDir {
+    fn new() -> Self {
+      let dir = std::env::temp_dir().join(format!("gw-staging-test-{}", Uuid::new_v4()));
+      fs::create_dir_all(&dir).unwrap();
+      Self(dir)
+    }
+  }
+
+  impl Drop for TempDir {
+    fn drop(&mut self) {
+      let _ = fs::remove_dir_all(&self.0);
+    }
+  }
+
+  fn write(path: &Path, content: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+  }
+
+  fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap()
+  }
+
+  #[test]
+  fn commit_replaces_staged_entries_and_keeps_the_rest() {
+    let tmp = TempDir::new();
+    let installed = tmp.0.join("game");
+    write(&installed.join("bin/game.exe"), "old");
+    write(&installed.join("saves/1.sav"), "user data");
+    write(&staging_path(&installed).join("bin/game.exe"), "new");
+    write(&staging_path(&installed).join("gamedata/a.db"), "db");
+
+    commit(&installed).unwrap();
+
+    assert_eq!(read(&installed.join("bin/game.exe")), "new");
+    assert_eq!(read(&installed.join("gamedata/a.db")), "db");
+    assert_eq!(read(&installed.join("saves/1.sav")), "user data");
+    for suffix in [STAGING_SUFFIX, PREVIOUS_SUFFIX, JOURNAL_SUFFIX] {
+      assert!(!sibling(&installed, suffix).exists(), "{} left behind", suffix);
+    }
+  }
+
+  #[test]
+  fn commit_without_staging_fails_and_changes_nothing() {
+    let tmp = TempDir::new();
+    let installed = tmp.0.join("game");
+    write(&installed.join("bin/game.exe"), "old");
+
+    assert!(commit(&installed).is_err());
+    assert_eq!(read(&installed.join("bin/game.exe")), "old");
+  }
+
+  /// State of a commit interrupted after `bin` was swapped and before `gamedata` was.
+  fn interrupted_commit(installed: &Path) {
+    write(&installed.join("saves/1.sav"), "user data");
+    write(&installed.join("bin/game.exe"), "new");
+    write(&installed.join("gamedata/a.db"), "old db");
+    write(&sibling(installed, PREVIOUS_SUFFIX).join("bin/game.exe"), "old");
+    write(&staging_path(installed).join("gamedata/a.db"), "new db");
+    write(&sibling(installed, JOURNAL_SUFFIX), r#"["bin","gamedata"]"#);
+  }
+
+  #[test]
+  fn rollback_restores_the_parked_entries() {
+    let tmp = TempDir::new();
+    let installed = tmp.0.join("game");
+    interrupted_commit(&installed);
+
+    rollback(&installed, &["bin".to_owned(), "gamedata".to_owned()]).unwrap();
+
+    assert_eq!(read(&installed.join("bin/game.exe")), "old");
+    assert_eq!(read(&installed.join("gamedata/a.db")), "old db");
+    assert_eq!(read(&installed.join("saves/1.sav")), "user data");
+    // The staged files are back in staging, so the commit can be retried.
+    assert_eq!(read(&staging_path(&installed).join("bin/game.exe")), "new");
+    assert!(!sibling(&installed, JOURNAL_SUFFIX).exists());
+    assert!(!sibling(&installed, PREVIOUS_SUFFIX).exists());
+  }
+
+  #[test]
+  fn recover_rolls_back_and_cleans_only_known_installs() {
+    let tmp = TempDir::new();
+    let installed = tmp.0.join("game");
+    let resumed = tmp.0.join("next");
+    interrupted_commit(&installed);
+    write(&staging_path(&resumed).join("a.db"), "partial");
+    // Look-alikes of another program in the same parent dir.
+    write(&tmp.0.join(".other.previous/keep"), "foreign");
+    write(&tmp.0.join(".other.staging/keep"), "foreign");
+    write(&tmp.0.join(".other.commit.json"), "[]");
+
+    recover_installs(&[installed.clone(), resumed.clone()], std::slice::from_ref(&resumed));
+
+    assert_eq!(read(&installed.join("bin/game.exe")), "old");
+    assert!(!staging_path(&installed).exists());
+    assert!(!sibling(&installed, PREVIOUS_SUFFIX).exists());
+    assert_eq!(read(&staging_path(&resumed).join("a.db")), "partial");
+    assert_eq!(read(&tmp.0.join(".other.previous/keep")), "foreign");
+    assert_eq!(read(&tmp.0.join(".other.staging/keep")), "foreign");
+    assert!(tmp.0.join(".other.commit.json").exists());
+  }
+
+  #[test]
+  fn recover_keeps_parked_entries_of_an_unreadable_journal() {
+    let tmp = TempDir::new();
+    let installed = tmp.0.join("game");
+    interrupted_commit(&installed);
+    write(&sibling(&installed, JOURNAL_SUFFIX), "{not json");
+
+    recover_installs(std::slice::from_ref(&installed), &[]);
+
+    assert_eq!(read(&sibling(&installed, PREVIOUS_SUFFIX).join("bin/game.exe")), "old");
+  }
+}
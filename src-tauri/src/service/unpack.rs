@@ -1,12 +1,45 @@
 use std::fs;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use zip::ZipArchive;
 
 pub type NetSpeedCallback = Box<dyn Fn(&str, &str, usize, usize) + Send + Sync>;
 
+type UnpackError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Threads shared by every archive being unpacked. An archive always runs on
+/// the calling thread and adds idle ones up to its share of the total, so a
+/// lone archive uses all cores and archives unpacked side by side split them.
+/// Never waits: the caller is usually a blocking-pool thread.
+struct ThreadBudget {
+  /// (total, in use, archives being unpacked)
+  state: StdMutex<(usize, usize, usize)>,
+}
+
+impl ThreadBudget {
+  /// `side_by_side` is how many archives the caller is about to unpack at
+  /// once, so the first of them does not take every thread.
+  fn acquire(&self, wanted: usize, side_by_side: usize) -> usize {
+    let mut state = crate::utils::locks::lock(&self.state);
+    state.2 += 1;
+    let share = (state.0 / side_by_side.max(state.2)).max(1);
+    let idle = state.0.saturating_sub(state.1);
+    let taken = wanted.min(share).min(idle).max(1);
+    state.1 += taken;
+    taken
+  }
+
+  fn release(&self, taken: usize) {
+    let mut state = crate::utils::locks::lock(&self.state);
+    state.1 = state.1.saturating_sub(taken);
+    state.2 = state.2.saturating_sub(1);
+  }
+}
+
 pub struct ServiceUnpacker {
   callback: Arc<NetSpeedCallback>,
+  budget: ThreadBudget,
 }
 
 impl ServiceUnpacker {
@@ -16,70 +49,169 @@ impl ServiceUnpacker {
   {
     Self {
       callback: Arc::new(Box::new(callback)),
+      budget: ThreadBudget {
+        state: StdMutex::new((1, 0, 0)),
+      },
     }
   }
 
-  pub fn extract_zip(
+  /// Sets the unpack thread count (`AppConfig::unpack_threads`); applies to archives started afterwards.
+  pub fn set_threads(&self, threads: usize) {
+    let mut state = crate::utils::locks::lock(&self.budget.state);
+    state.0 = threads.max(1);
+  }
+
+  pub fn threads(&self) -> usize {
+    crate::utils::locks::lock(&self.budget.state).0
+  }
+
+  /// Extracts `file_path` into `extract_to`, entries spread over the threads
+  /// free in the budget. Blocks the calling thread.
+  pub fn extract_zip(&self, release_name: &str, file_name: &str, file_path: &Path, extract_to: &Path) -> Result<(), UnpackError> {
+    self.extract_zip_shared(release_name, file_name, file_path, extract_to, 1)
+  }
+
+  /// `extract_zip` for one of `side_by_side` archives unpacked at once; the
+  /// archive gets at most its share of the threads.
+  pub fn extract_zip_shared(
     &self,
     release_name: &str,
     file_name: &str,
     file_path: &Path,
     extract_to: &Path,
-  ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let file = fs::File::open(file_path)?;
-    let mut archive = ZipArchive::new(file)?;
+    side_by_side: usize,
+  ) -> Result<(), UnpackError> {
+    let total_files = ZipArchive::new(fs::File::open(file_path)?)?.len();
 
     if !extract_to.exists() {
       fs::create_dir_all(extract_to)?;
     }
 
-    let total_files = archive.len();
+    (self.callback)(release_name, file_name, 0, total_files);
 
-    for i in 0..total_files {
-      // Вызываем callback перед обработкой файла
-      (self.callback)(release_name, file_name, i, total_files);
+    let threads = self.budget.acquire(total_files, side_by_side);
+    scopeguard::defer! { self.budget.release(threads); };
 
-      let mut file = archive.by_index(i)?;
+    // Every thread reads its own handle; entries are handed out one by one so
+    // a few large files do not leave the other threads idle.
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
 
-      // Ключевое изменение: получаем путь, который безопасен для системы
-      let outpath = match file.enclosed_name() {
-        Some(path) => extract_to.join(path),
-        None => {
-          log::warn!("Пропущен подозрительный файл в архиве: {}", file.name());
-          continue;
+    let worker = || -> Result<(), UnpackError> {
+      let mut archive = ZipArchive::new(fs::File::open(file_path)?)?;
+      loop {
+        let i = next.fetch_add(1, Ordering::SeqCst);
+        if i >= total_files || failed.load(Ordering::SeqCst) {
+          return Ok(());
         }
-      };
-
-      // Используем встроенный метод is_dir() вместо проверки на '/'
-      if file.is_dir() {
-        fs::create_dir_all(&outpath)?;
-      } else {
-        // Убеждаемся, что родительская директория существует
-        if let Some(p) = outpath.parent() {
-          if !p.exists() {
-            fs::create_dir_all(p)?;
-          }
+        if let Err(e) = extract_entry(&mut archive, i, extract_to) {
+          failed.store(true, Ordering::SeqCst);
+          return Err(e);
         }
-
-        // Распаковка файла
-        let mut outfile = fs::File::create(&outpath)?;
-        std::io::copy(&mut file, &mut outfile)?;
+        let count = done.fetch_add(1, Ordering::SeqCst) + 1;
+        (self.callback)(release_name, file_name, count, total_files);
       }
+    };
 
-      // Установка прав доступа для Unix
-      #[cfg(unix)]
-      {
-        use std::os::unix::fs::PermissionsExt;
-        if let Some(mode) = file.unix_mode() {
-          fs::set_permissions(&outpath, fs::Permissions::from_mode(mode))?;
-        }
-      }
-    }
+    let results: Vec<Result<(), UnpackError>> = if threads > 1 {
+      std::thread::scope(|s| {
+        let handles: Vec<_> = (0..threads).map(|_| s.spawn(worker)).collect();
+        handles
+          .into_iter()
+          .map(|h| h.join().unwrap_or_else(|_| Err("unpack thread panicked".into())))
+          .collect()
+      })
+    } else {
+      vec![worker()]
+    };
+    results.into_iter().collect::<Result<Vec<_>, _>>()?;
 
     // Финальный вызов callback
     (self.callback)(release_name, file_name, total_files, total_files);
-    log::info!("Successfully extracted {:?} to {:?}", file_path, extract_to);
+    log::info!("Successfully extracted {:?} to {:?} ({} threads)", file_path, extract_to, threads);
+
+    Ok(())
+  }
+
+  /// Extracts only the entries named in `names` (paths inside the archive).
+  /// Blocks the calling thread; used to repair single files of an install.
+  pub fn extract_zip_entries(
+    &self,
+    release_name: &str,
+    file_name: &str,
+    file_path: &Path,
+    extract_to: &Path,
+    names: &[String],
+  ) -> Result<(), UnpackError> {
+    let mut archive = ZipArchive::new(fs::File::open(file_path)?)?;
+    let indices: Vec<usize> = names.iter().filter_map(|name| archive.index_for_name(name)).collect();
+    if indices.len() < names.len() {
+      log::warn!("{} of the requested entries are not in {:?}", names.len() - indices.len(), file_path);
+    }
 
+    (self.callback)(release_name, file_name, 0, indices.len());
+    for (done, index) in indices.iter().enumerate() {
+      extract_entry(&mut archive, *index, extract_to)?;
+      (self.callback)(release_name, file_name, done + 1, indices.len());
+    }
     Ok(())
   }
 }
+
+/// Paths (`/`-separated) of the files in an archive, without extracting it.
+pub fn archive_file_names(file_path: &Path) -> Result<Vec<String>, UnpackError> {
+  let mut archive = ZipArchive::new(fs::File::open(file_path)?)?;
+  let mut names = Vec::with_capacity(archive.len());
+  for i in 0..archive.len() {
+    let file = archive.by_index_raw(i)?;
+    if file.is_dir() {
+      continue;
+    }
+    if let Some(path) = file.enclosed_name() {
+      names.push(path.to_string_lossy().replace('\\', "/"));
+    }
+  }
+  Ok(names)
+}
+
+fn extract_entry(archive: &mut ZipArchive<fs::File>, index: usize, extract_to: &Path) -> Result<(), UnpackError> {
+  let mut file = archive.by_index(index)?;
+
+  // Ключевое изменение: получаем путь, который безопасен для системы
+  let outpath = match file.enclosed_name() {
+    Some(path) => extract_to.join(path),
+    None => {
+      log::warn!("Пропущен подозрительный файл в архиве: {}", file.name());
+      return Ok(());
+    }
+  };
+
+  // Используем встроенный метод is_dir() вместо проверки на '/'
+  // (create_dir_all tolerates another thread creating the same directory).
+  if file.is_dir() {
+    fs::create_dir_all(&outpath)?;
+  } else {
+    // Убеждаемся, что родительская директория существует
+    if let Some(p) = outpath.parent() {
+      if !p.exists() {
+        fs::create_dir_all(p)?;
+      }
+    }
+
+    // Распаковка файла
+    let mut outfile = fs::File::create(&outpath)?;
+    std::io::copy(&mut file, &mut outfile)?;
+  }
+
+  // Установка прав доступа для Unix
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    if let Some(mode) = file.unix_mode() {
+      fs::set_permissions(&outpath, fs::Permissions::from_mode(mode))?;
+    }
+  }
+
+  Ok(())
+}
@@ -1,7 +1,8 @@
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
-use crate::consts::{BASE_DIR, GITHUB_LAUNCHER_REPO_NAME, MAIN_DEVELOPER_NAME, REPO_LAUNCGER_ID_2};
+use crate::configs::DistributionProfile::profile;
+use crate::consts::BASE_DIR;
 use crate::providers::ApiClient::ApiClient::ApiClient;
 use crate::providers::dto::{ReleaseAssetGit, ReleaseGit, ReleasePlatform};
 use crate::utils::paths::get_exe_name;
@@ -45,8 +46,7 @@ impl ServiceUpdater {
     log::debug!("ServiceUpdater.check, start");
 
     // Try the static release index first (0 API calls).
-    let provider_id = api_client.current_provider()?.id();
-    if let Ok(index) = crate::service::index::load_index(provider_id).await {
+    if let Ok(index) = api_client.current_provider()?.release_index().await {
       log::debug!("ServiceUpdater.check, launcher version from index: {}", &index.launcher.version);
       let current_v = Version::parse(&current_version).unwrap_or(Version::new(0, 0, 0));
       let latest_v = Version::parse(&index.launcher.version).unwrap_or(Version::new(0, 0, 0));
@@ -76,11 +76,11 @@ impl ServiceUpdater {
     let api = api_client.current_provider()?;
 
     let project_id = if api.is_suppot_subgroups() {
-      REPO_LAUNCGER_ID_2.to_string()
+      profile().gitlab.launcher_release_project_id.to_string()
     } else {
-      GITHUB_LAUNCHER_REPO_NAME.to_string()
+      profile().github.launcher_repo.clone()
     };
-    let latest_release = api.get_launcher_latest_release(MAIN_DEVELOPER_NAME, &project_id).await?;
+    let latest_release = api.get_launcher_latest_release(&profile().github.launcher_owner, &project_id).await?;
 
     log::debug!("ServiceUpdater.check, latest_release.tag_name: {:?}", &latest_release.version);
 
@@ -0,0 +1,345 @@
+// Verify / repair of an installed version.
+//
+// The expected state of the game folder is the per-file list (`entries`) of
+// the release manifest with the manifests of the installed patches laid over
+// it in chain order: a patch replaces the entries it ships and drops its
+// `deleted_files`. Verify hashes every expected file and lists what is
+// missing, modified or not expected at all; repair downloads only the
+// archives holding broken files and extracts just those entries.
+
+use std::{
+  collections::{BTreeMap, HashMap},
+  path::Path,
+  sync::{
+    Arc, Mutex as StdMutex,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+  },
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use walkdir::WalkDir;
+
+use crate::{
+  handlers::{dto::ReleaseManifestEntry, patch_install::download_manifest},
+  providers::ApiClient::ApiClient::ApiClient,
+  service::{
+    disk_space::SpacePlan,
+    files::{ServiceFiles, fetch_asset},
+    index::IndexAsset,
+    unpack::ServiceUnpacker,
+  },
+  utils::{hash::sha256_file, patch_markers::read_installed_patches},
+};
+
+/// Archives fetched by a repair, inside the install dir.
+const REPAIR_DIR: &str = ".repair";
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+  pub version_name: String,
+  /// Expected files checked.
+  pub checked: u32,
+  pub missing: Vec<String>,
+  pub modified: Vec<String>,
+  /// Files in the game folder no manifest lists (mods, leftovers); never touched by repair.
+  pub extra: Vec<String>,
+}
+
+impl VerifyReport {
+  pub fn is_intact(&self) -> bool {
+    self.missing.is_empty() && self.modified.is_empty()
+  }
+}
+
+/// Archive of the release or of a patch that carries expected files.
+struct ArchiveSource {
+  /// Release name or patch tag.
+  label: String,
+  name: String,
+  asset: Option<IndexAsset>,
+}
+
+struct Expected {
+  size: u64,
+  sha256: String,
+  archive: usize,
+}
+
+/// Expected content of an install.
+pub struct InstallPlan {
+  provider_id: String,
+  files: BTreeMap<String, Expected>,
+  archives: Vec<ArchiveSource>,
+}
+
+impl InstallPlan {
+  fn add(&mut self, label: &str, entries: &[ReleaseManifestEntry], assets: &[IndexAsset]) {
+    for entry in entries {
+      let archive = match self.archives.iter().position(|a| a.label == label && a.name == entry.archive) {
+        Some(i) => i,
+        None => {
+          self.archives.push(ArchiveSource {
+            label: label.to_owned(),
+            name: entry.archive.clone(),
+            asset: assets.iter().find(|a| a.name == entry.archive).cloned(),
+          });
+          self.archives.len() - 1
+        }
+      };
+      self.files.insert(
+        normalize(&entry.path),
+        Expected {
+          size: entry.size,
+          sha256: entry.sha256.clone(),
+          archive,
+        },
+      );
+    }
+  }
+}
+
+fn normalize(path: &str) -> String {
+  path.replace('\\', "/").trim_start_matches('/').to_owned()
+}
+
+/// Builds the expected content of `installed_path` from the release index of
+/// the current provider and the installed patch markers.
+pub async fn load_plan(api_client: &ApiClient, version_name: &str, installed_path: &Path) -> Result<InstallPlan> {
+  let api = api_client.current_provider()?;
+  let provider_id = api.id().to_owned();
+  let index = api.release_index().await.context("Verify needs the release index")?;
+  let entry = index
+    .releases
+    .iter()
+    .find(|r| r.name == version_name || r.path == version_name)
+    .ok_or_else(|| anyhow!("Release '{}' not found in the index", version_name))?;
+
+  let manifest = download_manifest(api_client, &entry.manifest)
+    .await
+    .context("Cannot fetch the release manifest")?;
+  if manifest.entries.is_empty() {
+    bail!("Release '{}' was packed without a file list and cannot be verified", version_name);
+  }
+
+  let mut plan = InstallPlan {
+    provider_id,
+    files: BTreeMap::new(),
+    archives: vec![],
+  };
+  plan.add(&entry.name, &manifest.entries, &entry.assets);
+
+  for marker in read_installed_patches(installed_path) {
+    let patch = entry
+      .patches
+      .iter()
+      .find(|p| p.tag == marker.name)
+      .ok_or_else(|| anyhow!("Installed patch '{}' is not in the release index", marker.name))?;
+    let url = patch
+      .manifest
+      .as_deref()
+      .ok_or_else(|| anyhow!("Patch '{}' has no manifest", patch.tag))?;
+    let manifest = download_manifest(api_client, url).await?;
+    if manifest.entries.is_empty() {
+      bail!("Patch '{}' was packed without a file list and cannot be verified", patch.tag);
+    }
+
+    for deleted in &manifest.deleted_files {
+      plan.files.remove(&normalize(deleted));
+    }
+    plan.add(&patch.tag, &manifest.entries, &patch.assets);
+  }
+
+  Ok(plan)
+}
+
+/// Checks `paths` (all expected files when None) against the plan. Blocking:
+/// hashes on every core; `progress(done, total)` is called per file.
+pub fn verify(
+  version_name: &str,
+  installed_path: &Path,
+  plan: &InstallPlan,
+  paths: Option<&[String]>,
+  mut cancel: broadcast::Receiver<()>,
+  progress: impl Fn(usize, usize) + Sync,
+) -> Result<VerifyReport> {
+  let targets: Vec<(&String, &Expected)> = match paths {
+    Some(paths) => paths.iter().filter_map(|p| plan.files.get_key_value(p)).collect(),
+    None => plan.files.iter().collect(),
+  };
+
+  let total = targets.len();
+  let next = AtomicUsize::new(0);
+  let done = AtomicUsize::new(0);
+  let cancelled = AtomicBool::new(false);
+  let broken: StdMutex<(Vec<String>, Vec<String>)> = StdMutex::new((vec![], vec![]));
+
+  let worker = || {
+    loop {
+      let i = next.fetch_add(1, Ordering::SeqCst);
+      if i >= total || cancelled.load(Ordering::Relaxed) {
+        return;
+      }
+      let (path, expected) = targets[i];
+      let file = installed_path.join(path);
+
+      let state = match std::fs::metadata(&file) {
+        Err(_) => Some(true),
+        Ok(meta) if !meta.is_file() => Some(true),
+        Ok(meta) if meta.len() != expected.size => Some(false),
+        Ok(_) => match sha256_file(&file) {
+          Ok(hash) if hash.eq_ignore_ascii_case(&expected.sha256) => None,
+          _ => Some(false),
+        },
+      };
+      match state {
+        Some(true) => crate::utils::locks::lock(&broken).0.push(path.clone()),
+        Some(false) => crate::utils::locks::lock(&broken).1.push(path.clone()),
+        None => {}
+      }
+
+      progress(done.fetch_add(1, Ordering::SeqCst) + 1, total);
+    }
+  };
+
+  let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(total.max(1));
+  std::thread::scope(|s| {
+    let handles: Vec<_> = (0..threads).map(|_| s.spawn(worker)).collect();
+    while handles.iter().any(|h| !h.is_finished()) {
+      if cancel.try_recv().is_ok() {
+        cancelled.store(true, Ordering::Relaxed);
+      }
+      std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+  });
+  if cancelled.load(Ordering::Relaxed) {
+    bail!("USER_CANCELLED");
+  }
+
+  let (mut missing, mut modified) = broken.into_inner().unwrap_or_else(|e| e.into_inner());
+  missing.sort();
+  modified.sort();
+
+  // Extra files: everything else in the game folder, except the player's data
+  // (`appdata/`: saves, logs, settings, patch markers) and the launcher's own dot dirs.
+  let mut extra = vec![];
+  if paths.is_none() {
+    for entry in WalkDir::new(installed_path)
+      .into_iter()
+      .filter_entry(|e| e.depth() != 1 || !(e.file_name().to_string_lossy().starts_with('.') || e.file_name() == "appdata"))
+      .flatten()
+    {
+      if !entry.file_type().is_file() {
+        continue;
+      }
+      let Ok(rel) = entry.path().strip_prefix(installed_path) else {
+        continue;
+      };
+      let rel = normalize(&rel.to_string_lossy());
+      if !plan.files.contains_key(&rel) {
+        extra.push(rel);
+      }
+    }
+    extra.sort();
+  }
+
+  let report = VerifyReport {
+    version_name: version_name.to_owned(),
+    checked: total as u32,
+    missing,
+    modified,
+    extra,
+  };
+  log::info!(
+    "Verify of '{}': {} checked, {} missing, {} modified, {} extra",
+    version_name,
+    report.checked,
+    report.missing.len(),
+    report.modified.len(),
+    report.extra.len()
+  );
+  Ok(report)
+}
+
+/// Restores the missing and modified files of `report`: downloads the
+/// archives holding them into `<install>/.repair/` and extracts only those
+/// entries. Returns the report of the repaired files checked again.
+#[allow(clippy::too_many_arguments)]
+pub async fn repair(
+  api_client: &ApiClient,
+  files: &ServiceFiles,
+  unpack: &Arc<ServiceUnpacker>,
+  installed_path: &Path,
+  plan: &Arc<InstallPlan>,
+  report: &VerifyReport,
+  cancel_tx: &broadcast::Sender<()>,
+  log: impl Fn(String),
+) -> Result<VerifyReport> {
+  let broken: Vec<String> = report.missing.iter().chain(&report.modified).cloned().collect();
+
+  let mut by_archive: HashMap<usize, Vec<String>> = HashMap::new();
+  for path in &broken {
+    if let Some(expected) = plan.files.get(path) {
+      by_archive.entry(expected.archive).or_default().push(path.clone());
+    }
+  }
+
+  let repair_dir = installed_path.join(REPAIR_DIR);
+  let needed: u64 = by_archive
+    .keys()
+    .filter_map(|i| plan.archives[*i].asset.as_ref().map(|a| a.size))
+    .chain(broken.iter().filter_map(|p| plan.files.get(p).map(|e| e.size)))
+    .sum();
+  let mut space = SpacePlan::default();
+  space.add(&repair_dir, needed)?;
+  space.ensure().map_err(|e| anyhow!(e))?;
+
+  for (i, (archive, paths)) in by_archive.iter().enumerate() {
+    let source = &plan.archives[*archive];
+    let asset = source
+      .asset
+      .as_ref()
+      .ok_or_else(|| anyhow!("Archive '{}' of '{}' is not in the release index", source.name, source.label))?;
+    log(format!(
+      "Repair: {} of {} ({}/{}, {} files)",
+      &source.name,
+      &source.label,
+      i + 1,
+      by_archive.len(),
+      paths.len()
+    ));
+
+    let dir = repair_dir.join(&source.label);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Cannot create {:?}", dir))?;
+    fetch_asset(api_client, files, &plan.provider_id, &report.version_name, asset, &dir, cancel_tx).await?;
+
+    let archive_path = dir.join(&asset.name);
+    let (svc, vn, name, to, names) = (
+      unpack.clone(),
+      report.version_name.clone(),
+      asset.name.clone(),
+      installed_path.to_path_buf(),
+      paths.clone(),
+    );
+    let from = archive_path.clone();
+    tokio::task::spawn_blocking(move || svc.extract_zip_entries(&vn, &name, &from, &to, &names))
+      .await?
+      .map_err(|e| anyhow!("Extract failed: {}", e))?;
+    let _ = std::fs::remove_file(&archive_path);
+  }
+
+  if let Err(e) = std::fs::remove_dir_all(&repair_dir) {
+    log::warn!("Cannot remove {:?}: {}", repair_dir, e);
+  }
+
+  let (version_name, path, plan, cancel) = (
+    report.version_name.clone(),
+    installed_path.to_path_buf(),
+    plan.clone(),
+    cancel_tx.subscribe(),
+  );
+  let mut after = tokio::task::spawn_blocking(move || verify(&version_name, &path, &plan, Some(broken.as_slice()), cancel, |_, _| {})).await??;
+  after.extra = report.extra.clone();
+  Ok(after)
+}
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::{
   panic,
   sync::{Arc, Mutex as StdMutex},
+  time::Duration,
 };
 use tokio::sync::Mutex;
 
@@ -10,19 +11,23 @@ use tauri::Manager;
 use tauri::{App, Emitter};
 
 use crate::handlers::patch_install::check_patches_available;
-use crate::handlers::start_download_version::CancelMap;
 use crate::handlers::upload_v2::UploadCancelMap;
+use crate::service::download_jobs::{DownloadContext, DownloadJobs};
 use crate::service::files::ServiceFiles;
 use crate::service::get_release::ServiceGetRelease;
+use crate::service::health_monitor::{HealthMap, HealthMonitor};
 use crate::service::keybind_manager::KeybindManager;
+use crate::service::staging;
 use crate::service::unpack::ServiceUnpacker;
 use crate::service::updater::ServiceUpdater;
 use crate::service::wake_detector::WakeDetector;
 use crate::utils::encoding::{decode_token, encode_token, is_legacy_token};
 use crate::utils::errors::log_full_error;
 use crate::utils::http_cache;
+use crate::utils::proxy;
 use crate::{
-  configs::{AppConfig::AppConfig, GameConfig::GameConfig, TmpLtx, UserLtx},
+  configs::{AppConfig::AppConfig, DistributionProfile, GameConfig::GameConfig, TmpLtx, UserLtx},
+  consts::HEALTH_CHECK_INTERVAL_SECS,
   logger::Logger,
   service::{client::ServiceClient, dto::UserData, main::Service},
 };
@@ -64,7 +69,28 @@ pub fn tauri_setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
   log::info!("Start app setup");
 
   let config = AppConfig::load_or_create(app.handle())?;
+  // Interrupted installs: roll back half-done commits, drop staging nobody resumes into.
+  // Done before the window can send commands and before the download queue runs,
+  // so nothing reads or writes an install while it is being put back.
+  staging::recover(&config);
+  // Before any HTTP client is built (providers, http_cache shared client).
+  if let Err(e) = proxy::set(&config.proxy) {
+    log::error!("Saved proxy settings are not applied, fix them in the settings: {:#}", e);
+  }
   http_cache::init(app.handle())?;
+  DistributionProfile::init(app.handle(), &config)?;
+  // A distribution with its own name renames the window; the built-in one keeps the title from tauri.conf.json.
+  let brand = &DistributionProfile::profile().name;
+  if *brand != DistributionProfile::DistributionProfile::default().name
+    && let Some(window) = app.get_webview_window("main")
+    && let Err(e) = window.set_title(brand)
+  {
+    log::warn!("Cannot set the window title to '{}': {}", brand, e);
+  }
+  let download_speed_limit = config.download_speed_limit;
+  let bandwidth_schedule = config.bandwidth_schedule.clone();
+  let saved_jobs = config.download_jobs.clone();
+  let unpack_threads = config.unpack_threads();
   let config_arc = Arc::new(Mutex::new(config));
   let config_arc_clone = config_arc.clone();
 
@@ -96,14 +122,32 @@ pub fn tauri_setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
   let service_unpack_arc = Arc::new(ServiceUnpacker::new(move |release_name, file_name, count, total| {
     let _ = handle2.emit("game-archive-unack-progress", (release_name, file_name, count, total));
   }));
-  let service_files_arc = Arc::new(ServiceFiles::new(move |release_name, file_name, bytes, total_bytes, speed| {
-    let _ = handle3.emit("download-speed-status", (release_name, file_name, &bytes, &total_bytes, &speed));
+  service_unpack_arc.set_threads(unpack_threads);
+  let service_files_arc = Arc::new(ServiceFiles::new(move |release_name, file_name, bytes, total_bytes, speed, throttle| {
+    let _ = handle3.emit("download-speed-status", (release_name, file_name, &bytes, &total_bytes, &speed, &throttle));
   }));
+  if let Err(e) = service_files_arc.set_bandwidth(download_speed_limit, &bandwidth_schedule) {
+    // A hand-edited config with a bad rule: keep the base cap only.
+    log::warn!("Ignoring bandwidth schedule: {}", e);
+    let _ = service_files_arc.set_bandwidth(download_speed_limit, &[]);
+  }
   let service_updater_arc = Arc::new(ServiceUpdater::new(move |release_name, bytes, speed| {
     let _ = handle4.emit("download-launcher-status", (release_name, &bytes, &speed));
   }));
   let service_clone = service_arc.clone();
 
+  let download_jobs = Arc::new(DownloadJobs::new(
+    DownloadContext {
+      app: app.handle().clone(),
+      config: config_arc.clone(),
+      service: service_arc.clone(),
+      files: service_files_arc.clone(),
+      unpack: service_unpack_arc.clone(),
+    },
+    saved_jobs,
+  ));
+  let download_jobs_bg = download_jobs.clone();
+
   let user_data_placeholder = Arc::new(Mutex::new(Option::<UserData>::None));
 
   log::info!("Init Service Completed");
@@ -115,6 +159,10 @@ pub fn tauri_setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
   let wake = WakeDetector::new(wake_callback);
   wake.start_watcher(5.0);
 
+  let health_map: HealthMap = Arc::new(StdMutex::new(HashMap::new()));
+  let health_monitor = HealthMonitor::new(app.handle().clone(), service_arc.clone(), config_arc.clone(), health_map.clone());
+  health_monitor.start(Duration::from_secs(HEALTH_CHECK_INTERVAL_SECS));
+
   // Регистрируем всё в стейте
   app.manage(config_arc);
   app.manage(Arc::new(Mutex::new(user_ltx_config)));
@@ -125,8 +173,9 @@ pub fn tauri_setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
   app.manage(service_files_arc);
   app.manage(service_unpack_arc);
   app.manage(service_updater_arc);
-  app.manage(Arc::new(StdMutex::new(HashMap::new())) as CancelMap);
+  app.manage(download_jobs);
   app.manage(Arc::new(StdMutex::new(HashMap::new())) as UploadCancelMap);
+  app.manage(health_map);
 
   log::info!("init App State Completed");
 
@@ -175,12 +224,16 @@ pub fn tauri_setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
           // them, get_tokens masks them — the startup event must not leak).
           let mut cfg_snapshot = config_guard.clone();
           cfg_snapshot.tokens.clear();
+          cfg_snapshot.proxy.password.clear();
           let _ = app_handle_bg.emit("config-loaded", cfg_snapshot);
         }
 
         let _ = app_handle_bg.emit("versions-loaded", releases);
       }
 
+      // Providers are ready: run the download queue restored from the config.
+      download_jobs_bg.start();
+
       // Auto-check for available patches (lightweight, silent).
       {
         let api_client = {
@@ -273,6 +326,9 @@ pub fn tauri_setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
     }
     .await;
 
+    // Also when init failed early, so new downloads are not stuck in the queue.
+    download_jobs_bg.start();
+
     if let Err(e) = result {
       log::error!("Background initialization failed: {:?}", e);
       log_full_error(&e);
@@ -0,0 +1,27 @@
+//! Background job scope.
+//!
+//! Provider calls cannot tell a command the UI is waiting on from a download
+//! or upload job running on its own. A job runs its work inside `scope`, so
+//! code deep in a provider (e.g. the GitHub quota back-off) can check whether
+//! it may wait, and for how long: only until the job's cancel signal.
+//!
+//! The scope is task-local: tasks spawned from inside a job are not in it.
+
+use std::future::Future;
+
+use tokio::sync::broadcast;
+
+tokio::task_local! {
+  static JOB_CANCEL: broadcast::Sender<()>;
+}
+
+/// Runs `fut` as a background job stopped through `cancel_tx`.
+pub async fn scope<F: Future>(cancel_tx: &broadcast::Sender<()>, fut: F) -> F::Output {
+  JOB_CANCEL.scope(cancel_tx.clone(), fut).await
+}
+
+/// Cancel receiver of the current background job, None outside of one
+/// (an interactive command must not be held back).
+pub fn cancel_receiver() -> Option<broadcast::Receiver<()>> {
+  JOB_CANCEL.try_with(|tx| tx.subscribe()).ok()
+}
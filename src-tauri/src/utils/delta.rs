@@ -0,0 +1,157 @@
+//! Binary deltas of modified game files (zstd "patch-from").
+//!
+//! The old content of a file is the reference prefix of the compressor, so
+//! the frame of the new content only carries what changed. Both sides are
+//! held in memory and must fit into one zstd window, which caps the file
+//! size at `MAX_DELTA_FILE_SIZE`.
+
+use std::{
+  fs,
+  io::{self, Write},
+  path::Path,
+};
+
+use anyhow::{Context, Result};
+
+/// `.deltas/<path>` in the patch folder (and in the unpacked patch) holds the
+/// delta of `<path>`.
+pub const DELTA_DIR: &str = ".deltas";
+/// Largest base or patched file a delta is made for (zstd window limit).
+pub const MAX_DELTA_FILE_SIZE: u64 = 1 << 31;
+const WINDOW_LOG_MAX: u32 = 31;
+const LEVEL: i32 = 9;
+
+/// Window that spans both files, as `zstd --patch-from` picks it.
+fn window_log(size: u64) -> u32 {
+  (64 - size.max(1).leading_zeros() + 1).clamp(10, WINDOW_LOG_MAX)
+}
+
+/// Writes to `out` the delta that turns `base` into the content of
+/// `new_file`. Returns the delta size. Blocking.
+pub fn encode(base: &[u8], new_file: &Path, out: &Path) -> Result<u64> {
+  let new = fs::read(new_file).with_context(|| format!("Cannot read {:?}", new_file))?;
+  if let Some(parent) = out.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  let file = fs::File::create(out).with_context(|| format!("Cannot create {:?}", out))?;
+  let mut encoder = zstd::stream::write::Encoder::with_ref_prefix(io::BufWriter::new(file), LEVEL, base)?;
+  encoder.window_log(window_log(base.len().max(new.len()) as u64))?;
+  encoder.long_distance_matching(true)?;
+  encoder.write_all(&new)?;
+  encoder.finish()?.flush()?;
+
+  Ok(fs::metadata(out)?.len())
+}
+
+/// Rebuilds into `out` the file the delta was made for, from `base`. Blocking.
+pub fn decode(base: &Path, delta: &Path, out: &Path) -> Result<()> {
+  let base = fs::read(base).with_context(|| format!("Cannot read {:?}", base))?;
+  let reader = io::BufReader::new(fs::File::open(delta).with_context(|| format!("Cannot open {:?}", delta))?);
+  let mut decoder = zstd::stream::read::Decoder::with_ref_prefix(reader, &base)?;
+  decoder.window_log_max(WINDOW_LOG_MAX)?;
+
+  let mut writer = io::BufWriter::new(fs::File::create(out).with_context(|| format!("Cannot create {:?}", out))?);
+  io::copy(&mut decoder, &mut writer).with_context(|| format!("Cannot apply {:?}", delta))?;
+  writer.flush()?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use uuid::Uuid;
+
+  use super::*;
+
+  struct TempDir(PathBuf);
+
+  impl TempDir {
+    fn new() -> Self {
+      let dir = std::env::temp_dir().join(format!("gw-delta-test-{}", Uuid::new_v4()));
+      fs::create_dir_all(&dir).unwrap();
+      Self(dir)
+    }
+  }
+
+  impl Drop for TempDir {
+    fn drop(&mut self) {
+      let _ = fs::remove_dir_all(&self.0);
+    }
+  }
+
+  /// Deterministic bytes that do not compress to nothing.
+  fn noise(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    (0..len)
+      .map(|_| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) as u8
+      })
+      .collect()
+  }
+
+  /// `base` with a few bytes changed near both ends and `grow` bytes appended.
+  fn modified(base: &[u8], grow: usize) -> Vec<u8> {
+    let mut new = base.to_vec();
+    for i in [0, base.len() / 2, base.len().saturating_sub(1)] {
+      if let Some(b) = new.get_mut(i) {
+        *b ^= 0x5a;
+      }
+    }
+    new.extend(noise(grow, base.len() as u64));
+    new
+  }
+
+  fn round_trip(base: &[u8], new: &[u8]) -> u64 {
+    let tmp = TempDir::new();
+    let (base_path, new_path, delta_path, out_path) = (tmp.0.join("base"), tmp.0.join("new"), tmp.0.join("d/delta"), tmp.0.join("out"));
+    fs::write(&base_path, base).unwrap();
+    fs::write(&new_path, new).unwrap();
+
+    let size = encode(base, &new_path, &delta_path).unwrap();
+    decode(&base_path, &delta_path, &out_path).unwrap();
+
+    assert_eq!(fs::read(&out_path).unwrap(), new, "round trip of {} -> {} bytes", base.len(), new.len());
+    size
+  }
+
+  #[test]
+  fn round_trips_small_and_empty_files() {
+    round_trip(b"", b"");
+    round_trip(b"", b"new file content");
+    round_trip(b"old file content", b"");
+    round_trip(b"[section]\nkey = 1\n", b"[section]\nkey = 2\n");
+  }
+
+  #[test]
+  fn round_trips_around_window_size_steps() {
+    // The window grows in powers of two with the larger file; both sides of each step must decode.
+    for size in [1023, 1024, 1025, (1 << 16) - 1, 1 << 16, (1 << 16) + 1, (1 << 20) + 1] {
+      let base = noise(size, 7);
+      round_trip(&base, &modified(&base, 0));
+      // The patched file crosses into the next window size.
+      round_trip(&base, &modified(&base, size / 2 + 1));
+      // And a shrinking file: the base alone decides the window.
+      round_trip(&base, &base[..size / 3]);
+    }
+  }
+
+  #[test]
+  fn delta_of_a_small_change_is_small() {
+    let base = noise(1 << 18, 3);
+    let size = round_trip(&base, &modified(&base, 0));
+    assert!(size < 1024, "delta of three changed bytes takes {} bytes", size);
+  }
+
+  #[test]
+  fn window_covers_files_up_to_the_limit() {
+    for size in [0, 1, 1 << 10, (1 << 16) - 1, 1 << 16, (1 << 30) + 1, MAX_DELTA_FILE_SIZE] {
+      let log = window_log(size);
+      assert!((10..=WINDOW_LOG_MAX).contains(&log));
+      assert!(1u64 << log >= size, "window 2^{} is smaller than {} bytes", log, size);
+    }
+    assert_eq!(window_log(MAX_DELTA_FILE_SIZE), WINDOW_LOG_MAX);
+  }
+}
@@ -11,3 +11,67 @@ pub fn upload_log(app: &tauri::AppHandle, msg: String) {
   log::info!("{}", &msg);
   let _ = app.emit("upload-log", msg);
 }
+
+/// Non-success answer to a download request, kept typed so retries can tell a
+/// missing file (404) from an overloaded host (429 / 5xx).
+#[derive(Debug)]
+pub struct HttpStatusError {
+  pub context: String,
+  pub status: reqwest::StatusCode,
+  /// `Retry-After` of the response (seconds or HTTP date).
+  pub retry_after: Option<std::time::Duration>,
+  pub body: String,
+}
+
+impl HttpStatusError {
+  /// Consumes the response (the body goes into the message).
+  pub async fn from_response(context: &str, response: reqwest::Response) -> Self {
+    let status = response.status();
+    let retry_after = response
+      .headers()
+      .get(reqwest::header::RETRY_AFTER)
+      .and_then(|v| v.to_str().ok())
+      .and_then(parse_retry_after);
+    let body = response.text().await.unwrap_or_else(|_| "<failed to read response body>".to_string());
+
+    Self {
+      context: context.to_owned(),
+      status,
+      retry_after,
+      body,
+    }
+  }
+}
+
+impl std::fmt::Display for HttpStatusError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}: {} – {}", self.context, self.status, self.body)
+  }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+fn parse_retry_after(value: &str) -> Option<std::time::Duration> {
+  let value = value.trim();
+  if let Ok(secs) = value.parse::<u64>() {
+    return Some(std::time::Duration::from_secs(secs));
+  }
+  let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+  let secs = (at.timestamp() - chrono::Utc::now().timestamp()).max(0) as u64;
+  Some(std::time::Duration::from_secs(secs))
+}
+
+/// A download stream that delivered no bytes for `DOWNLOAD_STALL_TIMEOUT_SECS`.
+#[derive(Debug)]
+pub struct StallError {
+  pub file_name: String,
+  pub downloaded: u64,
+}
+
+impl std::fmt::Display for StallError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Download of {} stalled at {} bytes", self.file_name, self.downloaded)
+  }
+}
+
+impl std::error::Error for StallError {}
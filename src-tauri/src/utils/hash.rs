@@ -0,0 +1,45 @@
+use std::{
+  fs::File,
+  io::{Read, Write},
+  path::Path,
+};
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+/// Read buffer for hashing multi-GB archives.
+const HASH_BUF_SIZE: usize = 1024 * 1024;
+
+/// Lowercase hex SHA-256 of a file. Blocking: call it from `spawn_blocking`.
+pub fn sha256_file(path: &Path) -> Result<String> {
+  let mut file = File::open(path).with_context(|| format!("Cannot open {:?} for hashing", path))?;
+  let mut hasher = Sha256::new();
+  let mut buf = vec![0u8; HASH_BUF_SIZE];
+
+  loop {
+    let n = file.read(&mut buf).with_context(|| format!("Cannot read {:?} for hashing", path))?;
+    if n == 0 {
+      break;
+    }
+    hasher.update(&buf[..n]);
+  }
+
+  Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Copies `reader` into `writer` and returns the lowercase hex SHA-256 of the copied bytes.
+pub fn copy_sha256(reader: &mut impl Read, writer: &mut impl Write) -> Result<String> {
+  let mut hasher = Sha256::new();
+  let mut buf = vec![0u8; HASH_BUF_SIZE];
+
+  loop {
+    let n = reader.read(&mut buf).context("Cannot read for hashing")?;
+    if n == 0 {
+      break;
+    }
+    hasher.update(&buf[..n]);
+    writer.write_all(&buf[..n]).context("Cannot write hashed data")?;
+  }
+
+  Ok(format!("{:x}", hasher.finalize()))
+}
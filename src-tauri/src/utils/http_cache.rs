@@ -6,7 +6,7 @@
 
 use std::fs;
 use std::path::PathBuf;
-use std::sync::{LazyLock, OnceLock};
+use std::sync::{Mutex as StdMutex, OnceLock};
 use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
@@ -38,7 +38,31 @@ fn fetch_lock() -> &'static Mutex<()> {
 /// Shared client for ad-hoc metadata GETs/HEADs (release index, manifests,
 /// bg etag). One connection pool instead of a fresh TLS context per call
 /// site — `reqwest::Client::new()` was created on the fly in 6 places.
-pub static SHARED_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+/// Built by `init` (a failure stops the setup with its error instead of a
+/// panic on first use), rebuilt by `rebuild_shared_client` when the proxy
+/// settings change.
+static SHARED_CLIENT: OnceLock<StdMutex<reqwest::Client>> = OnceLock::new();
+
+fn build_shared_client() -> Result<reqwest::Client> {
+    Ok(crate::utils::proxy::client_builder()?.build()?)
+}
+
+fn shared_client_slot() -> Result<&'static StdMutex<reqwest::Client>> {
+    SHARED_CLIENT
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("shared HTTP client not built — call http_cache::init first"))
+}
+
+/// Cheap handle to the shared client (reqwest clients are `Arc` inside).
+pub fn shared_client() -> Result<reqwest::Client> {
+    Ok(crate::utils::locks::lock(shared_client_slot()?).clone())
+}
+
+pub fn rebuild_shared_client() -> Result<()> {
+    let client = build_shared_client()?;
+    *crate::utils::locks::lock(shared_client_slot()?) = client;
+    Ok(())
+}
 
 // ---------------------------------------------------------------------------
 // Public types
@@ -77,8 +101,14 @@ struct CacheMeta {
 // Initialization
 // ---------------------------------------------------------------------------
 
-/// Call once during `tauri_setup`, before any async work.
+/// Call once during `tauri_setup`, before any async work and after
+/// `proxy::set`: also builds the shared client.
 pub fn init(app_handle: &tauri::AppHandle) -> Result<()> {
+    let client = build_shared_client().context("Failed to build the shared HTTP client")?;
+    SHARED_CLIENT
+        .set(StdMutex::new(client))
+        .map_err(|_| anyhow::anyhow!("http_cache already initialised"))?;
+
     let dir = app_handle
         .path()
         .resolve(BASE_DIR, BaseDirectory::AppConfig)
@@ -129,6 +159,21 @@ pub async fn fetch(
     client: &reqwest::Client,
     url: &str,
     ttl: Duration,
+) -> Result<CachedBody> {
+    fetch_observed(client, url, ttl, &|_, _| {}).await
+}
+
+/// Status + headers of every response that reached the server, so callers
+/// can track things like API rate-limit headers through the cache.
+pub type ResponseObserver = dyn Fn(reqwest::StatusCode, &reqwest::header::HeaderMap) + Sync;
+
+/// [`fetch`] that reports each network response to `observe` (cache hits
+/// send no request and are not reported).
+pub async fn fetch_observed(
+    client: &reqwest::Client,
+    url: &str,
+    ttl: Duration,
+    observe: &ResponseObserver,
 ) -> Result<CachedBody> {
     let dir = cache_dir()?.to_path_buf();
     let key = hash_url(url);
@@ -188,6 +233,9 @@ pub async fn fetch(
     }
 
     let resp = req.send().await;
+    if let Ok(r) = &resp {
+        observe(r.status(), r.headers());
+    }
 
     match resp {
         Ok(r) if r.status() == reqwest::StatusCode::NOT_MODIFIED => {
@@ -1,13 +1,17 @@
 pub mod CountingWriter;
+pub mod background;
+pub mod delta;
 pub mod encoding;
 pub mod errors;
 pub mod git;
+pub mod hash;
 pub mod http_cache;
 pub mod locks;
 pub mod parse_strings;
 pub mod paths;
 pub mod patch_collect;
 pub mod patch_markers;
+pub mod proxy;
 pub mod resources;
 pub mod restart;
 pub mod split_args;
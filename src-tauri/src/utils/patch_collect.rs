@@ -13,15 +13,31 @@
 //! The "latest reachable tag" rule covers both the first patch after a full
 //! release (tag of the full release) and subsequent patches (tag created by
 //! the launcher when the previous patch was uploaded).
+//!
+//! Cumulative mode squashes a published patch chain into one patch: every repo
+//! is diffed from the base the first patch of the chain was collected against
+//! up to the tag of the last one, which also folds the `deleted_files` of the
+//! chain together. HEAD must be at that tag (files are copied from disk).
+//!
+//! Delta mode: a modified file above the size threshold is stored as a binary
+//! delta against its blob at the base tag (`.deltas/<path>`, see
+//! `utils::delta`); the full file goes to a sibling `.full` folder, packed
+//! separately for installs whose copy differs from the base.
 
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use crate::handlers::dto::PatchDelta;
+use crate::utils::delta::{self, DELTA_DIR, MAX_DELTA_FILE_SIZE};
+use crate::utils::hash::sha256_file;
+
 /// Per-repository outcome of the collection.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -58,6 +74,12 @@ pub struct PatchCollectResult {
   pub deleted_files: Vec<String>,
   /// Base tag of the root repo — used as base_release_tag in the patch manifest.
   pub base_tag: Option<String>,
+  /// Modified files stored as deltas (delta mode only).
+  pub deltas: Vec<PatchDelta>,
+  /// Full copies of the delta files, None when there are no deltas.
+  pub full_dir: Option<String>,
+  /// Patch tags a cumulative patch replaces, in chain order (empty otherwise).
+  pub supersedes: Vec<String>,
   pub repos: Vec<RepoPatchReport>,
   pub changed: u32,
   pub deleted: u32,
@@ -89,15 +111,20 @@ pub(crate) fn find_git_roots(base: &Path) -> Vec<PathBuf> {
 }
 
 /// Returns the tag reachable from `head` whose target commit has the newest
-/// commit time. Ties are resolved arbitrarily (first found wins).
+/// commit time, ignoring the tags in `skip`. Ties are resolved arbitrarily
+/// (first found wins).
 fn latest_reachable_tag<'repo>(
   repo: &'repo git2::Repository,
   head: &git2::Commit<'repo>,
+  skip: &HashSet<&str>,
 ) -> Option<(String, git2::Commit<'repo>)> {
   let tags = repo.tag_names(None).ok()?;
   let mut best: Option<(i64, String, git2::Commit<'repo>)> = None;
 
   for name in tags.iter().flatten() {
+    if skip.contains(name) {
+      continue;
+    }
     let ref_name = format!("refs/tags/{}", name);
     let Ok(reference) = repo.find_reference(&ref_name) else { continue };
     // Annotated tags point to a tag object — peel down to the commit.
@@ -245,7 +272,27 @@ fn patch_dir_root() -> Result<PathBuf> {
 ///
 /// `exclude_patterns` are glob patterns (relative to `source_dir`) for files
 /// that should be skipped during collection (e.g. caches, logs, build artifacts).
-pub fn collect_patch(source_dir: PathBuf, exclude_patterns: Vec<String>) -> Result<PatchCollectResult> {
+/// `delta_min_size` enables delta mode for modified files of at least that many bytes.
+pub fn collect_patch(source_dir: PathBuf, exclude_patterns: Vec<String>, delta_min_size: Option<u64>) -> Result<PatchCollectResult> {
+  collect(source_dir, exclude_patterns, delta_min_size, None)
+}
+
+/// Collects one cumulative patch replacing `chain` (patch tags, oldest first).
+pub fn collect_cumulative_patch(
+  source_dir: PathBuf,
+  exclude_patterns: Vec<String>,
+  delta_min_size: Option<u64>,
+  chain: Vec<String>,
+) -> Result<PatchCollectResult> {
+  if chain.is_empty() {
+    bail!("no published patches to squash");
+  }
+  let mut result = collect(source_dir, exclude_patterns, delta_min_size, Some(&chain))?;
+  result.supersedes = chain;
+  Ok(result)
+}
+
+fn collect(source_dir: PathBuf, exclude_patterns: Vec<String>, delta_min_size: Option<u64>, chain: Option<&[String]>) -> Result<PatchCollectResult> {
   if !source_dir.is_dir() {
     bail!("source dir does not exist: {:?}", source_dir);
   }
@@ -262,18 +309,35 @@ pub fn collect_patch(source_dir: PathBuf, exclude_patterns: Vec<String>) -> Resu
   };
 
   let patch_dir = patch_dir_root()?.join(format!("gw-patch-{}", Uuid::new_v4()));
+  let full_dir = patch_dir.with_extension("full");
+  let delta_opts = delta_min_size.map(|min_size| DeltaOptions {
+    full_dir: &full_dir,
+    min_size,
+  });
 
   let mut result = PatchCollectResult {
     patch_dir: patch_dir.to_string_lossy().into_owned(),
     deleted_files: Vec::new(),
     base_tag: None,
+    deltas: Vec::new(),
+    full_dir: None,
+    supersedes: Vec::new(),
     repos: Vec::new(),
     changed: 0,
     deleted: 0,
   };
 
   for repo_dir in find_git_roots(&source_dir) {
-    let report = collect_repo(&repo_dir, &source_dir, &patch_dir, &mut result.deleted_files, exclude_set.as_ref());
+    let report = collect_repo(
+      &repo_dir,
+      &source_dir,
+      &patch_dir,
+      &mut result.deleted_files,
+      &mut result.deltas,
+      exclude_set.as_ref(),
+      delta_opts.as_ref(),
+      chain,
+    );
     log::debug!(
       "collect_patch repo {:?}: status={:?} changed={} deleted={}",
       report.repo_rel_path,
@@ -292,25 +356,118 @@ pub fn collect_patch(source_dir: PathBuf, exclude_patterns: Vec<String>) -> Resu
     result.repos.push(report);
   }
 
+  if !result.deltas.is_empty() {
+    result.full_dir = Some(full_dir.to_string_lossy().into_owned());
+  }
+
   log::info!(
-    "collect_patch done: repos: {}, changed: {}, deleted: {}, patch_dir: {:?}",
+    "collect_patch done: repos: {}, changed: {}, deleted: {}, deltas: {}, patch_dir: {:?}",
     result.repos.len(),
     result.changed,
     result.deleted,
+    result.deltas.len(),
     result.patch_dir
   );
 
   Ok(result)
 }
 
-/// Collects tag..HEAD changes of a single repository into the patch folder.
-/// Files matching `exclude_set` (relative to `source_dir`) are silently skipped.
+struct DeltaOptions<'a> {
+  full_dir: &'a Path,
+  min_size: u64,
+}
+
+/// Stores `src` as a delta against its blob at the base tag, plus its full
+/// copy in `full_dir`. None when the file is out of the size range or the
+/// delta would not be clearly smaller: the full file is shipped instead.
+fn make_delta(
+  repo: &git2::Repository,
+  base_id: git2::Oid,
+  src: &Path,
+  rel: &Path,
+  patch_dir: &Path,
+  opts: &DeltaOptions,
+) -> Result<Option<PatchDelta>> {
+  let size = fs::metadata(src)?.len();
+  if size < opts.min_size || size > MAX_DELTA_FILE_SIZE {
+    return Ok(None);
+  }
+  let blob = repo.find_blob(base_id)?;
+  let base = blob.content();
+  if base.len() as u64 > MAX_DELTA_FILE_SIZE {
+    return Ok(None);
+  }
+
+  let delta_path = patch_dir.join(DELTA_DIR).join(rel);
+  let delta_size = delta::encode(base, src, &delta_path)?;
+  if delta_size * 10 > size * 9 {
+    let _ = fs::remove_file(&delta_path);
+    return Ok(None);
+  }
+
+  let full = opts.full_dir.join(rel);
+  if let Some(parent) = full.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::copy(src, &full).context("copy full file of a delta")?;
+
+  Ok(Some(PatchDelta {
+    path: to_rel_slash(rel),
+    base_size: base.len() as u64,
+    base_sha256: format!("{:x}", Sha256::digest(base)),
+    size,
+    sha256: sha256_file(src)?,
+    archive: String::new(),
+  }))
+}
+
+/// Diff range of a repository: (base tag, base commit, HEAD). Without `chain`
+/// the base is the latest tag reachable from HEAD. With it, the base is the one
+/// the first chain patch present in the repo was collected against (that tag
+/// itself when the repo had no earlier tag), and HEAD must be at the last tag.
+/// `Ok(None)` when the repo has no usable tag.
+fn diff_range<'repo>(
+  repo: &'repo git2::Repository,
+  chain: Option<&[String]>,
+) -> Result<Option<(String, git2::Commit<'repo>, git2::Commit<'repo>)>, String> {
+  let head = repo
+    .head()
+    .and_then(|r| r.peel_to_commit())
+    .map_err(|e| format!("cannot resolve HEAD: {}", e))?;
+
+  let Some(chain) = chain else {
+    return Ok(latest_reachable_tag(repo, &head, &HashSet::new()).map(|(tag, base)| (tag, base, head)));
+  };
+
+  let tag_commit = |tag: &str| repo.find_reference(&format!("refs/tags/{}", tag)).and_then(|r| r.peel_to_commit()).ok();
+  let Some(last) = chain.last() else { return Ok(None) };
+  match tag_commit(last) {
+    Some(target) if target.id() == head.id() => {}
+    Some(_) => return Err(format!("HEAD is not at tag '{}'", last)),
+    None => return Err(format!("tag '{}' not found", last)),
+  }
+
+  let Some((first_tag, first)) = chain.iter().find_map(|t| tag_commit(t).map(|c| (t.clone(), c))) else {
+    return Ok(None);
+  };
+  let skip: HashSet<&str> = chain.iter().map(String::as_str).collect();
+  let (tag, base) = latest_reachable_tag(repo, &first, &skip).unwrap_or((first_tag, first));
+  Ok(Some((tag, base, head)))
+}
+
+/// Collects the changes of a single repository (see `diff_range`) into the
+/// patch folder. Files matching `exclude_set` (relative to `source_dir`) are
+/// silently skipped.
+#[allow(clippy::too_many_arguments)]
 fn collect_repo(
   repo_dir: &Path,
   source_dir: &Path,
   patch_dir: &Path,
   deleted_files: &mut Vec<String>,
+  deltas: &mut Vec<PatchDelta>,
   exclude_set: Option<&GlobSet>,
+  delta_opts: Option<&DeltaOptions>,
+  chain: Option<&[String]>,
 ) -> RepoPatchReport {
   let rel_prefix = repo_dir
     .strip_prefix(source_dir)
@@ -336,19 +493,18 @@ fn collect_repo(
     }
   };
 
-  let head = match repo.head().and_then(|r| r.peel_to_commit()) {
-    Ok(commit) => commit,
-    Err(e) => {
+  let (tag_name, base_commit, head) = match diff_range(&repo, chain) {
+    Ok(Some(range)) => range,
+    Ok(None) => {
+      report.status = RepoPatchStatus::NoTags;
+      return report;
+    }
+    Err(message) => {
       report.status = RepoPatchStatus::Error;
-      report.message = Some(format!("cannot resolve HEAD: {}", e));
+      report.message = Some(message);
       return report;
     }
   };
-
-  let Some((tag_name, base_commit)) = latest_reachable_tag(&repo, &head) else {
-    report.status = RepoPatchStatus::NoTags;
-    return report;
-  };
   report.base_tag = tag_name.clone();
 
   if base_commit.id() == head.id() {
@@ -423,6 +579,18 @@ fn collect_repo(
           continue;
         }
 
+        if let (git2::Delta::Modified, Some(opts)) = (status, delta_opts) {
+          match make_delta(&repo, delta.old_file().id(), &src, &rel, patch_dir, opts) {
+            Ok(Some(d)) => {
+              deltas.push(d);
+              report.changed += 1;
+              continue;
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("collect_patch: no delta for {:?}, shipping the full file: {}", rel, e),
+          }
+        }
+
         let dest = patch_dir.join(&rel);
         if let Some(parent) = dest.parent() {
           if let Err(e) = fs::create_dir_all(parent).context("create patch subfolder") {
@@ -21,6 +21,33 @@ pub struct InstalledPatch {
   /// Release notes from the updates repo.
   #[serde(default)]
   pub notes: Option<String>,
+  /// What the install changed, for uninstalling. None for patches installed
+  /// before backups existed: those cannot be uninstalled.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub backup: Option<PatchBackup>,
+  /// Patch tags a cumulative patch replaced (empty for regular patches).
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub supersedes: Vec<String>,
+}
+
+impl InstalledPatch {
+  /// Tag of the chain state the patch left: the last replaced patch for a
+  /// cumulative one. The next patch of the chain has it as `base_patch`.
+  pub fn chain_tip(&self) -> &str {
+    self.supersedes.last().unwrap_or(&self.name)
+  }
+}
+
+/// Files touched by a patch, relative to the install path (`/`-separated).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatchBackup {
+  /// Files the patch overwrote or deleted; the old copies are kept in
+  /// `<install_path>/appdata/patches/<name>/`.
+  #[serde(default)]
+  pub backed_up: Vec<String>,
+  /// Files the patch created.
+  #[serde(default)]
+  pub added: Vec<String>,
 }
 
 /// Returns `<install_path>/appdata/patches`.
@@ -28,6 +55,20 @@ fn patches_dir(install_path: &Path) -> PathBuf {
   install_path.join("appdata").join("patches")
 }
 
+/// Guard against path traversal via malicious tag names.
+fn check_name(name: &str) -> Result<()> {
+  if name.is_empty() || name.contains('/') || name.contains('\\') || name.contains("..") {
+    anyhow::bail!("invalid patch name (contains path separator or '..'): {}", name);
+  }
+  Ok(())
+}
+
+/// Returns `<install_path>/appdata/patches/<name>`, the backup store of a patch.
+pub fn patch_backup_dir(install_path: &Path, name: &str) -> Result<PathBuf> {
+  check_name(name)?;
+  Ok(patches_dir(install_path).join(name))
+}
+
 /// Reads all `*.json` marker files from the patches directory, parses
 /// each as `InstalledPatch`, and returns them sorted by (installed_at, name).
 /// Corrupted or unreadable files are skipped with a warning.
@@ -84,10 +125,7 @@ pub fn read_installed_patches(install_path: &Path) -> Vec<InstalledPatch> {
 /// Writes a JSON marker file for the given patch.
 /// Rejects names that contain path separators or `..` (traversal guard).
 pub fn write_patch_marker(install_path: &Path, patch: &InstalledPatch) -> Result<()> {
-  // Guard against path traversal via malicious tag names.
-  if patch.name.contains('/') || patch.name.contains('\\') || patch.name.contains("..") {
-    anyhow::bail!("invalid patch name (contains path separator or '..'): {}", patch.name);
-  }
+  check_name(&patch.name)?;
 
   let dir = patches_dir(install_path);
   std::fs::create_dir_all(&dir).context("create patches marker dir")?;
@@ -99,3 +137,17 @@ pub fn write_patch_marker(install_path: &Path, patch: &InstalledPatch) -> Result
   log::info!("patch_markers: wrote {:?}", file_path);
   Ok(())
 }
+
+/// Deletes the JSON marker file of a patch. A missing marker is not an error.
+pub fn remove_patch_marker(install_path: &Path, name: &str) -> Result<()> {
+  check_name(name)?;
+
+  let file_path = patches_dir(install_path).join(format!("{}.json", name));
+  match std::fs::remove_file(&file_path) {
+    Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e).context("remove patch marker file"),
+    _ => {
+      log::info!("patch_markers: removed {:?}", file_path);
+      Ok(())
+    }
+  }
+}
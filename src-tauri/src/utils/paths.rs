@@ -68,6 +68,21 @@ pub fn assert_download_url_allowed(url: &str) -> Result<()> {
     return Ok(());
   }
 
+  // Self-hosted Gitea/Forgejo and static mirrors (only when configured).
+  let profile = crate::configs::DistributionProfile::profile();
+  let configured_hosts = [profile.gitea.host.as_str(), profile.mirror.base_url.as_str()];
+  if configured_hosts
+    .iter()
+    .filter_map(|base| url::Url::parse(base).ok())
+    .any(|u| u.host_str().is_some_and(|h| h.eq_ignore_ascii_case(&host)))
+  {
+    return Ok(());
+  }
+  // Hosts the mirror index links to directly.
+  if profile.mirror.allowed_hosts.iter().any(|h| h.trim().eq_ignore_ascii_case(&host)) {
+    return Ok(());
+  }
+
   bail!("download URL host not allowed: {}", host)
 }
 
@@ -0,0 +1,209 @@
+// Outbound proxy shared by every HTTP client the launcher builds.
+//
+// `AppConfig.proxy` is copied here at startup and on every change; clients are
+// created through `client_builder`, so the provider clients and the shared
+// metadata client pick the proxy up when they are (re)built. A disabled proxy
+// leaves reqwest's default: the system `HTTP(S)_PROXY` / `NO_PROXY` variables.
+
+use std::sync::{LazyLock, Mutex};
+
+use anyhow::{Context, Result, bail};
+use reqwest::{ClientBuilder, NoProxy, Proxy};
+
+use crate::{
+  configs::AppConfig::{ProxyScheme, ProxySettings},
+  utils::encoding::decode_token,
+};
+
+static PROXY: LazyLock<Mutex<ProxySettings>> = LazyLock::new(|| Mutex::new(ProxySettings::default()));
+
+/// Replaces the active settings. Settings a client cannot be built with are
+/// rejected and leave the active ones in place, so no client silently goes
+/// direct. Clients built before keep the old proxy until they are rebuilt
+/// (`ApiClient::rebuild_clients`, `http_cache::rebuild_shared_client`).
+pub fn set(settings: &ProxySettings) -> Result<()> {
+  validate(settings)?;
+  *crate::utils::locks::lock(&PROXY) = settings.clone();
+  Ok(())
+}
+
+/// Fails when no HTTP client can be built with `settings`.
+pub fn validate(settings: &ProxySettings) -> Result<()> {
+  client_builder_for(settings)?
+    .build()
+    .context("Cannot build an HTTP client with the proxy settings")?;
+  Ok(())
+}
+
+pub fn current() -> ProxySettings {
+  crate::utils::locks::lock(&PROXY).clone()
+}
+
+/// Proxy url with the credentials in it: reqwest takes the basic auth of
+/// HTTP proxies and the SOCKS5 auth from the url alike.
+fn proxy_url(settings: &ProxySettings) -> Result<String> {
+  let host = settings.host.trim();
+  if host.is_empty() {
+    bail!("Proxy host is empty");
+  }
+  if settings.port == 0 {
+    bail!("Proxy port is not set");
+  }
+
+  // socks5h: host names are resolved by the proxy, the player may have no usable DNS.
+  let scheme = match settings.scheme {
+    ProxyScheme::Http => "http",
+    ProxyScheme::Https => "https",
+    ProxyScheme::Socks5 => "socks5h",
+  };
+  let mut url = url::Url::parse(&format!("{}://{}:{}", scheme, host, settings.port)).with_context(|| format!("Invalid proxy host '{}'", host))?;
+
+  if !settings.username.is_empty() {
+    let password = decode_token(&settings.password).context("Cannot decode the stored proxy password")?;
+    url
+      .set_username(&settings.username)
+      .map_err(|_| anyhow::anyhow!("Proxy url cannot carry credentials"))?;
+    url
+      .set_password(Some(&password))
+      .map_err(|_| anyhow::anyhow!("Proxy url cannot carry credentials"))?;
+  }
+
+  Ok(url.to_string())
+}
+
+pub fn build_proxy(settings: &ProxySettings) -> Result<Proxy> {
+  let proxy = Proxy::all(proxy_url(settings)?).context("Invalid proxy settings")?;
+  Ok(proxy.no_proxy(NoProxy::from_string(&settings.bypass.join(","))))
+}
+
+/// `Client::builder()` with the active proxy applied.
+pub fn client_builder() -> Result<ClientBuilder> {
+  client_builder_for(&current())
+}
+
+fn client_builder_for(settings: &ProxySettings) -> Result<ClientBuilder> {
+  let builder = reqwest::Client::builder();
+
+  if !settings.enabled {
+    return Ok(builder);
+  }
+
+  Ok(builder.proxy(build_proxy(settings)?))
+}
+
+#[cfg(test)]
+mod tests {
+  use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+  };
+
+  use super::*;
+  use crate::utils::encoding::encode_token;
+
+  /// Stand-in HTTP proxy: answers one request and hands back its head.
+  async fn proxy_stand_in() -> (u16, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = tokio::spawn(async move {
+      let (mut socket, _) = listener.accept().await.unwrap();
+      let mut head = Vec::new();
+      let mut buf = [0u8; 1024];
+      while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = socket.read(&mut buf).await.unwrap();
+        if n == 0 {
+          break;
+        }
+        head.extend_from_slice(&buf[..n]);
+      }
+      socket
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\nConnection: close\r\n\r\nvia-proxy")
+        .await
+        .unwrap();
+      String::from_utf8_lossy(&head).into_owned()
+    });
+
+    (port, handle)
+  }
+
+  fn http_proxy(port: u16) -> ProxySettings {
+    ProxySettings {
+      enabled: true,
+      scheme: ProxyScheme::Http,
+      host: "127.0.0.1".to_owned(),
+      port,
+      ..ProxySettings::default()
+    }
+  }
+
+  #[tokio::test]
+  async fn requests_go_through_the_proxy() {
+    let (port, proxy) = proxy_stand_in().await;
+    let mut settings = http_proxy(port);
+    settings.username = "player".to_owned();
+    settings.password = encode_token("secret");
+
+    let client = client_builder_for(&settings).unwrap().build().unwrap();
+    let body = client
+      .get("http://releases.invalid/index.json")
+      .send()
+      .await
+      .unwrap()
+      .text()
+      .await
+      .unwrap();
+    let head = proxy.await.unwrap();
+
+    assert_eq!(body, "via-proxy");
+    assert!(head.starts_with("GET http://releases.invalid/index.json HTTP/1.1\r\n"), "{}", head);
+    let auth = head.lines().find(|l| l.to_ascii_lowercase().starts_with("proxy-authorization:"));
+    // base64("player:secret")
+    assert_eq!(
+      auth.map(|l| l[l.find(':').unwrap() + 1..].trim()),
+      Some("Basic cGxheWVyOnNlY3JldA=="),
+      "{}",
+      head
+    );
+  }
+
+  #[tokio::test]
+  async fn bypassed_hosts_skip_the_proxy() {
+    let mut settings = http_proxy(9);
+    settings.bypass = vec!["127.0.0.1".to_owned()];
+    let (port, server) = proxy_stand_in().await;
+
+    let client = client_builder_for(&settings).unwrap().build().unwrap();
+    let body = client
+      .get(format!("http://127.0.0.1:{}/bg.jpg", port))
+      .send()
+      .await
+      .unwrap()
+      .text()
+      .await
+      .unwrap();
+
+    assert_eq!(body, "via-proxy");
+    assert!(server.await.unwrap().starts_with("GET /bg.jpg HTTP/1.1\r\n"));
+  }
+
+  #[test]
+  fn invalid_settings_are_rejected() {
+    let active = current();
+
+    assert!(set(&http_proxy(0)).is_err());
+    let mut no_host = http_proxy(8080);
+    no_host.host = " ".to_owned();
+    assert!(set(&no_host).is_err());
+
+    assert_eq!(current().host, active.host);
+    assert!(
+      validate(&ProxySettings {
+        port: 0,
+        ..ProxySettings::default()
+      })
+      .is_ok(),
+      "a disabled proxy is not checked"
+    );
+  }
+}
@@ -1,5 +1,3 @@
-export const COFF_FROM_COMPRESSED_SIZE = 0.3;
-
 /**
  * Default glob patterns for files/dirs to exclude when packing game data
  * or collecting patches. Shared between Pack and Releases views.
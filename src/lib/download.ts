@@ -4,7 +4,7 @@ import { selectedVersion, updateVersionProgress, removeDownloadState, versions }
 import { formatSpeedBytesPerSec } from '../utils/dwn';
 import { invoke } from '@tauri-apps/api/core';
 import { get } from 'svelte/store';
-import { expandedIndex, fetchLocalVersions, launcherDwnBytes, launcherDwnNeedUpdate, launcherDwnProgress, launcherDwnTotalBytes, launcherDwnVersion, localVersions } from '../store/main';
+import { downloadJobs, downloadThrottle, expandedIndex, fetchLocalVersions, launcherDwnBytes, launcherDwnNeedUpdate, launcherDwnProgress, launcherDwnTotalBytes, launcherDwnVersion, localVersions } from '../store/main';
 
 const unlisten: Map<string, (() => void)> = new Map();
 
@@ -27,8 +27,9 @@ export async function initDownloadListeners() {
       status,
     }));
   }));
-  unlisten.set('download-speed-status', await listen('download-speed-status', (event: Event<[string, string, number, number, number]>) => {
-    const [versionName, fileName, bytes, totalBytes, speed] = event.payload;
+  unlisten.set('download-speed-status', await listen('download-speed-status', (event: Event<[string, string, number, number, number, ThrottleStatus]>) => {
+    const [versionName, fileName, bytes, totalBytes, speed, throttle] = event.payload;
+    downloadThrottle.set(throttle);
 
     const [speedValue, sfxValue] = formatSpeedBytesPerSec(speed);
 
@@ -52,6 +53,7 @@ export async function initDownloadListeners() {
         speedValue,
         sfxValue,
         status: 1,
+        source: prev?.source,
       });
 
       // Incremental aggregates: adjust the totals by this file's delta
@@ -106,6 +108,7 @@ export async function initDownloadListeners() {
           speedValue: old?.speedValue || 0,
           sfxValue: old?.sfxValue || "",
           status: item.unpacked ? 3 : (old?.status || 0),
+          source: old?.source,
         });
       }
 
@@ -145,6 +148,28 @@ export async function initDownloadListeners() {
       };
     });
   }));
+  // Another provider took over a file after failures (or a new file started on the current one).
+  unlisten.set('download-file-source', await listen('download-file-source', (event: Event<DownloadFileSource>) => {
+    const { version_name, file_name, provider_id } = event.payload;
+
+    updateVersionProgress(version_name, (version) => {
+      const map = version.filesProgress ?? new Map();
+      const prev = map.get(file_name);
+      if (!prev) {
+        return {};
+      }
+      map.set(file_name, { ...prev, source: provider_id });
+      return { filesProgress: map };
+    });
+  }));
+  unlisten.set('download-jobs', await listen('download-jobs', (event: Event<DownloadJob[]>) => {
+    applyDownloadJobs(event.payload);
+  }));
+  try {
+    applyDownloadJobs(await invoke<DownloadJob[]>("get_download_jobs"));
+  } catch (e) {
+    console.error("get_download_jobs failed:", e);
+  }
   unlisten.set('cancel-download-version', await listen('cancel-download-version', (event: Event<string>) => {
     const versionName = event.payload;
 
@@ -194,3 +219,28 @@ export async function initDownloadListeners() {
     expandedIndex.set(null);
   }));
 }
+
+// Jobs restored after a restart run without a pending command on this side,
+// so the release rows follow the queue itself: active jobs are "in progress",
+// jobs that just stopped are shown as paused.
+function applyDownloadJobs(jobs: DownloadJob[]) {
+  const wasActive = new Set(get(downloadJobs).filter((j) => j.state === "running" || j.state === "queued").map((j) => j.id));
+  downloadJobs.set(jobs);
+
+  for (const job of jobs) {
+    if (job.kind.type !== "release") {
+      continue;
+    }
+    if (job.state === "running" || job.state === "queued") {
+      updateVersionProgress(job.version_name, () => ({
+        inProgress: true,
+        isStoped: false,
+      }));
+    } else if (wasActive.has(job.id)) {
+      updateVersionProgress(job.version_name, () => ({
+        inProgress: false,
+        isStoped: true,
+      }));
+    }
+  }
+}
@@ -1,7 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import type { Event } from "@tauri-apps/api/event";
-import { fontColor, connectStatus, providersWasInited, allowPackMod, versionsWillBeLoaded, appConfig, fetchLocalVersions, showDlgRestartApp, newLauncherVersionDownloaded, launcherDwnVersion, launcherDwnNeedUpdate, providers, radioApiProvider, moveProgress } from '../store/main';
+import { fontColor, connectStatus, providersWasInited, allowPackMod, versionsWillBeLoaded, appConfig, fetchLocalVersions, showDlgRestartApp, newLauncherVersionDownloaded, launcherDwnVersion, launcherDwnNeedUpdate, providers, radioApiProvider, moveProgress, fallbackApiProvider } from '../store/main';
 import { ConnectStatus, DownloadStatus } from "../consts";
 import { selectedVersion, versions } from '../store/upload';
 import { get } from 'svelte/store';
@@ -49,6 +49,16 @@ export async function initMainListeners() {
     moveProgress.setItem(version_name, event.payload);
   }));
 
+  unlisten.set('provider-switched', await listen('provider-switched', (event: Event<ProviderSwitch>) => {
+    console.log('provider-switched:', event.payload);
+
+    fallbackApiProvider.set(event.payload.reason === 'unavailable' ? event.payload.to : null);
+    invoke<[string, ProviderStatus][]>('get_api_providers_stats').then(result => {
+      result.sort((a, b) => (a[1].latency_ms ?? Number.MAX_SAFE_INTEGER) - (b[1].latency_ms ?? Number.MAX_SAFE_INTEGER));
+      providers.set(result)
+    });
+  }));
+
   unlisten.set('config-loaded', await listen('config-loaded', (event: Event<AppConfig>) => {
     if (event.payload.selected_provider_id) {
       radioApiProvider.set(event.payload.selected_provider_id);
@@ -1,6 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import { sep } from "@tauri-apps/api/path";
-import { appConfig, providers, providersWasInited, radioApiProvider, versionsWillBeLoaded } from "../store/main";
+import { appConfig, fallbackApiProvider, providers, providersWasInited, radioApiProvider, versionsWillBeLoaded } from "../store/main";
 import { downloadStates, restoreDownloadState, versions } from "../store/upload";
 import { prepareVersionItem } from "./main";
 import { get } from "svelte/store";
@@ -28,6 +28,7 @@ export async function switchProvider(id: string): Promise<void> {
     // Switch backend first — must complete before get_available_versions
     // to avoid getting the old provider's cached release list.
     await invoke("set_current_api_provider", { provider: id });
+    fallbackApiProvider.set(null);
 
     const data = await invoke<Version[]>("get_available_versions");
     const separ = await sep();
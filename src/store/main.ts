@@ -38,6 +38,12 @@ export const launcherDwnProgress = writable(0);
 
 export const providers = writable<[string, ProviderStatus][]>([]);
 export const radioApiProvider = writable<string>("github");
+// Provider used at runtime instead of the selected one (health monitor failover), null = the selected one.
+export const fallbackApiProvider = writable<string | null>(null);
+// Speed cap reported with the last download progress event.
+export const downloadThrottle = writable<ThrottleStatus | null>(null);
+// Download queue as persisted by the backend, in priority order.
+export const downloadJobs = writable<DownloadJob[]>([]);
 
 export const moveProgress = createMapStore<string, ProgressPayload>();
 
@@ -41,12 +41,27 @@ declare interface VersionFileDownload {
   sfxValue: string;
   // 0 - в очереди на загрузку; 1 - загружается; 2 - распаковывается; 3 - скачаен и распакован
   status: number;
+  // Provider serving the file ("download-file-source"), set once it is known.
+  source?: string;
+}
+declare interface DownloadFileSource {
+  version_name: string;
+  file_name: string;
+  provider_id: string;
 }
 declare interface InstalledPatch {
   name: string;
   provider_id: string;
   installed_at?: string | null;
   notes?: string | null;
+  // Present when the patch can be uninstalled.
+  backup?: PatchBackup | null;
+  // Cumulative patch: the patch tags it replaced.
+  supersedes?: string[];
+}
+declare interface PatchBackup {
+  backed_up: string[];
+  added: string[];
 }
 declare interface Version {
   id: number;
@@ -96,6 +111,7 @@ declare interface FileProgress {
   is_unpacked: boolean;
   size: number;
   total_size: number;
+  sha256?: string | null;
 }
 declare interface RunParams {
   cmd_params: string;
@@ -120,6 +136,11 @@ declare interface RunParams {
   font_legacy: boolean;
   [index: string]: unknown;
 }
+// Fields of the distribution profile the UI uses (`get_distribution_profile`).
+declare interface DistributionProfile {
+  name: string;
+  index_repo_name: string;
+}
 declare interface AppConfig {
   latest_pid: number;
   first_run: boolean;
@@ -149,6 +170,66 @@ declare interface AppConfig {
   tokens: Dict<string>;
   progress_upload?: VersionProgressUpload;
   progress_download: Dict<VersionProgress>;
+  // Gitea/Forgejo mirror web root, overrides the distribution profile; applied on start
+  gitea_host?: string | null;
+  // static mirror base url, overrides the distribution profile; applied on start
+  mirror_base_url?: string | null;
+  proxy: ProxySettings;
+  // 0 = default worker count
+  download_workers: number;
+  // bytes per second, 0 = unlimited
+  download_speed_limit: number;
+  // overrides download_speed_limit inside the windows, first match wins
+  bandwidth_schedule: BandwidthRule[];
+  // 0 = one per CPU core
+  unpack_threads: number;
+  // priority order
+  download_jobs: DownloadJob[];
+}
+declare interface DownloadJob {
+  // version name for releases, "patch:<version>:<patch>" for patches
+  id: string;
+  version_name: string;
+  kind: { type: "release" } | { type: "patch"; patch_name: string } | { type: "patch_chain"; patches: string[] };
+  state: "queued" | "running" | "paused" | "failed";
+  error?: string | null;
+}
+// peak space a download/install needs per volume (reserve included)
+declare interface VolumeNeed {
+  // planned paths on this volume
+  paths: string[];
+  needed: number;
+  available: number;
+}
+declare interface SpacePlan {
+  volumes: VolumeNeed[];
+}
+declare interface BandwidthRule {
+  // local time, "HH:MM"; `to` before `from` spans midnight
+  from: string;
+  to: string;
+  // bytes per second, 0 = unlimited
+  limit: number;
+}
+declare interface ThrottleStatus {
+  // bytes per second, 0 = unlimited
+  limit: number;
+  scheduled: boolean;
+}
+declare interface ProviderSwitch {
+  from?: string | null;
+  to: string;
+  reason: "unavailable" | "restored";
+}
+declare interface ProxySettings {
+  enabled: boolean;
+  scheme: "http" | "https" | "socks5";
+  host: string;
+  port: number;
+  username: string;
+  // Always empty on the webview side; set it via `set_proxy_settings(proxy, password)`.
+  password: string;
+  bypass: string[];
 }
 
 
@@ -160,6 +241,13 @@ declare interface UploadManifest {
 declare interface ReleaseManifestFile {
   name: string;
   size: number;
+  sha256?: string | null;
+}
+declare interface ReleaseManifestEntry {
+  path: string;
+  size: number;
+  sha256: string;
+  archive: string;
 }
 declare interface ReleaseManifest {
   total_files_count: number;
@@ -172,12 +260,53 @@ declare interface ReleaseManifest {
   base_patch?: string;
   base_release_tag?: string;
   deleted_files: string[];
+  // Per-file list of the unpacked game, used by verify / repair.
+  entries?: ReleaseManifestEntry[];
+  deltas?: PatchDelta[];
+  supersedes?: string[];
+}
+// Binary delta of a modified file in a patch; `archive` is the fullN.zip
+// with the whole file for installs whose local copy is not the base.
+declare interface PatchDelta {
+  path: string;
+  base_size: number;
+  base_sha256: string;
+  size: number;
+  sha256: string;
+  archive: string;
+}
+declare interface VerifyReport {
+  version_name: string;
+  checked: number;
+  missing: string[];
+  modified: string[];
+  extra: string[];
 }
 
 
+declare interface RateLimitStatus {
+  limit: number | null;
+  remaining: number | null;
+  reset_at: number | null;
+  blocked_until: number | null;
+}
+
 declare interface ProviderStatus {
   available: boolean;
   latency_ms: number | null;
+  rate_limit: RateLimitStatus | null;
+}
+
+/** Rolling statistics of the background provider health checks. */
+declare interface ProviderHealth {
+  avg_latency_ms: number | null;
+  /** Share of failed checks in the window, 0..1. */
+  error_rate: number;
+  consecutive_failures: number;
+  consecutive_successes: number;
+  checks: number;
+  /** Unix seconds of the last check. */
+  last_checked_at: number | null;
 }
 
 declare interface ProgressPayload {
@@ -230,6 +359,10 @@ declare interface PatchCollectResult {
   repos: RepoPatchReport[];
   changed: number;
   deleted: number;
+  deltas: PatchDelta[];
+  full_dir: string | null;
+  // Cumulative collect: the patch tags the patch replaces.
+  supersedes: string[];
 }
 
 // Partial update patches: upload result (stage 2)
@@ -272,6 +405,8 @@ declare interface PatchInfo {
   notes: string | null;
   size: number | null;
   is_next: boolean;
+  // Cumulative patch: the patch tags it replaces.
+  supersedes: string[];
 }
 declare interface PatchCheckResult {
   patches: PatchInfo[];